@@ -61,6 +61,7 @@ fn main() -> anyhow::Result<()> {
         },
         credentials: AtcoderRetrieveSampleTestCasesCredentials {
             username_and_password: &mut username_and_password(credentials),
+            retry_login: false,
         },
         full: if full {
             Some(RetrieveFullTestCases {
@@ -72,15 +73,22 @@ fn main() -> anyhow::Result<()> {
                         CredentialsVia::Env => env::var("DROPBOX_ACCESS_TOKEN")?,
                     },
                 },
+                max_download_size: None,
             })
         } else {
             None
         },
+        strict: false,
+        statement: false,
+        statement_language: Default::default(),
+        editorial: false,
         cookie_storage: CookieStorage {
             cookie_store: CookieStore::default(),
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
@@ -68,11 +68,14 @@ fn main() -> anyhow::Result<()> {
         target: ProblemInContest::Index { contest, problem },
         credentials: AtcoderSubmitCredentials {
             username_and_password: &mut username_and_password(credentials),
+            retry_login: false,
         },
         language_id,
+        language_name: None,
         code: fs::read_to_string(&file)
             .with_context(|| format!("Failed to read {}", file.display()))?,
         watch_submission: !no_watch,
+        watch_timeout: None,
         cookie_storage: CookieStorage {
             cookie_store: CookieStore::default(),
             on_update: Box::new(move |cookie_store| -> _ {
@@ -90,6 +93,8 @@ fn main() -> anyhow::Result<()> {
             }),
         },
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
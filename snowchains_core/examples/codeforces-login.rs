@@ -48,6 +48,8 @@ fn main() -> anyhow::Result<()> {
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
@@ -58,11 +58,17 @@ fn main() -> anyhow::Result<()> {
             username_and_password: &mut username_and_password(credentials),
         },
         full: None,
+        strict: false,
+        statement: false,
+        statement_language: Default::default(),
+        editorial: false,
         cookie_storage: CookieStorage {
             cookie_store: CookieStore::default(),
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
@@ -1,7 +1,10 @@
 use anyhow::{bail, Context as _};
 use indicatif::ProgressDrawTarget;
 use maplit::btreemap;
-use snowchains_core::{judge::CommandExpression, testsuite::TestSuite};
+use snowchains_core::{
+    judge::{CommandExpression, JudgeOptions, PrintOptions},
+    testsuite::TestSuite,
+};
 use std::{env, ffi::OsString, fs, path::PathBuf};
 use structopt::StructOpt;
 use termcolor::BufferedStandardStream;
@@ -54,6 +57,7 @@ fn main() -> anyhow::Result<()> {
             env: btreemap!(),
         },
         &test_cases,
+        JudgeOptions::default(),
     )?;
 
     let stdout = BufferedStandardStream::stdout(if atty::is(atty::Stream::Stdout) {
@@ -63,7 +67,13 @@ fn main() -> anyhow::Result<()> {
     });
 
     eprintln!();
-    outcome.print_pretty(stdout, None)?;
+    outcome.print_pretty(
+        stdout,
+        PrintOptions {
+            diff_context: Some(3),
+            ..PrintOptions::default()
+        },
+    )?;
 
     Ok(())
 }
@@ -33,6 +33,8 @@ fn main() -> anyhow::Result<()> {
         credentials: (),
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
@@ -81,12 +81,19 @@ fn main() -> anyhow::Result<()> {
                         CredentialsVia::Env => env::var("YUKICODER_API_KEY")?,
                     },
                 },
+                max_download_size: None,
             })
         } else {
             None
         },
+        strict: false,
+        statement: false,
+        statement_language: Default::default(),
+        editorial: false,
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
@@ -67,11 +67,15 @@ fn main() -> anyhow::Result<()> {
         },
         credentials: YukicoderSubmitCredentials { api_key },
         language_id,
+        language_name: None,
         code: fs::read_to_string(&file)
             .with_context(|| format!("Failed to read {}", file.display()))?,
         watch_submission: false,
+        watch_timeout: None,
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell::new(),
     })?;
 
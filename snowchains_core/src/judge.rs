@@ -1,22 +1,76 @@
-use crate::testsuite::{BatchTestCase, CheckerShell, ExpectedOutput};
-use anyhow::{anyhow, bail};
+use crate::testsuite::{
+    crlf_to_lf, BatchTestCase, BatchTestSuite, CheckerShell, ExpectedOutput, Generator,
+    InputFilter, InteractiveTestCase, PartialBatchTestCase, Reference, Tester,
+};
+use anyhow::{anyhow, bail, Context as _};
 use futures_util::{select, FutureExt as _};
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use similar::{Algorithm, ChangeTag, DiffTag, TextDiff};
 use std::{
+    borrow::Cow,
     cmp,
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     env,
     ffi::{OsStr, OsString},
+    fmt,
     future::Future,
-    io, iter,
+    io, iter, mem,
+    ops::Range,
     path::{Path, PathBuf},
     process::{ExitStatus, Output, Stdio},
     sync::Arc,
     time::{Duration, Instant},
 };
+use strum::{EnumString, EnumVariantNames};
 use termcolor::{Color, WriteColor};
-use tokio::io::AsyncWriteExt as _;
-use unicode_width::UnicodeWidthStr as _;
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _};
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr as _};
+
+/// Options for [`JudgeOutcome::print_pretty`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PrintOptions {
+    /// Texts longer than this are printed as their sizes.
+    pub display_limit: Option<usize>,
+    /// Prints the details of accepted test cases as well.
+    pub verbose: bool,
+    /// Number of unchanged lines shown around each difference between the expected output and
+    /// the actual one. `None` disables the diff.
+    pub diff_context: Option<usize>,
+    /// Renders trailing spaces as `·`.
+    pub visible_whitespace: bool,
+    /// Omits accepted test cases entirely. Takes precedence over `verbose`.
+    pub quiet: bool,
+    /// Granularity of the diff.
+    pub diff_algorithm: DiffAlgorithm,
+    /// Lines of texts and diffs wider than this are truncated with `…`, except for the changed
+    /// lines of diffs. `None` disables it.
+    pub width: Option<usize>,
+    /// Omits the elapsed times so that the output does not change between runs.
+    pub hide_elapsed: bool,
+}
+
+/// Options for [`judge`] and [`judge_interactive`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JudgeOptions {
+    /// Stops at the first failure. The rest of the test cases are skipped and not contained in
+    /// the outcome.
+    pub fail_fast: bool,
+    /// Maximum number of the test cases run at once. `None` means the number of the CPUs. With
+    /// `Some(1)`, the test cases are run one by one in order. [`judge_interactive`] ignores it
+    /// and always runs them one by one.
+    pub jobs: Option<usize>,
+}
+
+/// Granularity of the diff between the expected output and the actual one.
+#[derive(EnumString, EnumVariantNames, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum DiffAlgorithm {
+    /// Marks whole lines.
+    #[default]
+    Line,
+    /// Marks lines, and highlights the words that differ within each pair of changed lines.
+    Word,
+}
 
 #[non_exhaustive]
 #[derive(Debug, Clone)]
@@ -25,13 +79,31 @@ pub struct JudgeOutcome {
 }
 
 impl JudgeOutcome {
-    pub fn print_pretty<W: WriteColor>(
-        &self,
-        mut wtr: W,
-        display_limit: Option<usize>,
-    ) -> io::Result<()> {
+    /// Prints the verdicts.
+    ///
+    /// Details are printed only for failed test cases unless `verbose` is `true`.
+    pub fn print_pretty<W: WriteColor>(&self, mut wtr: W, opts: PrintOptions) -> io::Result<()> {
+        let PrintOptions {
+            display_limit,
+            verbose,
+            diff_context,
+            visible_whitespace,
+            quiet,
+            diff_algorithm,
+            width,
+            hide_elapsed,
+        } = opts;
+
+        let exceeds_display_limit = |text: &str| matches!(display_limit, Some(l) if l < text.len());
+
+        let mut first = true;
+
         for (i, verdict) in self.verdicts.iter().enumerate() {
-            if i > 0 {
+            if quiet && matches!(verdict, Verdict::Accepted { .. }) {
+                continue;
+            }
+
+            if !mem::replace(&mut first, false) {
                 writeln!(wtr)?;
             }
 
@@ -44,87 +116,358 @@ impl JudgeOutcome {
             )?;
 
             wtr.set_color(color_spec!(Bold, Fg(verdict.summary_color())))?;
-            writeln!(wtr, "{}", verdict.summary())?;
+            writeln!(wtr, "{}", verdict.summary(hide_elapsed))?;
             wtr.reset()?;
 
-            let mut write_text =
-                |header: &str, text: &str, highlight_numbers: bool| -> io::Result<()> {
-                    wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
-                    writeln!(wtr, "{}", header)?;
-                    wtr.reset()?;
+            if !verbose && matches!(verdict, Verdict::Accepted { .. }) {
+                continue;
+            }
 
-                    if text.is_empty() {
-                        wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
-                        writeln!(wtr, "EMPTY")?;
-                        return wtr.reset();
-                    }
+            let write_text = |wtr: &mut W,
+                              header: &str,
+                              text: &str,
+                              highlight_numbers: bool|
+             -> io::Result<()> {
+                wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+                writeln!(wtr, "{}", header)?;
+                wtr.reset()?;
+
+                if text.is_empty() {
+                    wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                    writeln!(wtr, "EMPTY")?;
+                    return wtr.reset();
+                }
 
-                    if matches!(display_limit, Some(l) if l < text.len()) {
-                        wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
-                        writeln!(wtr, "{} B", text.len())?;
-                        return wtr.reset();
-                    }
+                if exceeds_display_limit(text) {
+                    wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                    writeln!(wtr, "{} B", text.len())?;
+                    return wtr.reset();
+                }
 
-                    for token in parse_to_tokens(text, highlight_numbers) {
-                        match token {
-                            Token::SpcLf(s) | Token::Plain(s) => wtr.write_all(s.as_ref())?,
-                            Token::Cr(n) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                (0..n).try_for_each(|_| wtr.write_all(b"\\r"))?;
-                                wtr.reset()?;
-                            }
-                            Token::Tab(n) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                (0..n).try_for_each(|_| wtr.write_all(b"\\t"))?;
-                                wtr.reset()?;
-                            }
-                            Token::OtherWhitespaceControl(s) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                write!(wtr, "{}", s.escape_unicode())?;
-                                wtr.reset()?;
-                            }
-                            Token::HighlightedNumber(s) => {
-                                wtr.set_color(color_spec!(Fg(Color::Cyan)))?;
-                                wtr.write_all(s.as_ref())?;
-                                wtr.reset()?;
-                            }
-                        }
-                    }
+                write_tokens(
+                    &mut *wtr,
+                    &truncate_lines(text, width),
+                    highlight_numbers,
+                    visible_whitespace,
+                )?;
 
-                    if !text.ends_with('\n') {
-                        wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                        writeln!(wtr, "⏎")?;
-                        wtr.reset()?;
-                    }
+                if !text.ends_with('\n') {
+                    wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                    writeln!(wtr, "⏎")?;
+                    wtr.reset()?;
+                }
 
-                    Ok(())
-                };
+                Ok(())
+            };
 
-            write_text("stdin:", verdict.stdin(), false)?;
+            write_text(&mut wtr, "stdin:", verdict.stdin(), false)?;
             if let Some(expected) = verdict.expected().expected_stdout() {
-                write_text("expected:", expected, verdict.expected().is_float())?;
+                write_text(
+                    &mut wtr,
+                    "expected:",
+                    expected,
+                    verdict.expected().is_float(),
+                )?;
             } else if let Some(example) = verdict.expected().example() {
-                write_text("example:", example, verdict.expected().is_float())?;
+                write_text(&mut wtr, "example:", example, verdict.expected().is_float())?;
             }
             if let Some(stdout) = verdict.stdout() {
-                write_text("actual:", stdout, verdict.expected().is_float())?;
+                write_text(&mut wtr, "actual:", stdout, verdict.expected().is_float())?;
+            }
+            if let (Verdict::WrongAnswer { stdout, .. }, Some(expected), Some(context)) =
+                (verdict, verdict.expected().expected_stdout(), diff_context)
+            {
+                if !(exceeds_display_limit(expected) || exceeds_display_limit(stdout)) {
+                    write_diff(
+                        &mut wtr,
+                        expected,
+                        stdout,
+                        context,
+                        diff_algorithm,
+                        width,
+                        verdict.expected().is_float(),
+                        visible_whitespace,
+                    )?;
+                }
+            }
+            if let Some(expected_stderr) = verdict.expected_stderr() {
+                write_text(&mut wtr, "expected stderr:", expected_stderr, false)?;
             }
-            if let Some(stderr) = verdict.stderr().filter(|s| !s.is_empty()) {
-                write_text("stderr:", stderr, verdict.expected().is_float())?;
+            if let Some(stderr) = verdict.stderr().filter(|s| verbose || !s.is_empty()) {
+                write_text(&mut wtr, "stderr:", stderr, verdict.expected().is_float())?;
             }
             if let Some(checker_stdout) = verdict.checker_stdout().filter(|s| !s.is_empty()) {
-                write_text("checker stdout: ", checker_stdout, false)?;
+                write_text(&mut wtr, "checker stdout: ", checker_stdout, false)?;
             }
             if let Some(checker_stderr) = verdict.checker_stderr().filter(|s| !s.is_empty()) {
-                write_text("checker stderr: ", checker_stderr, false)?;
+                write_text(&mut wtr, "checker stderr: ", checker_stderr, false)?;
             }
             if let Some(wrong_answer_note) = verdict.wrong_answer_note() {
-                write_text("note: ", &(wrong_answer_note.to_string() + "\n"), false)?;
+                write_text(
+                    &mut wtr,
+                    "note: ",
+                    &(wrong_answer_note.to_string() + "\n"),
+                    false,
+                )?;
             }
         }
 
         return wtr.flush();
 
+        #[allow(clippy::too_many_arguments)]
+        fn write_diff(
+            mut wtr: impl WriteColor,
+            expected: &str,
+            actual: &str,
+            context: usize,
+            algorithm: DiffAlgorithm,
+            width: Option<usize>,
+            highlight_numbers: bool,
+            visible_whitespace: bool,
+        ) -> io::Result<()> {
+            // One column for the sign.
+            let width = width.map(|w| w.saturating_sub(1));
+
+            let diff = TextDiff::from_lines(expected, actual);
+            let groups = diff.grouped_ops(context);
+
+            if groups.is_empty() {
+                return Ok(());
+            }
+
+            wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+            writeln!(wtr, "diff (-expected +actual):")?;
+            wtr.reset()?;
+
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    wtr.set_color(color_spec!(Fg(Color::Cyan)))?;
+                    writeln!(wtr, "...")?;
+                    wtr.reset()?;
+                }
+
+                for op in group {
+                    if let (DiffAlgorithm::Word, (DiffTag::Replace, old_range, new_range)) =
+                        (algorithm, op.as_tag_tuple())
+                    {
+                        let old_lines = &diff.old_slices()[old_range];
+                        let new_lines = &diff.new_slices()[new_range];
+
+                        for (sign, color, lines, partners) in &[
+                            ('-', Color::Red, old_lines, new_lines),
+                            ('+', Color::Green, new_lines, old_lines),
+                        ] {
+                            for (i, line) in lines.iter().enumerate() {
+                                wtr.set_color(color_spec!(Bold, Fg(*color)))?;
+                                write!(wtr, "{}", sign)?;
+                                wtr.reset()?;
+
+                                write_word_diff(
+                                    &mut wtr,
+                                    line,
+                                    partners.get(i).copied(),
+                                    *color,
+                                    highlight_numbers,
+                                    visible_whitespace,
+                                )?;
+
+                                if !line.ends_with('\n') {
+                                    wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                                    writeln!(wtr, "⏎")?;
+                                    wtr.reset()?;
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    for change in diff.iter_changes(op) {
+                        let (sign, color) = match change.tag() {
+                            ChangeTag::Equal => (' ', None),
+                            ChangeTag::Delete => ('-', Some(Color::Red)),
+                            ChangeTag::Insert => ('+', Some(Color::Green)),
+                        };
+
+                        if let Some(color) = color {
+                            wtr.set_color(color_spec!(Bold, Fg(color)))?;
+                        }
+                        write!(wtr, "{}", sign)?;
+                        wtr.reset()?;
+
+                        // Only the unchanged lines are truncated, so that the difference is
+                        // not cut off.
+                        let value = match change.tag() {
+                            ChangeTag::Equal => truncate_lines(change.value(), width),
+                            ChangeTag::Delete | ChangeTag::Insert => Cow::Borrowed(change.value()),
+                        };
+
+                        write_tokens(&mut wtr, &value, highlight_numbers, visible_whitespace)?;
+
+                        if change.missing_newline() {
+                            wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                            writeln!(wtr, "⏎")?;
+                            wtr.reset()?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Writes `line`, highlighting the words not in the LCS with `partner`.
+        ///
+        /// Falls back to writing `line` as it is if there are too many words to compare.
+        fn write_word_diff(
+            mut wtr: impl WriteColor,
+            line: &str,
+            partner: Option<&str>,
+            color: Color,
+            highlight_numbers: bool,
+            visible_whitespace: bool,
+        ) -> io::Result<()> {
+            const MAX_WORD_PAIRS: usize = 1 << 20;
+
+            let words = split_words(line);
+            let partner_words = partner.map(split_words).unwrap_or_default();
+
+            if partner.is_none() || words.len() * partner_words.len() > MAX_WORD_PAIRS {
+                return write_tokens(wtr, line, highlight_numbers, visible_whitespace);
+            }
+
+            let mut changed = vec![false; words.len()];
+            for op in similar::capture_diff_slices(Algorithm::Lcs, &words, &partner_words) {
+                if let (DiffTag::Delete | DiffTag::Replace, range, _) = op.as_tag_tuple() {
+                    changed[range].iter_mut().for_each(|c| *c = true);
+                }
+            }
+
+            for (i, (word, changed)) in words.iter().zip(changed).enumerate() {
+                if changed && !word.trim().is_empty() {
+                    wtr.set_color(color_spec!(Bold, Underline, Fg(color)))?;
+                    wtr.write_all(word.as_ref())?;
+                    wtr.reset()?;
+                } else {
+                    let at_end = i + 1 == words.len();
+                    write_tokens(
+                        &mut wtr,
+                        word,
+                        highlight_numbers,
+                        visible_whitespace && at_end,
+                    )?;
+                }
+            }
+
+            if line.ends_with('\n') {
+                wtr.write_all(b"\n")?;
+            }
+            Ok(())
+        }
+
+        /// Truncates each line of `text` wider than `width` columns, marking it with `…`.
+        fn truncate_lines(text: &str, width: Option<usize>) -> Cow<'_, str> {
+            let width = match width {
+                Some(width) if text.lines().any(|line| line.width() > width) => width,
+                _ => return Cow::Borrowed(text),
+            };
+
+            let mut acc = "".to_owned();
+            for line in text.split_inclusive('\n') {
+                let (line, lf) = match line.strip_suffix('\n') {
+                    Some(line) => (line, "\n"),
+                    None => (line, ""),
+                };
+                if line.width() > width {
+                    let mut columns = 0;
+                    for c in line.chars() {
+                        columns += c.width().unwrap_or(0);
+                        if columns + 1 > width {
+                            break;
+                        }
+                        acc.push(c);
+                    }
+                    acc += "…";
+                } else {
+                    acc += line;
+                }
+                acc += lf;
+            }
+            Cow::Owned(acc)
+        }
+
+        /// Splits `line` into runs of whitespace and non-whitespace characters, without the `\n`.
+        fn split_words(line: &str) -> Vec<&str> {
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            let mut words = vec![];
+            let mut start = 0;
+            let mut prev_is_whitespace = None;
+            for (i, c) in line.char_indices() {
+                if prev_is_whitespace == Some(!c.is_whitespace()) {
+                    words.push(&line[start..i]);
+                    start = i;
+                }
+                prev_is_whitespace = Some(c.is_whitespace());
+            }
+            if start < line.len() {
+                words.push(&line[start..]);
+            }
+            words
+        }
+
+        fn write_tokens(
+            mut wtr: impl WriteColor,
+            text: &str,
+            highlight_numbers: bool,
+            visible_whitespace: bool,
+        ) -> io::Result<()> {
+            let mut tokens = parse_to_tokens(text, highlight_numbers)
+                .into_iter()
+                .peekable();
+
+            while let Some(token) = tokens.next() {
+                match token {
+                    Token::SpcLf(s) if visible_whitespace => {
+                        let at_end = tokens.peek().is_none();
+                        let mut lines = s.split('\n').peekable();
+                        while let Some(spaces) = lines.next() {
+                            let followed_by_lf = lines.peek().is_some();
+                            if !spaces.is_empty() && (followed_by_lf || at_end) {
+                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                                (0..spaces.len()).try_for_each(|_| write!(wtr, "·"))?;
+                                wtr.reset()?;
+                            } else {
+                                wtr.write_all(spaces.as_ref())?;
+                            }
+                            if followed_by_lf {
+                                wtr.write_all(b"\n")?;
+                            }
+                        }
+                    }
+                    Token::SpcLf(s) | Token::Plain(s) => wtr.write_all(s.as_ref())?,
+                    Token::Cr(n) => {
+                        wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                        (0..n).try_for_each(|_| wtr.write_all(b"\\r"))?;
+                        wtr.reset()?;
+                    }
+                    Token::Tab(n) => {
+                        wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                        (0..n).try_for_each(|_| wtr.write_all(b"\\t"))?;
+                        wtr.reset()?;
+                    }
+                    Token::OtherWhitespaceControl(s) => {
+                        wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                        write!(wtr, "{}", s.escape_unicode())?;
+                        wtr.reset()?;
+                    }
+                    Token::HighlightedNumber(s) => {
+                        wtr.set_color(color_spec!(Fg(Color::Cyan)))?;
+                        wtr.write_all(s.as_ref())?;
+                        wtr.reset()?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
         #[derive(Debug)]
         enum Token<'a> {
             SpcLf(&'a str),
@@ -199,6 +542,18 @@ impl JudgeOutcome {
         }
     }
 
+    /// Keeps only the verdicts of `kinds`.
+    pub fn filter_by_kind(&self, kinds: &[VerdictKind]) -> Self {
+        let verdicts = self
+            .verdicts
+            .iter()
+            .filter(|v| kinds.contains(&v.kind()))
+            .cloned()
+            .collect();
+        Self { verdicts }
+    }
+
+    /// Returns [`JudgeFailure`] if any of the test cases failed.
     pub fn error_on_fail(&self) -> anyhow::Result<()> {
         let fails = self
             .verdicts
@@ -207,18 +562,49 @@ impl JudgeOutcome {
             .count();
 
         if fails > 0 {
-            bail!(
-                "{}/{} test{} failed",
+            bail!(JudgeFailure {
                 fails,
-                self.verdicts.len(),
-                if fails == 0 { "" } else { "s" }
-            );
+                total: self.verdicts.len(),
+            });
         }
 
         Ok(())
     }
 }
 
+/// Some of the test cases failed.
+#[derive(Debug, Clone, Copy)]
+pub struct JudgeFailure {
+    fails: usize,
+    total: usize,
+}
+
+impl fmt::Display for JudgeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} test{} failed",
+            self.fails,
+            self.total,
+            if self.fails == 0 { "" } else { "s" },
+        )
+    }
+}
+
+impl std::error::Error for JudgeFailure {}
+
+/// Judging was interrupted by Ctrl-C. The processes spawned for it have been killed.
+#[derive(Debug, Clone, Copy)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Interrupted by Ctrl-C")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
 #[derive(Debug, Clone)]
 pub enum Verdict {
     Accepted {
@@ -238,6 +624,8 @@ pub enum Verdict {
         checker_stdout: Arc<str>,
         checker_stderr: Arc<str>,
         expected: ExpectedOutput,
+        /// Set if stdout was accepted but stderr was not.
+        expected_stderr: Option<Arc<str>>,
         note: Option<WrongAnswerNote>,
     },
     RuntimeError {
@@ -258,6 +646,15 @@ pub enum Verdict {
 }
 
 impl Verdict {
+    pub fn kind(&self) -> VerdictKind {
+        match self {
+            Self::Accepted { .. } => VerdictKind::Accepted,
+            Self::WrongAnswer { .. } => VerdictKind::WrongAnswer,
+            Self::RuntimeError { .. } => VerdictKind::RuntimeError,
+            Self::TimelimitExceeded { .. } => VerdictKind::TimelimitExceeded,
+        }
+    }
+
     fn test_case_name(&self) -> Option<&str> {
         match self {
             Verdict::Accepted { test_case_name, .. }
@@ -317,6 +714,15 @@ impl Verdict {
         }
     }
 
+    fn expected_stderr(&self) -> Option<&str> {
+        match self {
+            Verdict::WrongAnswer {
+                expected_stderr, ..
+            } => expected_stderr.as_deref(),
+            _ => None,
+        }
+    }
+
     fn wrong_answer_note(&self) -> Option<WrongAnswerNote> {
         match *self {
             Self::WrongAnswer { note, .. } => note,
@@ -324,15 +730,20 @@ impl Verdict {
         }
     }
 
-    fn summary(&self) -> String {
+    fn summary(&self, hide_elapsed: bool) -> String {
         match self {
+            Self::Accepted { .. } if hide_elapsed => "Accepted".to_owned(),
             Self::Accepted { elapsed, .. } => format!("Accepted ({} ms)", elapsed.as_millis()),
             Self::TimelimitExceeded { timelimit, .. } => {
                 format!("Timelimit Exceeded ({} ms)", timelimit.as_millis())
             }
+            Self::WrongAnswer { .. } if hide_elapsed => "Wrong Answer".to_owned(),
             Self::WrongAnswer { elapsed, .. } => {
                 format!("Wrong Answer ({} ms)", elapsed.as_millis())
             }
+            Self::RuntimeError { status, .. } if hide_elapsed => {
+                format!("Runtime Error ({})", status)
+            }
             Self::RuntimeError {
                 elapsed, status, ..
             } => format!("Runtime Error ({} ms, {})", elapsed.as_millis(), status),
@@ -356,12 +767,30 @@ impl Verdict {
     }
 }
 
+#[derive(EnumString, strum::Display, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VerdictKind {
+    #[strum(serialize = "AC")]
+    Accepted,
+    #[strum(serialize = "WA")]
+    WrongAnswer,
+    #[strum(serialize = "RE")]
+    RuntimeError,
+    #[strum(serialize = "TLE")]
+    TimelimitExceeded,
+}
+
+impl VerdictKind {
+    pub const VARIANTS: &'static [&'static str] = &["AC", "WA", "RE", "TLE"];
+}
+
 #[derive(Copy, Clone, Debug, derive_more::Display)]
 pub enum WrongAnswerNote {
     #[display(
         fmt = "whitespace-separated words matched. try setting `match` to `SplitWhitespace`"
     )]
     WordsMatched,
+    #[display(fmt = "stdout matched, but stderr did not")]
+    StderrMismatched,
 }
 
 #[derive(Debug, Clone)]
@@ -375,6 +804,7 @@ pub struct CommandExpression {
 impl CommandExpression {
     async fn build(
         &self,
+        tmpdir: &Path,
         stdin: Option<&Path>,
         stdout: &Path,
         stderr: &Path,
@@ -389,20 +819,58 @@ impl CommandExpression {
         let stderr = tokio::fs::File::create(stderr).await?.into_std().await;
         cmd.args(&self.args)
             .current_dir(&self.cwd)
+            .env("TMPDIR", tmpdir)
             .envs(&self.env)
             .stdin(stdin)
             .stdout(stdout)
-            .stderr(stderr);
+            .stderr(stderr)
+            .kill_on_drop(true);
+        new_process_group(&mut cmd);
         Ok(cmd)
     }
 }
 
+/// Makes the child the leader of a new process group on Unix, for [`ProcessGroup`].
+fn new_process_group(cmd: &mut tokio::process::Command) -> &mut tokio::process::Command {
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setpgid(0, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    cmd
+}
+
+/// Kills the process group of a child spawned with [`new_process_group`] when dropped, so that
+/// the processes it spawned do not outlive it.
+///
+/// This is no-op on Windows, where only the child itself is killed by `kill_on_drop`.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.0 {
+            unsafe {
+                libc::killpg(pgid as _, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Judges `cmd` on `test_cases` in parallel.
 pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
     draw_target: ProgressDrawTarget,
     ctrl_c: fn() -> C,
     cmd: &CommandExpression,
     test_cases: &[BatchTestCase],
+    opts: JudgeOptions,
 ) -> anyhow::Result<JudgeOutcome> {
+    let JudgeOptions { fail_fast, jobs } = opts;
+
     let cmd = Arc::new(cmd.clone());
     let num_test_cases = test_cases.len();
 
@@ -413,23 +881,17 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
         .max()
         .unwrap_or(0);
 
-    let bash_exe = {
-        static GIT_BASH: &str = r"C:\Program Files\Git\bin\bash.exe";
-
-        let bash_exe = if cfg!(windows) && Path::new(GIT_BASH).exists() {
-            GIT_BASH
-        } else {
-            "bash"
-        };
-        which::which_in(bash_exe, env::var_os("PATH"), &cmd.cwd)
-            .map_err(|_| anyhow!("`{}` not found", bash_exe))?
-    };
+    let bash_exe = bash_exe(&cmd.cwd)?;
 
     let tempdir = tempfile::Builder::new()
         .prefix("snowchains-core-juding-")
         .tempdir()?;
     let tempdir_path = tempdir.path().to_owned();
 
+    let reference_outputs = Arc::new(ReferenceOutputs::default());
+
+    let jobs = jobs.unwrap_or_else(num_cpus::get).max(1);
+
     let mp = MultiProgress::with_draw_target(draw_target);
 
     let mut targets = vec![];
@@ -472,45 +934,90 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
 
         tokio::task::spawn(async move {
             let err_msg = match ctrl_c().await {
-                Ok(()) => "Recieved Ctrl-c".to_owned(),
-                Err(err) => err.to_string(),
+                Ok(()) => None,
+                Err(err) => Some(err.to_string()),
             };
             ctrl_c_tx.send(err_msg).unwrap();
         });
 
-        let (job_start_tx, mut job_start_rx) = tokio::sync::mpsc::channel(num_cpus::get());
-        for _ in 0..num_cpus::get() {
+        let (job_start_tx, mut job_start_rx) = tokio::sync::mpsc::channel(jobs);
+        for _ in 0..jobs {
             job_start_tx.send(()).await?;
         }
 
+        let (failed_tx, failed_rx) = tokio::sync::watch::channel(false);
+        let failed_tx = Arc::new(failed_tx);
+
         let mut results = vec![];
 
         for (i, (test_case, pb)) in targets.into_iter().enumerate() {
             let cmd = cmd.clone();
-            let stdin_path = tempdir_path.join(format!("{}-stdin", i));
-            let actual_stdout_path = tempdir_path.join(format!("{}-actual-stdout", i));
-            let expected_stdout_path = tempdir_path.join(format!("{}-expected-stdout", i));
-            let stderr_path = tempdir_path.join(format!("{}-stderr", i));
+            // Each test case has its own directory so that nothing collides between the cases
+            // running in parallel. Its empty `tmp` subdirectory is given to the solver, the
+            // checker, the reference, and the input filter as `$TMPDIR`, apart from the files of
+            // the harness.
+            let case_dir = tempfile::Builder::new()
+                .prefix(&format!("{}-", i))
+                .tempdir_in(&tempdir_path)?
+                .into_path();
+            let tmpdir = case_dir.join("tmp");
+            std::fs::create_dir(&tmpdir)?;
+            let stdin_path = case_dir.join("stdin");
+            let actual_stdout_path = case_dir.join("actual-stdout");
+            let expected_stdout_path = case_dir.join("expected-stdout");
+            let stderr_path = case_dir.join("stderr");
             let bash_exe = bash_exe.clone();
+            let reference_outputs = reference_outputs.clone();
 
             job_start_rx.recv().await;
 
+            if *failed_rx.borrow() {
+                tokio::task::block_in_place(|| finish_skipped(&pb));
+                job_start_tx.send(()).await?;
+                continue;
+            }
+
             let job_start_tx = job_start_tx.clone();
             let mut ctrl_c_rx = ctrl_c_rxs.pop().expect("should have enough length");
             let pb_clone = pb.clone();
+            let failed_tx = failed_tx.clone();
+            let mut failed_rx = failed_rx.clone();
 
             results.push(tokio::task::spawn(async move {
-                let result = tokio::task::spawn(async move {
+                let mut handle = tokio::task::spawn(async move {
                     tokio::fs::write(&stdin_path, test_case.input.as_ref()).await?;
 
                     let test_case_name = test_case.name.clone();
                     let timelimit = test_case.timelimit;
-                    let stdin = test_case.input.clone();
-                    let expected = test_case.output.clone();
 
                     let cwd = &cmd.cwd;
+
+                    let stdin = match &test_case.input_filter {
+                        Some(input_filter) => {
+                            let stdin =
+                                filter_input(input_filter, cwd, &tmpdir, &stdin_path, &bash_exe)
+                                    .await?;
+                            tokio::fs::write(&stdin_path, stdin.as_ref()).await?;
+                            stdin
+                        }
+                        None => test_case.input.clone(),
+                    };
+
+                    let mut expected = match test_case.output.clone() {
+                        ExpectedOutput::Reference { reference, r#match } => {
+                            let text = reference_outputs
+                                .get(&reference, &stdin, cwd, &tmpdir, &stdin_path, &bash_exe)
+                                .await?;
+                            ExpectedOutput::new(Some(text), r#match)
+                        }
+                        expected => expected,
+                    };
+                    if test_case.trim_cr {
+                        expected.trim_cr();
+                    }
                     let cmd = cmd
                         .build(
+                            &tmpdir,
                             (stdin.len() >= 10 * 1024).then(|| &*stdin_path),
                             &actual_stdout_path,
                             &stderr_path,
@@ -520,9 +1027,10 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                     let started = Instant::now();
 
                     let mut child = { cmd }.spawn()?;
+                    let _process_group = ProcessGroup(child.id());
 
-                    if let Some(mut child_stdin) = child.stdin.take() {
-                        child_stdin.write_all((*stdin).as_ref()).await?;
+                    if let Some(child_stdin) = child.stdin.take() {
+                        write_stdin(child_stdin, stdin.as_bytes()).await?;
                     }
 
                     macro_rules! with_ctrl_c {
@@ -530,8 +1038,11 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                             select! {
                                 __output = $future => __output,
                                 err_msg = ctrl_c_rx.recv().fuse() => {
-                                    let _ = child.kill();
-                                    bail!("{}", err_msg?);
+                                    let _ = child.kill().await;
+                                    match err_msg? {
+                                        Some(err_msg) => bail!("{}", err_msg),
+                                        None => return Err(Interrupted.into()),
+                                    }
                                 },
                             }
                         };
@@ -557,7 +1068,7 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                                     "{{prefix}}{{msg:{}}}",
                                     verdict.summary_style(),
                                 )));
-                                pb_clone.finish_with_message(&verdict.summary());
+                                pb_clone.finish_with_message(&verdict.summary(false));
                             });
                             return Ok(verdict);
                         }
@@ -567,8 +1078,14 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
 
                     let elapsed = Instant::now() - started;
 
-                    let stdout = utf8(tokio::fs::read(&actual_stdout_path).await?)?;
-                    let stderr = utf8(tokio::fs::read(&stderr_path).await?)?;
+                    let mut stdout = utf8(tokio::fs::read(&actual_stdout_path).await?)?;
+                    let mut stderr = utf8(tokio::fs::read(&stderr_path).await?)?;
+
+                    if test_case.trim_cr {
+                        stdout = crlf_to_lf(&stdout);
+                        stderr = crlf_to_lf(&stderr);
+                        tokio::fs::write(&actual_stdout_path, stdout.as_bytes()).await?;
+                    }
 
                     if matches!(timelimit, Some(t) if t < elapsed) {
                         Ok(Verdict::TimelimitExceeded {
@@ -588,9 +1105,10 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                             status,
                         })
                     } else if let Err((checker_stdout, checker_stderr, note)) = check(
-                        &test_case.output,
+                        &expected,
                         &stdout,
                         cwd,
+                        &tmpdir,
                         &stdin_path,
                         &actual_stdout_path,
                         &expected_stdout_path,
@@ -607,8 +1125,26 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                             checker_stdout,
                             checker_stderr,
                             expected,
+                            expected_stderr: None,
                             note,
                         })
+                    } else if let Some(expected_stderr) = test_case
+                        .expected_stderr
+                        .as_ref()
+                        .filter(|expected_stderr| !expected_stderr.accepts(&stderr))
+                    {
+                        Ok(Verdict::WrongAnswer {
+                            test_case_name,
+                            elapsed,
+                            stdin,
+                            stdout,
+                            stderr,
+                            checker_stdout: Arc::from(""),
+                            checker_stderr: Arc::from(""),
+                            expected,
+                            expected_stderr: expected_stderr.expected_stdout().map(Into::into),
+                            note: Some(WrongAnswerNote::StderrMismatched),
+                        })
                     } else {
                         Ok(Verdict::Accepted {
                             test_case_name,
@@ -619,18 +1155,34 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                             expected,
                         })
                     }
-                })
-                .await
-                .unwrap();
+                });
+
+                let result = select! {
+                    result = (&mut handle).fuse() => Some(result.unwrap()),
+                    _ = failed_rx.changed().fuse() => None,
+                };
+
+                let result = if let Some(result) = result {
+                    result
+                } else {
+                    // Another test case failed with `fail_fast`.
+                    handle.abort();
+                    tokio::task::block_in_place(|| finish_skipped(&pb));
+                    job_start_tx.send(()).await?;
+                    return Ok((i, None));
+                };
 
                 match &result {
                     Ok(verdict) => {
+                        if fail_fast && verdict.kind() != VerdictKind::Accepted {
+                            let _ = failed_tx.send(true);
+                        }
                         tokio::task::block_in_place(|| {
                             pb.set_style(progress_style(&format!(
                                 "{{prefix}}{{msg:{}}}",
                                 verdict.summary_style(),
                             )));
-                            pb.finish_with_message(&verdict.summary());
+                            pb.finish_with_message(&verdict.summary(false));
                         });
                     }
                     Err(err) => {
@@ -643,16 +1195,16 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
 
                 job_start_tx.send(()).await?;
                 let verdict = result?;
-                Ok::<_, anyhow::Error>((i, verdict))
+                Ok::<_, anyhow::Error>((i, Some(verdict)))
             }));
         }
 
         let mut verdicts = vec![None; num_targets];
         for result in results {
             let (i, element) = result.await??;
-            verdicts[i] = Some(element);
+            verdicts[i] = element;
         }
-        let verdicts = verdicts.into_iter().map(Option::unwrap).collect();
+        let verdicts = verdicts.into_iter().flatten().collect();
 
         Ok::<_, anyhow::Error>(JudgeOutcome { verdicts })
     });
@@ -661,56 +1213,715 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
 
     let outcome = rt.block_on(outcome)??;
     tempdir.close()?;
-    return Ok(outcome);
+    Ok(outcome)
+}
 
-    fn progress_style(template: impl AsRef<str>) -> ProgressStyle {
-        ProgressStyle::default_spinner().template(template.as_ref())
-    }
+/// A failure found by [`stress`].
+#[derive(Debug, Clone)]
+pub struct StressFailure {
+    pub seed: u64,
+    pub input: Arc<str>,
+    pub outcome: JudgeOutcome,
+}
 
-    fn align_left(s: &str, n: usize) -> String {
-        let spaces = n.saturating_sub(s.width());
-        s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
+impl StressFailure {
+    /// Converts into a test case named `stress-{seed}`.
+    ///
+    /// `out` is left empty so that `reference` produces it.
+    pub fn to_test_case(&self) -> PartialBatchTestCase {
+        PartialBatchTestCase {
+            name: Some(format!("stress-{}", self.seed)),
+            note: None,
+            r#in: self.input.clone(),
+            out: None,
+            timelimit: None,
+            r#match: None,
+            stderr: None,
+            stderr_match: None,
+        }
     }
+}
+
+/// Generates inputs with `generator` for each seed, and judges `cmd` against `reference` on them
+/// until a test case fails.
+pub fn stress<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
+    draw_target: ProgressDrawTarget,
+    ctrl_c: fn() -> C,
+    cmd: &CommandExpression,
+    test_suite: &BatchTestSuite,
+    seeds: Range<u64>,
+) -> anyhow::Result<Option<StressFailure>> {
+    let BatchTestSuite {
+        timelimit,
+        r#match,
+        reference,
+        generator,
+        ..
+    } = test_suite;
+
+    let reference = reference
+        .as_ref()
+        .with_context(|| "`reference` is required for stress testing")?;
+    let Generator {
+        cmd: generator_cmd,
+        shell,
+    } = generator
+        .as_ref()
+        .with_context(|| "`generator` is required for stress testing")?;
+
+    let bash_exe = bash_exe(&cmd.cwd)?;
+
+    let pb = ProgressBar::with_draw_target(seeds.end.saturating_sub(seeds.start), draw_target);
+    pb.set_style(progress_style("{msg:bold} {pos}/{len} {wide_bar}"));
+
+    for seed in seeds {
+        pb.set_message(&format!("Seed {}", seed));
+
+        let input = match generate(&bash_exe, generator_cmd, shell, &cmd.cwd, seed) {
+            Ok(input) => input,
+            Err(err) => {
+                pb.finish_and_clear();
+                return Err(err);
+            }
+        };
+
+        let test_case = BatchTestCase {
+            name: Some(format!("stress-{}", seed)),
+            timelimit: *timelimit,
+            input: input.clone(),
+            output: ExpectedOutput::Reference {
+                reference: reference.clone(),
+                r#match: r#match.clone(),
+            },
+            expected_stderr: None,
+            trim_cr: false,
+            input_filter: None,
+        };
+
+        let outcome = judge(
+            ProgressDrawTarget::hidden(),
+            ctrl_c,
+            cmd,
+            &[test_case],
+            JudgeOptions::default(),
+        )?;
+
+        if outcome.error_on_fail().is_err() {
+            pb.finish_and_clear();
+            return Ok(Some(StressFailure {
+                seed,
+                input,
+                outcome,
+            }));
+        }
 
-    fn align_right(s: &str, n: usize) -> String {
-        let spaces = n.saturating_sub(s.width());
-        itertools::repeat_n(' ', spaces).chain(s.chars()).collect()
+        pb.inc(1);
     }
+
+    pb.finish_and_clear();
+    Ok(None)
 }
 
-async fn check(
-    expected: &ExpectedOutput,
-    actual: &str,
-    cwd: &Path,
-    stdin_path: &Path,
-    actual_stdout_path: &Path,
-    expected_stdout_path: &Path,
-    bash_exe: &Path,
-) -> anyhow::Result<Result<(), (Arc<str>, Arc<str>, Option<WrongAnswerNote>)>> {
-    match expected {
-        ExpectedOutput::Deterministic(expected) => Ok(if expected.accepts(actual) {
-            Ok(())
-        } else {
-            let note = expected
-                .expected_stdout()
-                .filter(|expected| expected.split_whitespace().eq(actual.split_whitespace()))
-                .map(|_| WrongAnswerNote::WordsMatched);
-            Err((Arc::from(""), Arc::from(""), note))
-        }),
-        ExpectedOutput::Checker { text, cmd, shell } => {
+/// Generates inputs with `generator` for each seed, and runs `cmd` and `solvers` on them until
+/// their outputs disagree.
+///
+/// The output of `cmd` is the expected output for `solvers`, compared with `match`. The verdicts in
+/// the outcome are named `solution` and the `cmd` of each solver.
+pub fn vote<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
+    draw_target: ProgressDrawTarget,
+    ctrl_c: fn() -> C,
+    cmd: &CommandExpression,
+    solvers: &[Reference],
+    test_suite: &BatchTestSuite,
+    seeds: Range<u64>,
+) -> anyhow::Result<Option<StressFailure>> {
+    let BatchTestSuite {
+        timelimit,
+        r#match,
+        generator,
+        ..
+    } = test_suite;
+
+    let Generator {
+        cmd: generator_cmd,
+        shell,
+    } = generator
+        .as_ref()
+        .with_context(|| "`generator` is required for stress testing")?;
+
+    if solvers.is_empty() {
+        bail!("No solvers to compare with");
+    }
+
+    let bash_exe = bash_exe(&cmd.cwd)?;
+
+    let solver_cmds = solvers
+        .iter()
+        .map(|Reference { cmd: solver, shell }| {
             let (program, args) = match shell {
-                CheckerShell::Bash => (bash_exe, [OsStr::new("-c"), OsStr::new(cmd)]),
+                CheckerShell::Bash => (&bash_exe, ["-c", solver]),
             };
+            CommandExpression {
+                program: program.into(),
+                args: args.iter().map(Into::into).collect(),
+                cwd: cmd.cwd.clone(),
+                env: BTreeMap::new(),
+            }
+        })
+        .collect::<Vec<_>>();
 
-            let mut env_vars = vec![("INPUT", stdin_path), ("ACTUAL_OUTPUT", actual_stdout_path)];
-            if let Some(text) = text {
-                tokio::fs::write(expected_stdout_path, text.as_ref()).await?;
-                env_vars.push(("EXPECTED_OUTPUT", expected_stdout_path));
+    let pb = ProgressBar::with_draw_target(seeds.end.saturating_sub(seeds.start), draw_target);
+    pb.set_style(progress_style("{msg:bold} {pos}/{len} {wide_bar}"));
+
+    for seed in seeds {
+        pb.set_message(&format!("Seed {}", seed));
+
+        let input = match generate(&bash_exe, generator_cmd, shell, &cmd.cwd, seed) {
+            Ok(input) => input,
+            Err(err) => {
+                pb.finish_and_clear();
+                return Err(err);
             }
+        };
 
-            let Output {
-                status,
-                stdout,
+        let run = |name: &str, cmd: &CommandExpression, output: ExpectedOutput| {
+            let test_case = BatchTestCase {
+                name: Some(name.to_owned()),
+                timelimit: *timelimit,
+                input: input.clone(),
+                output,
+                expected_stderr: None,
+                trim_cr: false,
+                input_filter: None,
+            };
+            judge(
+                ProgressDrawTarget::hidden(),
+                ctrl_c,
+                cmd,
+                &[test_case],
+                JudgeOptions::default(),
+            )
+        };
+
+        let mut outcome = run("solution", cmd, ExpectedOutput::new(None, r#match.clone()))?;
+
+        let expected = outcome.verdicts[0].stdout().map(Arc::from);
+
+        for (Reference { cmd: name, .. }, solver_cmd) in solvers.iter().zip(&solver_cmds) {
+            let output = ExpectedOutput::new(expected.clone(), r#match.clone());
+            outcome
+                .verdicts
+                .extend(run(name, solver_cmd, output)?.verdicts);
+        }
+
+        if outcome.error_on_fail().is_err() {
+            pb.finish_and_clear();
+            return Ok(Some(StressFailure {
+                seed,
+                input,
+                outcome,
+            }));
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_and_clear();
+    Ok(None)
+}
+
+/// Runs `generator` with `$SEED`.
+fn generate(
+    bash_exe: &Path,
+    generator: &str,
+    shell: &CheckerShell,
+    cwd: &Path,
+    seed: u64,
+) -> anyhow::Result<Arc<str>> {
+    let (program, args) = match shell {
+        CheckerShell::Bash => (bash_exe, ["-c", generator]),
+    };
+
+    let Output {
+        status,
+        stdout,
+        stderr,
+    } = std::process::Command::new(program)
+        .args(args)
+        .env("SEED", seed.to_string())
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if !status.success() {
+        bail!(
+            "The generator failed with seed {} ({}): {}",
+            seed,
+            status,
+            String::from_utf8_lossy(&stderr).trim_end(),
+        );
+    }
+
+    utf8(stdout)
+}
+
+pub fn judge_interactive<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
+    draw_target: ProgressDrawTarget,
+    ctrl_c: fn() -> C,
+    cmd: &CommandExpression,
+    test_cases: &[InteractiveTestCase],
+    opts: JudgeOptions,
+) -> anyhow::Result<JudgeOutcome> {
+    let JudgeOptions { fail_fast, jobs: _ } = opts;
+
+    let cmd = Arc::new(cmd.clone());
+    let num_test_cases = test_cases.len();
+
+    let quoted_name_width = test_cases
+        .iter()
+        .flat_map(|InteractiveTestCase { name, .. }| name.as_ref())
+        .map(|s| format!("{:?}", s).width())
+        .max()
+        .unwrap_or(0);
+
+    let bash_exe = bash_exe(&cmd.cwd)?;
+
+    let mp = MultiProgress::with_draw_target(draw_target);
+
+    let mut targets = vec![];
+
+    for (i, test_case) in test_cases.iter().enumerate() {
+        let pb = mp.add(ProgressBar::new_spinner());
+
+        pb.set_style(progress_style("{prefix}{spinner} {msg:bold}"));
+
+        pb.set_prefix(&format!(
+            "{}/{} ({} ",
+            align_right(&(i + 1).to_string(), num_test_cases.to_string().len()),
+            num_test_cases,
+            align_left(
+                &format!("{:?})", test_case.name.as_deref().unwrap_or("")),
+                quoted_name_width + 1,
+            ),
+        ));
+
+        pb.set_message("Waiting...");
+        pb.enable_steady_tick(50);
+
+        targets.push((test_case.clone(), pb));
+    }
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_io()
+        .enable_time()
+        .build()?;
+
+    let outcome = rt.spawn(async move {
+        let mut ctrl_c = Box::pin(ctrl_c().fuse());
+        let mut verdicts = vec![];
+
+        let mut targets = targets.into_iter();
+
+        // Runs one by one since a tester and a solver occupy two processes.
+        while let Some((test_case, pb)) = targets.next() {
+            tokio::task::block_in_place(|| pb.set_message("Judging..."));
+
+            let result = select! {
+                result = run_interactive(&cmd, &test_case, &bash_exe).fuse() => result,
+                result = ctrl_c => Err(match result {
+                    Ok(()) => Interrupted.into(),
+                    Err(err) => err.into(),
+                }),
+            };
+
+            match &result {
+                Ok(verdict) => {
+                    tokio::task::block_in_place(|| {
+                        pb.set_style(progress_style(format!(
+                            "{{prefix}}{{msg:{}}}",
+                            verdict.summary_style(),
+                        )));
+                        pb.finish_with_message(&verdict.summary(false));
+                    });
+                }
+                Err(err) => {
+                    tokio::task::block_in_place(|| {
+                        pb.set_style(progress_style("{prefix}{msg}"));
+                        pb.finish_with_message(&format!("{:?}", err));
+                    });
+                }
+            }
+
+            let verdict = result?;
+            let failed = verdict.kind() != VerdictKind::Accepted;
+            verdicts.push(verdict);
+
+            if fail_fast && failed {
+                for (_, pb) in &mut targets {
+                    tokio::task::block_in_place(|| finish_skipped(&pb));
+                }
+            }
+        }
+
+        Ok::<_, anyhow::Error>(JudgeOutcome { verdicts })
+    });
+
+    mp.join()?;
+
+    rt.block_on(outcome)?
+}
+
+async fn run_interactive(
+    cmd: &CommandExpression,
+    test_case: &InteractiveTestCase,
+    bash_exe: &Path,
+) -> anyhow::Result<Verdict> {
+    let InteractiveTestCase {
+        name: test_case_name,
+        timelimit,
+        tester: Tester {
+            cmd: tester_cmd,
+            shell,
+        },
+        args,
+    } = test_case;
+
+    let test_case_name = test_case_name.clone();
+    let timelimit = *timelimit;
+
+    let expected = ExpectedOutput::Checker {
+        text: None,
+        cmd: tester_cmd.clone(),
+        shell: *shell,
+    };
+
+    let mut tester = match shell {
+        CheckerShell::Bash => new_process_group(
+            tokio::process::Command::new(bash_exe)
+                .arg("-c")
+                .arg(tester_cmd)
+                .arg("tester")
+                .args(args)
+                .current_dir(&cmd.cwd)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true),
+        )
+        .spawn()?,
+    };
+    let _tester_process_group = ProcessGroup(tester.id());
+
+    let started = Instant::now();
+
+    let mut solver = new_process_group(
+        tokio::process::Command::new(&cmd.program)
+            .args(&cmd.args)
+            .current_dir(&cmd.cwd)
+            .envs(&cmd.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true),
+    )
+    .spawn()?;
+    let _solver_process_group = ProcessGroup(solver.id());
+
+    let tester_to_solver = tokio::task::spawn(relay(
+        tester.stdout.take().expect("specified `piped()`"),
+        solver.stdin.take().expect("specified `piped()`"),
+    ));
+    let solver_to_tester = tokio::task::spawn(relay(
+        solver.stdout.take().expect("specified `piped()`"),
+        tester.stdin.take().expect("specified `piped()`"),
+    ));
+    let tester_stderr = tokio::task::spawn(read_to_end(
+        tester.stderr.take().expect("specified `piped()`"),
+    ));
+    let solver_stderr = tokio::task::spawn(read_to_end(
+        solver.stderr.take().expect("specified `piped()`"),
+    ));
+
+    let wait = async {
+        let solver_status = solver.wait().await?;
+        let elapsed = Instant::now() - started;
+        let tester_status = tester.wait().await?;
+        Ok::<_, io::Error>((solver_status, tester_status, elapsed))
+    };
+
+    let (solver_status, tester_status, elapsed) = if let Some(timelimit) = timelimit {
+        let timeout = timelimit + Duration::from_millis(100);
+
+        if let Ok(statuses) = tokio::time::timeout(timeout, wait).await {
+            statuses?
+        } else {
+            let _ = solver.kill().await;
+            let _ = tester.kill().await;
+            return Ok(Verdict::TimelimitExceeded {
+                test_case_name,
+                timelimit,
+                stdin: utf8(tester_to_solver.await??)?,
+                expected,
+            });
+        }
+    } else {
+        wait.await?
+    };
+
+    let stdin = utf8(tester_to_solver.await??)?;
+    let stdout = utf8(solver_to_tester.await??)?;
+    let stderr = utf8(solver_stderr.await??)?;
+    let tester_stderr = utf8(tester_stderr.await??)?;
+
+    return Ok(if matches!(timelimit, Some(t) if t < elapsed) {
+        Verdict::TimelimitExceeded {
+            test_case_name,
+            timelimit: timelimit.unwrap(),
+            stdin,
+            expected,
+        }
+    } else if !solver_status.success() {
+        Verdict::RuntimeError {
+            test_case_name,
+            elapsed,
+            stdin,
+            stdout,
+            stderr,
+            expected,
+            status: solver_status,
+        }
+    } else if !tester_status.success() {
+        Verdict::WrongAnswer {
+            test_case_name,
+            elapsed,
+            stdin,
+            stdout,
+            stderr,
+            checker_stdout: Arc::from(""),
+            checker_stderr: tester_stderr,
+            expected,
+            expected_stderr: None,
+            note: None,
+        }
+    } else {
+        Verdict::Accepted {
+            test_case_name,
+            elapsed,
+            stdin,
+            stdout,
+            stderr,
+            expected,
+        }
+    });
+
+    /// Copies `from` to `to` and returns what has been copied.
+    ///
+    /// Keeps reading `from` even after `to` is closed so that the other process does not block.
+    async fn relay(
+        mut from: impl AsyncRead + Unpin,
+        mut to: impl AsyncWrite + Unpin,
+    ) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; 8 * 1024];
+        let mut log = vec![];
+        let mut to_closed = false;
+
+        loop {
+            let n = from.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            log.extend_from_slice(&buf[..n]);
+            if !to_closed {
+                to_closed = to.write_all(&buf[..n]).await.is_err() || to.flush().await.is_err();
+            }
+        }
+        Ok(log)
+    }
+
+    async fn read_to_end(mut from: impl AsyncRead + Unpin) -> io::Result<Vec<u8>> {
+        let mut buf = vec![];
+        from.read_to_end(&mut buf).await?;
+        Ok(buf)
+    }
+}
+
+fn bash_exe(cwd: &Path) -> anyhow::Result<PathBuf> {
+    static GIT_BASH: &str = r"C:\Program Files\Git\bin\bash.exe";
+
+    let bash_exe = if cfg!(windows) && Path::new(GIT_BASH).exists() {
+        GIT_BASH
+    } else {
+        "bash"
+    };
+    which::which_in(bash_exe, env::var_os("PATH"), cwd)
+        .map_err(|_| anyhow!("`{}` not found", bash_exe))
+}
+
+fn finish_skipped(pb: &ProgressBar) {
+    pb.set_style(progress_style("{prefix}{msg}"));
+    pb.finish_with_message("Skipped");
+}
+
+fn progress_style(template: impl AsRef<str>) -> ProgressStyle {
+    ProgressStyle::default_spinner().template(template.as_ref())
+}
+
+fn align_left(s: &str, n: usize) -> String {
+    let spaces = n.saturating_sub(s.width());
+    s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
+}
+
+fn align_right(s: &str, n: usize) -> String {
+    let spaces = n.saturating_sub(s.width());
+    itertools::repeat_n(' ', spaces).chain(s.chars()).collect()
+}
+
+/// Outputs of `Reference`s, cached for each input within a run.
+#[derive(Default)]
+struct ReferenceOutputs(tokio::sync::Mutex<HashMap<Reference, OutputsByInput>>);
+
+type OutputsByInput = HashMap<Arc<str>, Arc<str>>;
+
+impl ReferenceOutputs {
+    async fn get(
+        &self,
+        reference: &Reference,
+        input: &Arc<str>,
+        cwd: &Path,
+        tmpdir: &Path,
+        stdin_path: &Path,
+        bash_exe: &Path,
+    ) -> anyhow::Result<Arc<str>> {
+        if let Some(output) = self
+            .0
+            .lock()
+            .await
+            .get(reference)
+            .and_then(|m| m.get(input))
+        {
+            return Ok(output.clone());
+        }
+
+        let Reference { cmd, shell } = reference;
+
+        let (program, args) = match shell {
+            CheckerShell::Bash => (bash_exe, [OsStr::new("-c"), OsStr::new(cmd)]),
+        };
+
+        let Output {
+            status,
+            stdout,
+            stderr,
+        } = tokio::process::Command::new(program)
+            .args(args)
+            .env("INPUT", stdin_path)
+            .env("TMPDIR", tmpdir)
+            .current_dir(cwd)
+            .stdin(std::fs::File::open(stdin_path)?)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !status.success() {
+            bail!(
+                "The reference command failed ({}): {}",
+                status,
+                String::from_utf8_lossy(&stderr).trim_end(),
+            );
+        }
+
+        let output = utf8(stdout)?;
+        self.0
+            .lock()
+            .await
+            .entry(reference.clone())
+            .or_default()
+            .insert(input.clone(), output.clone());
+        Ok(output)
+    }
+}
+
+async fn filter_input(
+    input_filter: &InputFilter,
+    cwd: &Path,
+    tmpdir: &Path,
+    stdin_path: &Path,
+    bash_exe: &Path,
+) -> anyhow::Result<Arc<str>> {
+    let InputFilter { cmd, shell } = input_filter;
+
+    let (program, args) = match shell {
+        CheckerShell::Bash => (bash_exe, [OsStr::new("-c"), OsStr::new(cmd)]),
+    };
+
+    let Output {
+        status,
+        stdout,
+        stderr,
+    } = tokio::process::Command::new(program)
+        .args(args)
+        .env("TMPDIR", tmpdir)
+        .current_dir(cwd)
+        .stdin(std::fs::File::open(stdin_path)?)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !status.success() {
+        bail!(
+            "The input filter failed ({}): {}",
+            status,
+            String::from_utf8_lossy(&stderr).trim_end(),
+        );
+    }
+
+    utf8(stdout)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn check(
+    expected: &ExpectedOutput,
+    actual: &str,
+    cwd: &Path,
+    tmpdir: &Path,
+    stdin_path: &Path,
+    actual_stdout_path: &Path,
+    expected_stdout_path: &Path,
+    bash_exe: &Path,
+) -> anyhow::Result<Result<(), (Arc<str>, Arc<str>, Option<WrongAnswerNote>)>> {
+    match expected {
+        ExpectedOutput::Deterministic(expected) => Ok(if expected.accepts(actual) {
+            Ok(())
+        } else {
+            let note = expected
+                .expected_stdout()
+                .filter(|expected| expected.split_whitespace().eq(actual.split_whitespace()))
+                .map(|_| WrongAnswerNote::WordsMatched);
+            Err((Arc::from(""), Arc::from(""), note))
+        }),
+        ExpectedOutput::Reference { .. } => unreachable!("should have been resolved"),
+        ExpectedOutput::Checker { text, cmd, shell } => {
+            let (program, args) = match shell {
+                CheckerShell::Bash => (bash_exe, [OsStr::new("-c"), OsStr::new(cmd)]),
+            };
+
+            let mut env_vars = vec![
+                ("TMPDIR", tmpdir),
+                ("INPUT", stdin_path),
+                ("ACTUAL_OUTPUT", actual_stdout_path),
+            ];
+            if let Some(text) = text {
+                tokio::fs::write(expected_stdout_path, text.as_ref()).await?;
+                env_vars.push(("EXPECTED_OUTPUT", expected_stdout_path));
+            }
+
+            let Output {
+                status,
+                stdout,
                 stderr,
             } = tokio::process::Command::new(program)
                 .args(&args)
@@ -734,8 +1945,1251 @@ async fn check(
     }
 }
 
+/// Writes `input` to the solver. The solver may exit or close its stdin without reading all of
+/// it, so `BrokenPipe` is ignored and the verdict is left to the exit status and the output.
+async fn write_stdin(mut to: impl AsyncWrite + Unpin, input: &[u8]) -> io::Result<()> {
+    match to.write_all(input).await {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
 fn utf8(bytes: Vec<u8>) -> anyhow::Result<Arc<str>> {
     String::from_utf8(bytes)
         .map(Into::into)
         .map_err(|_| anyhow!("the output was not a valid UTF-8 string"))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        judge::{
+            CommandExpression, DiffAlgorithm, Interrupted, JudgeOptions, JudgeOutcome,
+            PrintOptions, Verdict, VerdictKind, WrongAnswerNote,
+        },
+        testsuite::{
+            BatchTestCase, BatchTestSuite, BlankLines, CheckerShell, DeterministicExpectedOutput,
+            ExpectedOutput, Generator, InputFilter, InteractiveTestCase, Match,
+            PartialBatchTestCase, Reference, Tester,
+        },
+    };
+    use indicatif::ProgressDrawTarget;
+    use maplit::btreemap;
+    use pretty_assertions::assert_eq;
+    use std::{
+        collections::HashSet,
+        env, fs,
+        process::Stdio,
+        time::{Duration, Instant},
+    };
+    use termcolor::{Ansi, NoColor};
+
+    #[test]
+    fn print_pretty() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![Verdict::Accepted {
+                test_case_name: Some("Sample 1".to_owned()),
+                elapsed: Duration::from_millis(10),
+                stdin: "1 2\n".into(),
+                stdout: "3\n".into(),
+                stderr: "".into(),
+                expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "3\n".into(),
+                    ignore_trailing_whitespace: false,
+                    blank_lines: BlankLines::Keep,
+                }),
+            }],
+        };
+
+        let print = |verbose| {
+            let mut wtr = NoColor::new(vec![]);
+            outcome
+                .print_pretty(
+                    &mut wtr,
+                    PrintOptions {
+                        verbose,
+                        ..PrintOptions::default()
+                    },
+                )
+                .unwrap();
+            String::from_utf8(wtr.into_inner()).unwrap()
+        };
+
+        assert_eq!("1/1 (\"Sample 1\") Accepted (10 ms)\n", print(false));
+
+        assert_eq!(
+            r#"1/1 ("Sample 1") Accepted (10 ms)
+stdin:
+1 2
+expected:
+3
+actual:
+3
+stderr:
+EMPTY
+"#,
+            print(true),
+        );
+    }
+
+    #[test]
+    fn print_pretty_width() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![Verdict::WrongAnswer {
+                test_case_name: Some("Sample 1".to_owned()),
+                elapsed: Duration::from_millis(10),
+                stdin: "1 2\n".into(),
+                stdout: "aaaaaaaaaaaaaaa\n0123456789abc\n".into(),
+                stderr: "".into(),
+                checker_stdout: "".into(),
+                checker_stderr: "".into(),
+                expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "aaaaaaaaaaaaaaa\n0123456789\n".into(),
+                    ignore_trailing_whitespace: false,
+                    blank_lines: BlankLines::Keep,
+                }),
+                expected_stderr: None,
+                note: None,
+            }],
+        };
+
+        let mut wtr = NoColor::new(vec![]);
+        outcome
+            .print_pretty(
+                &mut wtr,
+                PrintOptions {
+                    diff_context: Some(3),
+                    width: Some(10),
+                    ..PrintOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            r#"1/1 ("Sample 1") Wrong Answer (10 ms)
+stdin:
+1 2
+expected:
+aaaaaaaaa…
+0123456789
+actual:
+aaaaaaaaa…
+012345678…
+diff (-expected +actual):
+ aaaaaaaa…
+-0123456789
++0123456789abc
+"#,
+            String::from_utf8(wtr.into_inner()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn filter_by_kind() {
+        let expected = || {
+            ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                text: "3\n".into(),
+                ignore_trailing_whitespace: false,
+                blank_lines: BlankLines::Keep,
+            })
+        };
+
+        let wrong_answer = |name: &str| Verdict::WrongAnswer {
+            test_case_name: Some(name.to_owned()),
+            elapsed: Duration::from_millis(10),
+            stdin: "1 2\n".into(),
+            stdout: "4\n".into(),
+            stderr: "".into(),
+            checker_stdout: "".into(),
+            checker_stderr: "".into(),
+            expected: expected(),
+            expected_stderr: None,
+            note: None,
+        };
+
+        let outcome = JudgeOutcome {
+            verdicts: vec![
+                Verdict::Accepted {
+                    test_case_name: Some("1".to_owned()),
+                    elapsed: Duration::from_millis(10),
+                    stdin: "1 2\n".into(),
+                    stdout: "3\n".into(),
+                    stderr: "".into(),
+                    expected: expected(),
+                },
+                wrong_answer("2"),
+                Verdict::TimelimitExceeded {
+                    test_case_name: Some("3".to_owned()),
+                    timelimit: Duration::from_secs(2),
+                    stdin: "1 2\n".into(),
+                    expected: expected(),
+                },
+                wrong_answer("4"),
+            ],
+        };
+
+        let names = |outcome: &JudgeOutcome| {
+            outcome
+                .verdicts
+                .iter()
+                .map(|v| v.test_case_name().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let kinds = VerdictKind::VARIANTS
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect::<Vec<VerdictKind>>();
+
+        assert_eq!(
+            ["1", "2", "3", "4"],
+            *names(&outcome.filter_by_kind(&kinds))
+        );
+        assert_eq!(
+            ["2", "4"],
+            *names(&outcome.filter_by_kind(&["WA".parse().unwrap()])),
+        );
+        assert!(outcome.filter_by_kind(&[]).verdicts.is_empty());
+    }
+
+    #[test]
+    fn print_pretty_quiet() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![
+                Verdict::Accepted {
+                    test_case_name: Some("Sample 1".to_owned()),
+                    elapsed: Duration::from_millis(10),
+                    stdin: "1 2\n".into(),
+                    stdout: "3\n".into(),
+                    stderr: "".into(),
+                    expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                        text: "3\n".into(),
+                        ignore_trailing_whitespace: false,
+                        blank_lines: BlankLines::Keep,
+                    }),
+                },
+                Verdict::TimelimitExceeded {
+                    test_case_name: Some("Sample 2".to_owned()),
+                    timelimit: Duration::from_secs(2),
+                    stdin: "".into(),
+                    expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                        text: "0\n".into(),
+                        ignore_trailing_whitespace: false,
+                        blank_lines: BlankLines::Keep,
+                    }),
+                },
+            ],
+        };
+
+        let mut wtr = NoColor::new(vec![]);
+        outcome
+            .print_pretty(
+                &mut wtr,
+                PrintOptions {
+                    verbose: true,
+                    quiet: true,
+                    ..PrintOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            r#"2/2 ("Sample 2") Timelimit Exceeded (2000 ms)
+stdin:
+EMPTY
+expected:
+0
+"#,
+            String::from_utf8(wtr.into_inner()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn print_pretty_trailing_space() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![Verdict::WrongAnswer {
+                test_case_name: Some("Sample 1".to_owned()),
+                elapsed: Duration::from_millis(10),
+                stdin: "".into(),
+                stdout: "1\n2 \n3\n4\n5\n6\n".into(),
+                stderr: "".into(),
+                checker_stdout: "".into(),
+                checker_stderr: "".into(),
+                expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "1\n2\n3\n4\n5\n6\n".into(),
+                    ignore_trailing_whitespace: false,
+                    blank_lines: BlankLines::Keep,
+                }),
+                expected_stderr: None,
+                note: None,
+            }],
+        };
+
+        let mut wtr = NoColor::new(vec![]);
+        outcome
+            .print_pretty(
+                &mut wtr,
+                PrintOptions {
+                    diff_context: Some(1),
+                    visible_whitespace: true,
+                    ..PrintOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            r#"1/1 ("Sample 1") Wrong Answer (10 ms)
+stdin:
+EMPTY
+expected:
+1
+2
+3
+4
+5
+6
+actual:
+1
+2·
+3
+4
+5
+6
+diff (-expected +actual):
+ 1
+-2
++2·
+ 3
+"#,
+            String::from_utf8(wtr.into_inner()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn print_pretty_word_diff() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![Verdict::WrongAnswer {
+                test_case_name: Some("Sample 1".to_owned()),
+                elapsed: Duration::from_millis(10),
+                stdin: "".into(),
+                stdout: "1 2 9 4 5\n".into(),
+                stderr: "".into(),
+                checker_stdout: "".into(),
+                checker_stderr: "".into(),
+                expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "1 2 3 4 5\n".into(),
+                    ignore_trailing_whitespace: false,
+                    blank_lines: BlankLines::Keep,
+                }),
+                expected_stderr: None,
+                note: None,
+            }],
+        };
+
+        let mut wtr = Ansi::new(vec![]);
+        outcome
+            .print_pretty(
+                &mut wtr,
+                PrintOptions {
+                    diff_context: Some(3),
+                    diff_algorithm: DiffAlgorithm::Word,
+                    ..PrintOptions::default()
+                },
+            )
+            .unwrap();
+        let output = String::from_utf8(wtr.into_inner()).unwrap();
+        let diff = &output[output.find("diff").unwrap()..];
+
+        assert_eq!(
+            concat!(
+                "diff (-expected +actual):\n\x1b[0m",
+                "\x1b[1m\x1b[31m-\x1b[0m1 2 \x1b[1m\x1b[4m\x1b[31m3\x1b[0m 4 5\n",
+                "\x1b[1m\x1b[32m+\x1b[0m1 2 \x1b[1m\x1b[4m\x1b[32m9\x1b[0m 4 5\n",
+            ),
+            diff,
+        );
+    }
+
+    #[test]
+    fn expected_outputs_from_reference() -> anyhow::Result<()> {
+        let test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Exact {
+                ignore_trailing_whitespace: false,
+                blank_lines: BlankLines::Keep,
+            },
+            reference: Some(Reference {
+                cmd: "read a b; echo $((a + b))".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            generator: None,
+            input_filter: None,
+            cases: ["1 2\n", "2 2\n", "1 2\n"]
+                .iter()
+                .map(|&input| PartialBatchTestCase {
+                    name: None,
+                    note: None,
+                    r#in: input.into(),
+                    out: None,
+                    timelimit: None,
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                })
+                .collect(),
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let test_cases = test_suite.load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        let judge = |solution: &str| -> anyhow::Result<_> {
+            let cmd = CommandExpression {
+                program: "bash".into(),
+                args: vec!["-c".into(), solution.into()],
+                cwd: env::current_dir()?,
+                env: btreemap!(),
+            };
+            let outcome = super::judge(
+                ProgressDrawTarget::hidden(),
+                std::future::pending,
+                &cmd,
+                &test_cases,
+                JudgeOptions::default(),
+            )?;
+            Ok(outcome.verdicts)
+        };
+
+        let verdicts = judge("read a b; echo $((a + b))")?;
+        assert!(verdicts
+            .iter()
+            .all(|v| matches!(v, Verdict::Accepted { .. })));
+
+        let verdicts = judge("read a b; echo $((a * b))")?;
+        assert!(matches!(
+            &verdicts[..],
+            [
+                Verdict::WrongAnswer { .. },
+                Verdict::Accepted { .. },
+                Verdict::WrongAnswer { .. },
+            ],
+        ));
+        assert_eq!(Some("3\n"), verdicts[0].expected().expected_stdout());
+        Ok(())
+    }
+
+    #[test]
+    fn stress() -> anyhow::Result<()> {
+        let test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: Some(Reference {
+                cmd: "read a b; echo $((a + b))".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            generator: Some(Generator {
+                cmd: r#"echo "$SEED $((SEED * 2))""#.to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            input_filter: None,
+            cases: vec![],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec![
+                "-c".into(),
+                "read a b; if [ $a -ge 3 ]; then echo 0; else echo $((a + b)); fi".into(),
+            ],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let failure = super::stress(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_suite,
+            0..10,
+        )?
+        .unwrap();
+
+        assert_eq!(3, failure.seed);
+        assert_eq!("3 6\n", &*failure.input);
+        assert!(matches!(
+            &failure.outcome.verdicts[..],
+            [Verdict::WrongAnswer { .. }],
+        ));
+        assert_eq!(
+            PartialBatchTestCase {
+                name: Some("stress-3".to_owned()),
+                note: None,
+                r#in: "3 6\n".into(),
+                out: None,
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            },
+            failure.to_test_case(),
+        );
+
+        let failure = super::stress(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_suite,
+            0..3,
+        )?;
+        assert!(failure.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn vote() -> anyhow::Result<()> {
+        let test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: Some(Generator {
+                cmd: r#"echo "$SEED $((SEED * 2))""#.to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            input_filter: None,
+            cases: vec![],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), "read a b; echo $((a + b))".into()],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let solvers = [
+            "read a b; echo $((a * 3))",
+            "read a b; if [ $a -ge 2 ]; then echo 0; else echo $((a + b)); fi",
+        ]
+        .iter()
+        .map(|cmd| Reference {
+            cmd: (*cmd).to_owned(),
+            shell: CheckerShell::Bash,
+        })
+        .collect::<Vec<_>>();
+
+        let failure = super::vote(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &solvers,
+            &test_suite,
+            0..10,
+        )?
+        .unwrap();
+
+        assert_eq!(2, failure.seed);
+        assert_eq!("2 4\n", &*failure.input);
+        assert!(matches!(
+            &failure.outcome.verdicts[..],
+            [
+                Verdict::Accepted { .. },
+                Verdict::Accepted { .. },
+                Verdict::WrongAnswer { .. },
+            ],
+        ));
+        assert_eq!(
+            [
+                Some("solution"),
+                Some(&*solvers[0].cmd),
+                Some(&*solvers[1].cmd)
+            ],
+            [
+                failure.outcome.verdicts[0].test_case_name(),
+                failure.outcome.verdicts[1].test_case_name(),
+                failure.outcome.verdicts[2].test_case_name(),
+            ],
+        );
+        assert_eq!(
+            Some("6\n"),
+            failure.outcome.verdicts[2].expected().expected_stdout()
+        );
+
+        let failure = super::vote(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &solvers[..1],
+            &test_suite,
+            0..10,
+        )?;
+        assert!(failure.is_none());
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn interrupted() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-tests-")
+            .tempdir()?;
+
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), "sleep 60 & echo $! > ./pid; wait".into()],
+            cwd: tempdir.path().to_owned(),
+            env: btreemap!(),
+        };
+
+        let test_case = BatchTestCase {
+            name: None,
+            timelimit: None,
+            input: "".into(),
+            output: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+            expected_stderr: None,
+            trim_cr: false,
+            input_filter: None,
+        };
+
+        async fn ctrl_c() -> tokio::io::Result<()> {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Ok(())
+        }
+
+        let err = super::judge(
+            ProgressDrawTarget::hidden(),
+            ctrl_c,
+            &cmd,
+            &[test_case],
+            JudgeOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.is::<Interrupted>(), "{:?}", err);
+
+        let pid = fs::read_to_string(tempdir.path().join("pid"))?;
+        let is_alive = || match fs::read_to_string(format!("/proc/{}/stat", pid.trim())) {
+            // Ignores zombies, which are left if nothing reaps orphans.
+            Ok(stat) => !stat.contains(") Z "),
+            Err(_) => false,
+        };
+        let started = Instant::now();
+        while is_alive() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "`sleep` is still alive"
+            );
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn write_stdin_to_exited_process() -> anyhow::Result<()> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(async {
+                let mut child = tokio::process::Command::new("bash")
+                    .args(["-c", "exit 0"])
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                let child_stdin = child.stdin.take().unwrap();
+                child.wait().await?;
+
+                super::write_stdin(child_stdin, &[b'\n'; 1024]).await?;
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn solver_exits_without_reading_all_of_stdin() -> anyhow::Result<()> {
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: (0..20)
+                .map(|_| PartialBatchTestCase {
+                    name: None,
+                    note: None,
+                    r#in: "1\n".repeat(1000).into(),
+                    out: Some("1\n".into()),
+                    timelimit: None,
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                })
+                .collect(),
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        let judge = |solution: &str| -> anyhow::Result<_> {
+            let cmd = CommandExpression {
+                program: "bash".into(),
+                args: vec!["-c".into(), solution.into()],
+                cwd: env::current_dir()?,
+                env: btreemap!(),
+            };
+            let outcome = super::judge(
+                ProgressDrawTarget::hidden(),
+                std::future::pending,
+                &cmd,
+                &test_cases,
+                JudgeOptions::default(),
+            )?;
+            Ok(outcome.verdicts)
+        };
+
+        let verdicts = judge("exec 0<&-; echo 1")?;
+        assert!(verdicts
+            .iter()
+            .all(|v| matches!(v, Verdict::Accepted { .. })));
+
+        let verdicts = judge("exec 0<&-; exit 1")?;
+        assert!(verdicts
+            .iter()
+            .all(|v| matches!(v, Verdict::RuntimeError { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn fail_fast() -> anyhow::Result<()> {
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: (0..20)
+                .map(|i| PartialBatchTestCase {
+                    name: None,
+                    note: None,
+                    r#in: format!("{}\n", i).into(),
+                    out: Some("ok\n".into()),
+                    timelimit: None,
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                })
+                .collect(),
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        // Fails immediately on the first test case, and takes a while on the others.
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec![
+                "-c".into(),
+                r#"read -r i; if [ "$i" = 0 ]; then echo ng; else sleep 1; echo ok; fi"#.into(),
+            ],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let started = Instant::now();
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions {
+                fail_fast: true,
+                ..Default::default()
+            },
+        )?;
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(outcome.verdicts.len() < test_cases.len());
+        assert!(matches!(outcome.verdicts[0], Verdict::WrongAnswer { .. },));
+        assert!(outcome.error_on_fail().is_err());
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases[1..2],
+            JudgeOptions {
+                fail_fast: true,
+                ..Default::default()
+            },
+        )?;
+        assert!(outcome.error_on_fail().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn trim_cr() -> anyhow::Result<()> {
+        let mut test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::EXACT,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: None,
+                note: None,
+                r#in: "1\r\n2\r\n".into(),
+                out: Some("1\r\n2\r\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        // Echoes the input with CRLF regardless of the line endings of it.
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec![
+                "-c".into(),
+                r#"while IFS= read -r line; do printf '%s\r\n' "${line%$'\r'}"; done"#.into(),
+            ],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let judge = |test_cases: &[_]| {
+            super::judge(
+                ProgressDrawTarget::hidden(),
+                std::future::pending,
+                &cmd,
+                test_cases,
+                JudgeOptions::default(),
+            )
+        };
+
+        assert!(judge(&test_cases)?.error_on_fail().is_ok());
+
+        // Only the expected output has LF.
+        test_cases[0].output = ExpectedOutput::new(Some("1\n2\n".into()), Match::EXACT);
+        assert!(matches!(
+            &*judge(&test_cases)?.verdicts,
+            [Verdict::WrongAnswer { .. }],
+        ));
+
+        test_cases[0].set_trim_cr();
+        assert_eq!("1\n2\n", &*test_cases[0].input);
+        assert!(matches!(
+            &*judge(&test_cases)?.verdicts,
+            [Verdict::Accepted { stdout, .. }] if &**stdout == "1\n2\n",
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn input_filter() -> anyhow::Result<()> {
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: Some(InputFilter {
+                cmd: "tail -n +2".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            cases: vec![PartialBatchTestCase {
+                name: None,
+                note: None,
+                r#in: "# header\n3 4\n".into(),
+                out: Some("7\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        // Fails on the header.
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), "read a b; echo $((a + b))".into()],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions::default(),
+        )?;
+
+        assert!(matches!(
+            &*outcome.verdicts,
+            [Verdict::Accepted { stdin, .. }] if &**stdin == "3 4\n",
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn expected_stderr() -> anyhow::Result<()> {
+        let case = |name: &str, out: &str, stderr: Option<&str>| PartialBatchTestCase {
+            name: Some(name.to_owned()),
+            note: None,
+            r#in: "".into(),
+            out: Some(out.into()),
+            timelimit: None,
+            r#match: None,
+            stderr: stderr.map(Into::into),
+            stderr_match: Some(Match::SplitWhitespace),
+        };
+
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::EXACT,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![
+                case("ignored", "out\n", None),
+                case("both", "out\n", Some("err  \n")),
+                case("stderr", "out\n", Some("other\n")),
+                case("stdout", "other\n", Some("err\n")),
+            ],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), "echo out; echo err >&2".into()],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions::default(),
+        )?;
+
+        assert!(matches!(
+            &*outcome.verdicts,
+            [
+                Verdict::Accepted { .. },
+                Verdict::Accepted { .. },
+                Verdict::WrongAnswer {
+                    expected_stderr: Some(_),
+                    note: Some(WrongAnswerNote::StderrMismatched),
+                    ..
+                },
+                Verdict::WrongAnswer {
+                    expected_stderr: None,
+                    note: None,
+                    ..
+                },
+            ],
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn tmpdir_per_test_case() -> anyhow::Result<()> {
+        let case = |r#in: &str| PartialBatchTestCase {
+            name: None,
+            note: None,
+            r#in: r#in.into(),
+            out: Some(r#in.into()),
+            timelimit: None,
+            r#match: None,
+            stderr: None,
+            stderr_match: None,
+        };
+
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::EXACT,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![case("1\n"), case("2\n")],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        // Both of the cases write `$TMPDIR/input` and read it back after the other has written.
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec![
+                "-c".into(),
+                r#"cat > "$TMPDIR/input"; sleep 0.5; cat "$TMPDIR/input""#.into(),
+            ],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions::default(),
+        )?;
+
+        assert!(matches!(
+            &*outcome.verdicts,
+            [Verdict::Accepted { .. }, Verdict::Accepted { .. }],
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn tmpdir_is_empty() -> anyhow::Result<()> {
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::EXACT,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: None,
+                note: None,
+                r#in: "1\n".into(),
+                out: Some("".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        // The files of the harness, such as `stdin`, are not in `$TMPDIR`.
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), r#"ls -A "$TMPDIR""#.into()],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions::default(),
+        )?;
+
+        assert!(
+            matches!(&*outcome.verdicts, [Verdict::Accepted { .. }]),
+            "{:?}",
+            outcome.verdicts,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scale_timelimit() -> anyhow::Result<()> {
+        let test_cases = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: Some(Duration::from_millis(200)),
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: None,
+                note: None,
+                r#in: "".into(),
+                out: Some("1\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        }
+        .load_test_cases(
+            &env::current_dir()?,
+            None::<HashSet<String>>,
+            |_| unreachable!(),
+        )?;
+
+        let judge = |scale: Option<f64>| -> anyhow::Result<_> {
+            let mut test_cases = test_cases.clone();
+            if let Some(scale) = scale {
+                test_cases
+                    .iter_mut()
+                    .for_each(|test_case| test_case.scale_timelimit(scale));
+            }
+            let cmd = CommandExpression {
+                program: "bash".into(),
+                args: vec!["-c".into(), "sleep 0.3; echo 1".into()],
+                cwd: env::current_dir()?,
+                env: btreemap!(),
+            };
+            let outcome = super::judge(
+                ProgressDrawTarget::hidden(),
+                std::future::pending,
+                &cmd,
+                &test_cases,
+                JudgeOptions::default(),
+            )?;
+            Ok(outcome.verdicts)
+        };
+
+        assert!(matches!(
+            &judge(None)?[..],
+            [Verdict::TimelimitExceeded { .. }],
+        ));
+        assert!(matches!(&judge(Some(2.0))?[..], [Verdict::Accepted { .. }]));
+        assert!(matches!(&judge(Some(0.0))?[..], [Verdict::Accepted { .. }]));
+        Ok(())
+    }
+
+    #[test]
+    fn judge_interactive() -> anyhow::Result<()> {
+        let test_case = |name: &str, tester: &str, args: &[&str]| InteractiveTestCase {
+            name: Some(name.to_owned()),
+            timelimit: Some(Duration::from_secs(10)),
+            tester: Tester {
+                cmd: tester.to_owned(),
+                shell: CheckerShell::Bash,
+            },
+            args: args.iter().map(|&s| s.to_owned()).collect(),
+        };
+
+        const TESTER: &str = r#"echo "$1"; read -r x; [ "$x" = "$2" ]"#;
+
+        let test_cases = [
+            test_case("Accepted", TESTER, &["3", "6"]),
+            test_case("Wrong answer", TESTER, &["3", "7"]),
+            test_case("Broken tester", "echo broken >&2; exit 1", &[]),
+        ];
+
+        let cmd = CommandExpression {
+            program: "bash".into(),
+            args: vec![
+                "-c".into(),
+                r#"read -r n || exit 0; echo $((n * 2))"#.into(),
+            ],
+            cwd: env::current_dir()?,
+            env: btreemap!(),
+        };
+
+        let outcome = super::judge_interactive(
+            ProgressDrawTarget::hidden(),
+            std::future::pending,
+            &cmd,
+            &test_cases,
+            JudgeOptions::default(),
+        )?;
+
+        match &*outcome.verdicts {
+            [Verdict::Accepted {
+                stdin: stdin1,
+                stdout: stdout1,
+                ..
+            }, Verdict::WrongAnswer {
+                stdin: stdin2,
+                stdout: stdout2,
+                checker_stderr: checker_stderr2,
+                ..
+            }, Verdict::WrongAnswer {
+                stdin: stdin3,
+                stdout: stdout3,
+                checker_stderr: checker_stderr3,
+                ..
+            }] => {
+                assert_eq!(("3\n", "6\n"), (&**stdin1, &**stdout1));
+                assert_eq!(
+                    ("3\n", "6\n", ""),
+                    (&**stdin2, &**stdout2, &**checker_stderr2)
+                );
+                assert_eq!(
+                    ("", "", "broken\n"),
+                    (&**stdin3, &**stdout3, &**checker_stderr3)
+                );
+            }
+            verdicts => panic!("{:?}", verdicts),
+        }
+        Ok(())
+    }
+}
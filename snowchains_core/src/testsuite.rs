@@ -1,15 +1,21 @@
 use anyhow::{bail, ensure, Context as _};
 use camino::Utf8PathBuf;
+use either::Either;
 use humantime_serde::Serde;
 use ignore::{overrides::OverrideBuilder, WalkBuilder};
-use itertools::{EitherOrBoth, Itertools as _};
+use indexmap::IndexMap;
+use itertools::Itertools as _;
 use maplit::hashmap;
-use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest as _, Sha256};
 use std::{
     borrow::Borrow,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    fs,
+    fmt, fs,
     hash::Hash,
+    mem,
     path::Path,
     str::FromStr,
     sync::Arc,
@@ -27,14 +33,69 @@ pub enum TestSuite {
 
 impl TestSuite {
     pub fn to_yaml_pretty(&self) -> String {
+        self.to_yaml_pretty_with(LineEnding::Lf)
+    }
+
+    /// Serializes `self` with `line_ending`, ending with exactly one line break.
+    pub fn to_yaml_pretty_with(&self, line_ending: LineEnding) -> String {
+        line_ending.normalize(&self.to_yaml_pretty_lf(false))
+    }
+
+    /// Same as [`to_yaml_pretty_with`], but writes each test case whose `in` and `out` are single
+    /// short tokens in one line (e.g. `- {in: "5\n", out: "25\n"}`).
+    ///
+    /// [`to_yaml_pretty_with`]: Self::to_yaml_pretty_with
+    pub fn to_yaml_compact_with(&self, line_ending: LineEnding) -> String {
+        line_ending.normalize(&self.to_yaml_pretty_lf(true))
+    }
+
+    /// Sets `editorial`. Does nothing for `Unsubmittable`.
+    pub fn set_editorial(&mut self, editorial: Url) {
+        match self {
+            Self::Batch(suite) => suite.editorial = Some(editorial),
+            Self::Interactive(suite) => suite.editorial = Some(editorial),
+            Self::Unsubmittable => {}
+        }
+    }
+
+    fn to_yaml_pretty_lf(&self, compact: bool) -> String {
         return if let Self::Batch(suite) = self {
             (|| -> _ {
                 let mut yaml = "---\n".to_owned();
 
                 yaml += &key_value("type", "Batch").ok()?;
+
+                if let Some(description) = &suite.description {
+                    yaml += &key_value_in_literal_style("description", description).ok()?;
+                }
+
+                if let Some(editorial) = &suite.editorial {
+                    yaml += &key_value("editorial", editorial).ok()?;
+                }
+
                 yaml += &key_value("timelimit", Serde::from(suite.timelimit)).ok()?;
                 yaml += &key_value("match", &suite.r#match).ok()?;
 
+                if let Some(reference) = &suite.reference {
+                    yaml += &key_value("reference", reference).ok()?;
+                }
+
+                if let Some(generator) = &suite.generator {
+                    yaml += &key_value("generator", generator).ok()?;
+                }
+
+                if let Some(input_filter) = &suite.input_filter {
+                    yaml += &key_value("input_filter", input_filter).ok()?;
+                }
+
+                if let Some(cases_from) = &suite.cases_from {
+                    yaml += &key_value("cases_from", cases_from).ok()?;
+                }
+
+                if !suite.exclude.is_empty() {
+                    yaml += &key_value("exclude", &suite.exclude).ok()?;
+                }
+
                 yaml += if suite.cases.is_empty() {
                     "\ncases: []\n"
                 } else {
@@ -42,12 +103,23 @@ impl TestSuite {
                 };
 
                 for case in &suite.cases {
+                    if let Some(compact) = compact.then(|| compact_case(case)).flatten() {
+                        yaml += "  - ";
+                        yaml += &compact;
+                        yaml += "\n";
+                        continue;
+                    }
+
                     let mut part = "".to_owned();
 
                     if let Some(name) = &case.name {
                         part += &key_value("name", name).ok()?;
                     }
 
+                    if let Some(note) = &case.note {
+                        part += &key_value("note", note).ok()?;
+                    }
+
                     part += &key_value_in_literal_style("in", &case.r#in).ok()?;
 
                     if let Some(out) = &case.out {
@@ -62,6 +134,14 @@ impl TestSuite {
                         part += &key_value("match", r#match).ok()?;
                     }
 
+                    if let Some(stderr) = &case.stderr {
+                        part += &key_value_in_literal_style("stderr", stderr).ok()?;
+                    }
+
+                    if let Some(stderr_match) = &case.stderr_match {
+                        part += &key_value("stderr_match", stderr_match).ok()?;
+                    }
+
                     for (i, line) in part.lines().enumerate() {
                         yaml += match i {
                             0 => "  - ",
@@ -100,6 +180,49 @@ impl TestSuite {
         }
         .unwrap_or_else(|e| panic!("failed to serialize: {}", e));
 
+        /// Renders a case in the flow style (e.g. `{in: "5\n", out: "25\n"}`) if it only has `name`,
+        /// `in`, and `out`, and `in` and `out` are single short tokens.
+        fn compact_case(case: &PartialBatchTestCase) -> Option<String> {
+            const MAX_TOKEN_LEN: usize = 16;
+
+            let (name, r#in, out) = match case {
+                PartialBatchTestCase {
+                    name,
+                    note: None,
+                    r#in,
+                    out: Some(out),
+                    timelimit: None,
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                } => (name, r#in, out),
+                _ => return None,
+            };
+
+            let is_short_token = |s: &str| {
+                s.strip_suffix('\n').is_some_and(|token| {
+                    !token.is_empty()
+                        && token.chars().count() <= MAX_TOKEN_LEN
+                        && !token.chars().any(|c| c.is_whitespace() || c.is_control())
+                })
+            };
+
+            if !(is_short_token(r#in) && is_short_token(out)) {
+                return None;
+            }
+
+            let mut acc = "{".to_owned();
+            if let Some(name) = name {
+                acc += &format!("name: {}, ", serde_json::to_string(name).ok()?);
+            }
+            acc += &format!(
+                "in: {}, out: {}}}",
+                serde_json::to_string(&**r#in).ok()?,
+                serde_json::to_string(&**out).ok()?,
+            );
+            Some(acc)
+        }
+
         fn key_value(key: impl Serialize, value: impl Serialize) -> serde_yaml::Result<String> {
             let key = serde_yaml::to_value(key)?;
             let mut acc = serde_yaml::to_string(&hashmap!(key => value))?;
@@ -149,13 +272,66 @@ impl TestSuite {
     }
 }
 
+/// Line ending of saved test suites.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    /// CRLF on Windows, LF otherwise.
+    Native,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+            Self::Native if cfg!(windows) => "\r\n",
+            Self::Native => "\n",
+        }
+    }
+
+    /// Converts every line break in `text` to `self` and ensures exactly one trailing line break.
+    pub fn normalize(self, text: &str) -> String {
+        let eol = self.as_str();
+        let mut acc = text
+            .trim_end_matches(['\n', '\r'])
+            .replace("\r\n", "\n")
+            .replace('\n', eol);
+        acc += eol;
+        acc
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct BatchTestSuite {
-    #[serde(default, with = "humantime_serde")]
+    /// Notes on the test suite, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// URL of the editorial, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editorial: Option<Url>,
+    #[serde(default, with = "timelimit")]
     pub timelimit: Option<Duration>,
     pub r#match: Match,
+    /// Produces the expected outputs of the cases without `out`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<Reference>,
+    /// Generates random inputs for `judge::stress`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<Generator>,
+    /// Transforms the input of each case before it is given to the solver.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_filter: Option<InputFilter>,
     #[serde(default)]
     pub cases: Vec<PartialBatchTestCase>,
+    /// YAML file of the test cases, relative to the test suite. Used instead of `cases`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cases_from: Option<Utf8PathBuf>,
+    /// Globs of the names of the test cases to leave out (e.g. `*large*`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
     #[serde(default)]
     pub extend: Vec<Additional>,
 }
@@ -164,26 +340,91 @@ impl BatchTestSuite {
     pub fn load_test_cases<
         S: Borrow<str> + Eq + Hash,
         F: FnMut(Option<&Url>) -> anyhow::Result<Vec<PartialBatchTestCase>>,
+    >(
+        &self,
+        parent_dir: &Path,
+        names: Option<HashSet<S>>,
+        prepare_system_test_cases: F,
+    ) -> anyhow::Result<Vec<BatchTestCase>> {
+        self.load_test_cases_with_num_excluded(parent_dir, names, prepare_system_test_cases)
+            .map(|(cases, _)| cases)
+    }
+
+    /// Same as [`load_test_cases`], but also returns the number of the test cases left out by
+    /// `exclude`.
+    ///
+    /// The test cases in `names` are not left out.
+    ///
+    /// [`load_test_cases`]: Self::load_test_cases
+    pub fn load_test_cases_with_num_excluded<
+        S: Borrow<str> + Eq + Hash,
+        F: FnMut(Option<&Url>) -> anyhow::Result<Vec<PartialBatchTestCase>>,
     >(
         &self,
         parent_dir: &Path,
         mut names: Option<HashSet<S>>,
         mut prepare_system_test_cases: F,
-    ) -> anyhow::Result<Vec<BatchTestCase>> {
-        let mut cases = self.cases.clone();
+    ) -> anyhow::Result<(Vec<BatchTestCase>, usize)> {
+        let exclude = {
+            let mut builder = OverrideBuilder::new("");
+            for glob in &self.exclude {
+                builder
+                    .add(glob)
+                    .with_context(|| format!("Invalid glob in `exclude`: {:?}", glob))?;
+            }
+            builder.build()?
+        };
+        let mut num_excluded = 0;
+
+        let mut cases = if let Some(cases_from) = &self.cases_from {
+            ensure!(
+                self.cases.is_empty(),
+                "`cases` and `cases_from` cannot be used together",
+            );
+            let path = Path::new(cases_from);
+            let path = parent_dir.join(path.strip_prefix(".").unwrap_or(path));
+            let yaml = fs::read_to_string(&path)
+                .with_context(|| format!("Could not read {}", path.display()))?;
+            serde_yaml::from_str::<Vec<PartialBatchTestCase>>(&yaml)
+                .with_context(|| format!("Could not parse {}", path.display()))?
+        } else {
+            self.cases.clone()
+        };
         for extend in &self.extend {
             cases.extend(extend.load_test_cases(parent_dir, &mut prepare_system_test_cases)?);
         }
 
+        if let Some(PartialBatchTestCase { name, .. }) = cases
+            .iter()
+            .find(|case| matches!(case.stderr_match, Some(Match::Checker { .. })))
+        {
+            bail!(
+                "`stderr_match` cannot be `Checker`: {:?}",
+                name.as_deref().unwrap_or(""),
+            );
+        }
+
         let cases = cases
             .into_iter()
             .filter(
                 |PartialBatchTestCase { name, .. }| match (names.as_mut(), name.as_ref()) {
                     (Some(names), Some(name)) => names.remove(name),
+                    (None, Some(name)) if exclude.matched(name, false).is_whitelist() => {
+                        num_excluded += 1;
+                        false
+                    }
                     _ => true,
                 },
             )
-            .map(|case| BatchTestCase::new(case, self.timelimit, &self.r#match))
+            .map(|case| {
+                BatchTestCase::new(
+                    case,
+                    self.timelimit,
+                    &self.r#match,
+                    self.reference.as_ref(),
+                    self.input_filter.as_ref(),
+                )
+            })
             .collect();
 
         if let Some(names) = names {
@@ -195,20 +436,150 @@ impl BatchTestSuite {
             }
         }
 
-        Ok(cases)
+        Ok((cases, num_excluded))
+    }
+
+    /// Pairs the files in `dir` matching `in` and `out` by their file stems in the same way as
+    /// `extend: [{ type: Text }]`, and appends them to `cases`.
+    ///
+    /// Returns the number of the appended test cases. Fails if `cases_from` is set, since `cases`
+    /// cannot be used with it.
+    pub fn append_text_files(
+        &mut self,
+        dir: &Path,
+        r#in: &str,
+        out: &str,
+    ) -> anyhow::Result<usize> {
+        if let Some(cases_from) = &self.cases_from {
+            bail!(
+                "The test cases are loaded from `{}` (`cases_from`). Add them to it instead",
+                cases_from,
+            );
+        }
+
+        let text = Additional::Text {
+            path: ".".into(),
+            r#in: r#in.to_owned(),
+            out: out.to_owned(),
+            timelimit: None,
+            r#match: None,
+        };
+
+        let cases = text.load_test_cases(dir, |_| unreachable!())?;
+        let num_cases = cases.len();
+        self.cases.extend(cases);
+        Ok(num_cases)
+    }
+
+    /// Converts into a scaffold of an interactive test suite.
+    ///
+    /// `description` and `timelimit` are kept, and `cases` are put into `example` in order. Returns
+    /// the fields that cannot be kept and are dropped, as well.
+    pub fn to_interactive(&self) -> (InteractiveTestSuite, Vec<&'static str>) {
+        let mut dropped = vec![];
+        if self.r#match != Match::Lines {
+            dropped.push("match");
+        }
+        if self.reference.is_some() {
+            dropped.push("reference");
+        }
+        if self.generator.is_some() {
+            dropped.push("generator");
+        }
+        if self.input_filter.is_some() {
+            dropped.push("input_filter");
+        }
+        if self.cases_from.is_some() {
+            dropped.push("cases_from");
+        }
+        if !self.exclude.is_empty() {
+            dropped.push("exclude");
+        }
+        if !self.extend.is_empty() {
+            dropped.push("extend");
+        }
+        if self.cases.iter().any(|case| case.name.is_some()) {
+            dropped.push("cases[].name");
+        }
+        if self.cases.iter().any(|case| case.note.is_some()) {
+            dropped.push("cases[].note");
+        }
+        if self.cases.iter().any(|case| case.timelimit.is_some()) {
+            dropped.push("cases[].timelimit");
+        }
+        if self.cases.iter().any(|case| case.r#match.is_some()) {
+            dropped.push("cases[].match");
+        }
+        if self.cases.iter().any(|case| case.stderr.is_some()) {
+            dropped.push("cases[].stderr");
+        }
+        if self.cases.iter().any(|case| case.stderr_match.is_some()) {
+            dropped.push("cases[].stderr_match");
+        }
+
+        let example = self
+            .cases
+            .iter()
+            .flat_map(|case| {
+                let ins = case.r#in.lines().map(|l| Interaction::In(l.to_owned()));
+                let outs = case.out.iter().flat_map(|out| out.lines());
+                ins.chain(outs.map(|l| Interaction::Out(l.to_owned())))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let test_suite = InteractiveTestSuite {
+            description: self.description.clone(),
+            editorial: self.editorial.clone(),
+            timelimit: self.timelimit,
+            tester: None,
+            each_args: vec![],
+            example,
+        };
+
+        (test_suite, dropped)
     }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct PartialBatchTestCase {
     pub name: Option<String>,
+    /// A note on the test case, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
     #[serde(with = "serde_fn::arc_str")]
     pub r#in: Arc<str>,
     #[serde(default, with = "serde_fn::option_arc_str")]
     pub out: Option<Arc<str>>,
-    #[serde(default, with = "humantime_serde")]
+    #[serde(default, with = "timelimit")]
     pub timelimit: Option<Duration>,
     pub r#match: Option<Match>,
+    /// Expected stderr. stderr is not judged if this is absent.
+    #[serde(
+        default,
+        with = "serde_fn::option_arc_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stderr: Option<Arc<str>>,
+    /// How `stderr` is compared. Defaults to `Exact`. `Checker` is not allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_match: Option<Match>,
+}
+
+impl PartialBatchTestCase {
+    /// SHA-256 in hex of `in`, `out`, `stderr`, and how they are compared, with `match` and
+    /// `reference` of `test_suite` applied. It identifies the test case regardless of its `name`
+    /// and position, so that it can be tracked across edits which reorder the test cases.
+    pub fn content_hash(&self, test_suite: &BatchTestSuite) -> String {
+        BatchTestCase::new(
+            self.clone(),
+            None,
+            &test_suite.r#match,
+            test_suite.reference.as_ref(),
+            None,
+        )
+        .content_hash()
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
@@ -218,11 +589,7 @@ pub enum Additional {
         path: Utf8PathBuf,
         r#in: String,
         out: String,
-        #[serde(
-            default,
-            with = "humantime_serde",
-            skip_serializing_if = "Option::is_none"
-        )]
+        #[serde(default, with = "timelimit", skip_serializing_if = "Option::is_none")]
         timelimit: Option<Duration>,
         #[serde(skip_serializing_if = "Option::is_none")]
         r#match: Option<Match>,
@@ -255,46 +622,49 @@ impl Additional {
 
                 let mut cases = BTreeMap::<_, (Option<_>, Option<_>)>::new();
 
-                let walk = |overrides| -> _ {
-                    WalkBuilder::new(base)
-                        .max_depth(Some(128))
-                        .overrides(overrides)
-                        .standard_filters(false)
-                        .build()
-                        .map::<anyhow::Result<_>, _>(|entry| {
-                            let path = entry?.into_path();
-
-                            if path.is_dir() {
-                                return Ok(None);
-                            }
+                let in_matcher = OverrideBuilder::new(base).add(r#in)?.build()?;
+                let out_matcher = OverrideBuilder::new(base).add(out)?.build()?;
 
-                            let name = path
-                                .file_stem()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .into_owned();
+                // Walk `base` once and classify each file, rather than walking it once per glob.
+                for entry in WalkBuilder::new(base)
+                    .max_depth(Some(128))
+                    .standard_filters(false)
+                    .build()
+                {
+                    let path = entry?.into_path();
 
-                            let content = fs::read_to_string(&path)
-                                .with_context(|| format!("Could not read {}", path.display()))?
-                                .into();
+                    if path.is_dir() {
+                        continue;
+                    }
 
-                            Ok(Some((name, content)))
-                        })
-                        .flat_map(Result::transpose)
-                };
+                    let is_in = in_matcher.matched(&path, false).is_whitelist();
+                    let is_out = out_matcher.matched(&path, false).is_whitelist();
 
-                for result in walk(OverrideBuilder::new(base).add(r#in)?.build()?) {
-                    let (name, content) = result?;
-                    let (entry, _) = cases.entry(name.clone()).or_default();
-                    ensure!(entry.is_none(), "Duplicated name: {:?}", name);
-                    *entry = Some(content);
-                }
+                    if !(is_in || is_out) {
+                        continue;
+                    }
+
+                    let name = path
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned();
 
-                for result in walk(OverrideBuilder::new(base).add(out)?.build()?) {
-                    let (name, content) = result?;
-                    let (_, entry) = cases.entry(name.clone()).or_default();
-                    ensure!(entry.is_none(), "Duplicated name: {:?}", name);
-                    *entry = Some(content);
+                    let content = Arc::<str>::from(
+                        fs::read_to_string(&path)
+                            .with_context(|| format!("Could not read {}", path.display()))?,
+                    );
+
+                    let (in_entry, out_entry) = cases.entry(name.clone()).or_default();
+
+                    if is_in {
+                        ensure!(in_entry.is_none(), "Duplicated name: {:?}", name);
+                        *in_entry = Some(content.clone());
+                    }
+                    if is_out {
+                        ensure!(out_entry.is_none(), "Duplicated name: {:?}", name);
+                        *out_entry = Some(content);
+                    }
                 }
 
                 cases
@@ -308,10 +678,13 @@ impl Additional {
 
                         Ok(PartialBatchTestCase {
                             name: Some(name),
+                            note: None,
                             r#in,
                             out,
                             timelimit: *timelimit,
                             r#match: r#match.clone(),
+                            stderr: None,
+                            stderr_match: None,
                         })
                     })
                     .collect()
@@ -322,8 +695,16 @@ impl Additional {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(remote = "Self")]
 pub enum Match {
-    Exact,
+    Exact {
+        /// Ignores whitespace at the end of each line. The number of lines must still match.
+        #[serde(default)]
+        ignore_trailing_whitespace: bool,
+        /// How runs of blank lines are treated, on both sides.
+        #[serde(default, skip_serializing_if = "BlankLines::is_keep")]
+        blank_lines: BlankLines,
+    },
     SplitWhitespace,
     Lines,
     Float {
@@ -336,15 +717,337 @@ pub enum Match {
     },
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+impl Match {
+    pub(crate) const EXACT: Self = Self::Exact {
+        ignore_trailing_whitespace: false,
+        blank_lines: BlankLines::Keep,
+    };
+}
+
+/// How [`Match::Exact`] treats runs of blank lines before comparing.
+///
+/// A line is blank if it is empty, after trimming it if `ignore_trailing_whitespace`.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlankLines {
+    /// Compares them as they are.
+    #[default]
+    Keep,
+    /// Collapses each run of blank lines into a single blank line.
+    Collapse,
+    /// Removes all of the blank lines.
+    Remove,
+}
+
+impl BlankLines {
+    fn is_keep(&self) -> bool {
+        *self == Self::Keep
+    }
+}
+
+impl<'de> Deserialize<'de> for Match {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // `match: Exact` is a unit variant, which cannot be deserialized as a struct variant.
+        match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::String(s) if s == "Exact" => Ok(Self::EXACT),
+            value => Self::deserialize(value).map_err(D::Error::custom),
+        }
+    }
+}
+
+impl Serialize for Match {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if *self == Self::EXACT {
+            serializer.serialize_unit_variant("Match", 0, "Exact")
+        } else {
+            Self::serialize(self, serializer)
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum CheckerShell {
     Bash,
 }
 
+/// A known-correct command (e.g. a brute force) which reads the input from stdin and writes the
+/// expected output to stdout.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub cmd: String,
+    pub shell: CheckerShell,
+}
+
+/// A command which writes a random input to stdout. The seed is given as `$SEED`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Generator {
+    pub cmd: String,
+    pub shell: CheckerShell,
+}
+
+/// A command which reads the input of a test case from stdin and writes the input given to the
+/// solver to stdout (e.g. `tail -n +2` to strip a header line).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputFilter {
+    pub cmd: String,
+    pub shell: CheckerShell,
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct InteractiveTestSuite {
-    #[serde(default, with = "humantime_serde")]
+    /// Notes on the test suite, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// URL of the editorial, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editorial: Option<Url>,
+    #[serde(default, with = "timelimit")]
+    pub timelimit: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tester: Option<Tester>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub each_args: Vec<TesterArgs>,
+    /// An example of the interaction, for reference. It is not used for judging.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub example: Vec<Interaction>,
+}
+
+/// A line of an interaction, from the point of view of the solver.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Interaction {
+    /// A line the solver reads from the tester.
+    In(String),
+    /// A line the solver writes to the tester.
+    Out(String),
+}
+
+impl fmt::Display for Interaction {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::In(line) => write!(fmt, "< {}", line),
+            Self::Out(line) => write!(fmt, "> {}", line),
+        }
+    }
+}
+
+impl InteractiveTestSuite {
+    /// Converts into a batch test suite.
+    ///
+    /// `description` and `timelimit` are kept, and `example` becomes a test case. Returns the
+    /// fields that cannot be kept and are dropped, as well.
+    pub fn to_batch(&self) -> (BatchTestSuite, Vec<&'static str>) {
+        let mut dropped = vec![];
+        if self.tester.is_some() {
+            dropped.push("tester");
+        }
+        if !self.each_args.is_empty() {
+            dropped.push("each_args");
+        }
+
+        let lines = |f: fn(&Interaction) -> Option<&str>| -> String {
+            self.example
+                .iter()
+                .flat_map(f)
+                .map(|line| format!("{}\n", line))
+                .collect()
+        };
+
+        let cases = if self.example.is_empty() {
+            vec![]
+        } else {
+            vec![PartialBatchTestCase {
+                name: Some("example".to_owned()),
+                note: None,
+                r#in: lines(|i| match i {
+                    Interaction::In(line) => Some(line),
+                    Interaction::Out(_) => None,
+                })
+                .into(),
+                out: Some(
+                    lines(|i| match i {
+                        Interaction::In(_) => None,
+                        Interaction::Out(line) => Some(line),
+                    })
+                    .into(),
+                ),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }]
+        };
+
+        let test_suite = BatchTestSuite {
+            description: self.description.clone(),
+            editorial: self.editorial.clone(),
+            timelimit: self.timelimit,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases,
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        (test_suite, dropped)
+    }
+
+    /// Loads the test cases.
+    ///
+    /// `default_tester` is used if `tester` is not set.
+    pub fn load_test_cases<S: Borrow<str> + Eq + Hash>(
+        &self,
+        default_tester: Option<&Tester>,
+        mut names: Option<HashSet<S>>,
+    ) -> anyhow::Result<Vec<InteractiveTestCase>> {
+        let tester = self.tester.as_ref().or(default_tester).with_context(|| {
+            "`tester` is not set. Write a tester and set `tester` in the test suite, or run \
+             `snowchains retrieve testcases --tester-stub` to generate a stub of it"
+        })?;
+
+        let new_test_case = |name, args: &TesterArgs| -> anyhow::Result<_> {
+            Ok(InteractiveTestCase {
+                name,
+                timelimit: self.timelimit,
+                tester: Tester {
+                    cmd: args.expand(&tester.cmd)?,
+                    shell: tester.shell,
+                },
+                args: args.positional(),
+            })
+        };
+
+        let cases = if self.each_args.is_empty() {
+            vec![new_test_case(None, &TesterArgs::Positional(vec![]))?]
+        } else {
+            self.each_args
+                .iter()
+                .enumerate()
+                .map(|(i, args)| (Some((i + 1).to_string()), args))
+                .filter(|(name, _)| match (names.as_mut(), name.as_ref()) {
+                    (Some(names), Some(name)) => names.remove(name),
+                    _ => true,
+                })
+                .map(|(name, args)| {
+                    new_test_case(name.clone(), args)
+                        .with_context(|| format!("Could not expand `tester` for {:?}", name))
+                })
+                .collect::<anyhow::Result<_>>()?
+        };
+
+        if let Some(names) = names {
+            if !names.is_empty() {
+                bail!(
+                    "No such test cases: {:?}",
+                    names.iter().map(Borrow::borrow).collect::<BTreeSet<_>>(),
+                );
+            }
+        }
+
+        Ok(cases)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Tester {
+    pub cmd: String,
+    pub shell: CheckerShell,
+}
+
+/// Arguments for a tester.
+///
+/// In addition to `$1`, `$2`, ... and `$@` of the shell, `cmd` of the tester can contain:
+///
+/// - `${args}`: all of the arguments
+/// - `${arg[N]}`: the `N`th argument (1-based)
+/// - `${NAME}`: the argument named `NAME` (for `Named`)
+///
+/// They are replaced with shell-escaped values.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TesterArgs {
+    Positional(Vec<String>),
+    Named(IndexMap<String, String>),
+}
+
+impl TesterArgs {
+    fn positional(&self) -> Vec<String> {
+        match self {
+            Self::Positional(args) => args.clone(),
+            Self::Named(args) => args.values().cloned().collect(),
+        }
+    }
+
+    fn expand(&self, cmd: &str) -> anyhow::Result<String> {
+        let args = self.positional();
+
+        let mut expanded = "".to_owned();
+        let mut last_end = 0;
+
+        static VARIABLE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"\$\{(args|arg\[([0-9]+)\]|[a-zA-Z_][a-zA-Z0-9_]*)\}").unwrap()
+        });
+
+        for caps in VARIABLE.captures_iter(cmd) {
+            let whole = caps.get(0).unwrap();
+
+            let value = if &caps[1] == "args" {
+                args.iter().map(|s| sh_quote(s)).join(" ")
+            } else if let Some(n) = caps.get(2) {
+                let n = n.as_str().parse::<usize>().ok().filter(|&n| n > 0);
+                let arg = n.and_then(|n| args.get(n - 1)).with_context(|| {
+                    format!(
+                        "`{}` is out of range ({} argument(s) provided)",
+                        whole.as_str(),
+                        args.len(),
+                    )
+                })?;
+                sh_quote(arg)
+            } else if let Self::Named(named) = self {
+                match named.get(&caps[1]) {
+                    Some(arg) => sh_quote(arg),
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+
+            expanded += &cmd[last_end..whole.start()];
+            expanded += &value;
+            last_end = whole.end();
+        }
+
+        expanded += &cmd[last_end..];
+        return Ok(expanded);
+
+        fn sh_quote(s: &str) -> String {
+            format!("'{}'", s.replace('\'', r"'\''"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractiveTestCase {
+    pub name: Option<String>,
     pub timelimit: Option<Duration>,
+    pub tester: Tester,
+    pub args: Vec<String>,
+}
+
+impl InteractiveTestCase {
+    /// Multiplies `timelimit` by `scale`. If `scale` is `0` or the result overflows, removes
+    /// `timelimit`.
+    pub fn scale_timelimit(&mut self, scale: f64) {
+        self.timelimit = scale_timelimit(self.timelimit, scale);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -353,19 +1056,209 @@ pub struct BatchTestCase {
     pub timelimit: Option<Duration>,
     pub input: Arc<str>,
     pub output: ExpectedOutput,
+    /// Expected stderr. `None` if stderr is not judged.
+    pub expected_stderr: Option<DeterministicExpectedOutput>,
+    /// Converts CRLF in the output of the solver into LF before comparing.
+    pub trim_cr: bool,
+    /// Transforms `input` before it is given to the solver and the reference.
+    pub input_filter: Option<InputFilter>,
 }
 
 impl BatchTestCase {
-    fn new(case: PartialBatchTestCase, timelimit: Option<Duration>, matching: &Match) -> Self {
+    fn new(
+        case: PartialBatchTestCase,
+        timelimit: Option<Duration>,
+        matching: &Match,
+        reference: Option<&Reference>,
+        input_filter: Option<&InputFilter>,
+    ) -> Self {
+        let matching = case.r#match.unwrap_or_else(|| matching.clone());
+
+        let output = match (case.out, reference) {
+            (None, Some(reference)) => ExpectedOutput::Reference {
+                reference: reference.clone(),
+                r#match: matching,
+            },
+            (out, _) => ExpectedOutput::new(out, matching),
+        };
+
+        let stderr_matching = case.stderr_match.unwrap_or(Match::EXACT);
+        let expected_stderr =
+            case.stderr.map(
+                |text| match ExpectedOutput::new(Some(text), stderr_matching) {
+                    ExpectedOutput::Deterministic(expected) => expected,
+                    _ => unreachable!("`Checker` should have been rejected"),
+                },
+            );
+
         BatchTestCase {
             name: case.name,
             timelimit: case.timelimit.or(timelimit),
             input: case.r#in,
-            output: ExpectedOutput::new(case.out, case.r#match.unwrap_or_else(|| matching.clone())),
+            output,
+            expected_stderr,
+            trim_cr: false,
+            input_filter: input_filter.cloned(),
+        }
+    }
+
+    /// Converts CRLF into LF in `input`, the expected outputs, and the outputs of the solver.
+    pub fn set_trim_cr(&mut self) {
+        self.input = crlf_to_lf(&self.input);
+        self.output.trim_cr();
+        if let Some(expected_stderr) = &mut self.expected_stderr {
+            expected_stderr.trim_cr();
+        }
+        self.trim_cr = true;
+    }
+
+    /// Replaces how the output is compared with `matching`, keeping the expected output. `None`
+    /// accepts any output.
+    pub fn set_match(&mut self, matching: Option<Match>) {
+        self.output = mem::replace(
+            &mut self.output,
+            ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+        )
+        .with_match(matching);
+    }
+
+    /// Multiplies `timelimit` by `scale`. If `scale` is `0` or the result overflows, removes
+    /// `timelimit`.
+    pub fn scale_timelimit(&mut self, scale: f64) {
+        self.timelimit = scale_timelimit(self.timelimit, scale);
+    }
+
+    pub fn expected_kind(&self) -> ExpectedOutputKind {
+        self.output.kind()
+    }
+
+    /// The same as [`PartialBatchTestCase::content_hash`] of the test case this was loaded from,
+    /// unless `set_trim_cr` or `set_match` changed it.
+    pub fn content_hash(&self) -> String {
+        let (stdout, stdout_match) = self.output.text_and_match();
+        let (stderr, stderr_match) = self
+            .expected_stderr
+            .as_ref()
+            .map_or((None, None), DeterministicExpectedOutput::text_and_match);
+
+        let content = (&*self.input, stdout, stdout_match, stderr, stderr_match);
+        let content = serde_json::to_vec(&content).expect("should not fail");
+        hex::encode(Sha256::digest(&content))
+    }
+}
+
+/// Test cases loaded from a test suite.
+///
+/// ```
+/// use snowchains_core::testsuite::{TestCases, TestSuite};
+/// use std::{collections::HashSet, path::Path};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let test_suite = serde_yaml::from_str::<TestSuite>(
+///     r#"
+/// type: Batch
+/// match: Lines
+/// cases:
+///   - name: Sample 1
+///     in: "1 2\n"
+///     out: "3\n"
+///   - name: Sample 2
+///     in: "3 4\n"
+/// "#,
+/// )?;
+///
+/// let test_cases = match test_suite {
+///     TestSuite::Batch(test_suite) => TestCases::Batch(test_suite.load_test_cases(
+///         Path::new("."),
+///         None::<HashSet<String>>,
+///         |_| unimplemented!(),
+///     )?),
+///     TestSuite::Interactive(test_suite) => {
+///         TestCases::Interactive(test_suite.load_test_cases(None, None::<HashSet<String>>)?)
+///     }
+///     TestSuite::Unsubmittable => unreachable!(),
+/// };
+///
+/// assert_eq!(2, test_cases.len());
+///
+/// for test_case in test_cases.iter() {
+///     println!("{:?}: {:?}", test_case.name, test_case.expected);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestCases {
+    Batch(Vec<BatchTestCase>),
+    Interactive(Vec<InteractiveTestCase>),
+}
+
+impl TestCases {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Batch(test_cases) => test_cases.len(),
+            Self::Interactive(test_cases) => test_cases.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the names and the metadata of the test cases.
+    pub fn iter(&self) -> impl Iterator<Item = TestCaseMeta<'_>> {
+        match self {
+            Self::Batch(test_cases) => {
+                Either::Left(test_cases.iter().map(|test_case| TestCaseMeta {
+                    name: test_case.name.as_deref(),
+                    timelimit: test_case.timelimit,
+                    expected: Some(test_case.expected_kind()),
+                }))
+            }
+            Self::Interactive(test_cases) => {
+                Either::Right(test_cases.iter().map(|test_case| TestCaseMeta {
+                    name: test_case.name.as_deref(),
+                    timelimit: test_case.timelimit,
+                    expected: None,
+                }))
+            }
+        }
+    }
+
+    /// Multiplies `timelimit` of each test case by `scale`. If `scale` is `0` or the results
+    /// overflow, removes them.
+    pub fn scale_timelimit(&mut self, scale: f64) {
+        match self {
+            Self::Batch(test_cases) => test_cases
+                .iter_mut()
+                .for_each(|test_case| test_case.scale_timelimit(scale)),
+            Self::Interactive(test_cases) => test_cases
+                .iter_mut()
+                .for_each(|test_case| test_case.scale_timelimit(scale)),
         }
     }
 }
 
+/// An item of `TestCases::iter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestCaseMeta<'a> {
+    pub name: Option<&'a str>,
+    pub timelimit: Option<Duration>,
+    /// `None` for interactive test cases.
+    pub expected: Option<ExpectedOutputKind>,
+}
+
+pub(crate) fn crlf_to_lf(s: &str) -> Arc<str> {
+    s.replace("\r\n", "\n").into()
+}
+
+fn scale_timelimit(timelimit: Option<Duration>, scale: f64) -> Option<Duration> {
+    // A scaled `timelimit` that does not fit in `Duration` is as good as no `timelimit`.
+    timelimit
+        .filter(|_| scale > 0.0)
+        .and_then(|t| Duration::try_from_secs_f64(t.as_secs_f64() * scale).ok())
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExpectedOutput {
     Deterministic(DeterministicExpectedOutput),
@@ -374,19 +1267,32 @@ pub enum ExpectedOutput {
         cmd: String,
         shell: CheckerShell,
     },
+    /// Not yet produced. `judge` runs `reference` to get the expected output.
+    Reference {
+        reference: Reference,
+        r#match: Match,
+    },
 }
 
 impl ExpectedOutput {
-    fn new(text: Option<Arc<str>>, matching: Match) -> Self {
+    pub(crate) fn new(text: Option<Arc<str>>, matching: Match) -> Self {
         match (text, matching) {
             (text, Match::Checker { cmd, shell }) => Self::Checker { text, cmd, shell },
-            (Some(text), Match::Exact) => {
-                Self::Deterministic(DeterministicExpectedOutput::Exact { text })
-            }
-            (Some(text), Match::SplitWhitespace) => {
-                Self::Deterministic(DeterministicExpectedOutput::SplitWhitespace { text })
-            }
-            (Some(text), Match::Lines) => {
+            (
+                Some(text),
+                Match::Exact {
+                    ignore_trailing_whitespace,
+                    blank_lines,
+                },
+            ) => Self::Deterministic(DeterministicExpectedOutput::Exact {
+                text,
+                ignore_trailing_whitespace,
+                blank_lines,
+            }),
+            (Some(text), Match::SplitWhitespace) => {
+                Self::Deterministic(DeterministicExpectedOutput::SplitWhitespace { text })
+            }
+            (Some(text), Match::Lines) => {
                 Self::Deterministic(DeterministicExpectedOutput::Lines { text })
             }
             (
@@ -404,6 +1310,44 @@ impl ExpectedOutput {
         }
     }
 
+    fn with_match(self, matching: Option<Match>) -> Self {
+        match (self, matching) {
+            (_, None) => Self::Deterministic(DeterministicExpectedOutput::Pass),
+            (Self::Deterministic(expected), Some(matching)) => {
+                Self::new(expected.expected_stdout().map(Into::into), matching)
+            }
+            (Self::Checker { text, .. }, Some(matching)) => Self::new(text, matching),
+            (Self::Reference { reference, .. }, Some(matching)) => Self::Reference {
+                reference,
+                r#match: matching,
+            },
+        }
+    }
+
+    pub(crate) fn trim_cr(&mut self) {
+        match self {
+            Self::Deterministic(expected) => expected.trim_cr(),
+            Self::Checker { text: None, .. } | Self::Reference { .. } => {}
+            Self::Checker {
+                text: Some(text), ..
+            } => *text = crlf_to_lf(text),
+        }
+    }
+
+    fn text_and_match(&self) -> (Option<&str>, Option<Match>) {
+        match self {
+            Self::Deterministic(expected) => expected.text_and_match(),
+            Self::Checker { text, cmd, shell } => (
+                text.as_deref(),
+                Some(Match::Checker {
+                    cmd: cmd.clone(),
+                    shell: *shell,
+                }),
+            ),
+            Self::Reference { r#match, .. } => (None, Some(r#match.clone())),
+        }
+    }
+
     pub(crate) fn is_float(&self) -> bool {
         matches!(
             self,
@@ -414,7 +1358,7 @@ impl ExpectedOutput {
     pub(crate) fn expected_stdout(&self) -> Option<&str> {
         match self {
             Self::Deterministic(expected) => expected.expected_stdout(),
-            Self::Checker { .. } => None,
+            Self::Checker { .. } | Self::Reference { .. } => None,
         }
     }
 
@@ -424,6 +1368,40 @@ impl ExpectedOutput {
             _ => None,
         }
     }
+
+    pub fn kind(&self) -> ExpectedOutputKind {
+        match self {
+            Self::Deterministic(DeterministicExpectedOutput::Pass) => ExpectedOutputKind::Pass,
+            Self::Deterministic(DeterministicExpectedOutput::Exact { .. }) => {
+                ExpectedOutputKind::Exact
+            }
+            Self::Deterministic(DeterministicExpectedOutput::SplitWhitespace { .. }) => {
+                ExpectedOutputKind::SplitWhitespace
+            }
+            Self::Deterministic(DeterministicExpectedOutput::Lines { .. }) => {
+                ExpectedOutputKind::Lines
+            }
+            Self::Deterministic(DeterministicExpectedOutput::Float { .. }) => {
+                ExpectedOutputKind::Float
+            }
+            Self::Checker { .. } => ExpectedOutputKind::Checker,
+            Self::Reference { .. } => ExpectedOutputKind::Reference,
+        }
+    }
+}
+
+/// How the output of a test case is judged, without the expected text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExpectedOutputKind {
+    /// No expected output. Any output is accepted.
+    Pass,
+    Exact,
+    SplitWhitespace,
+    Lines,
+    Float,
+    Checker,
+    /// Produced by running `reference`.
+    Reference,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -431,6 +1409,8 @@ pub enum DeterministicExpectedOutput {
     Pass,
     Exact {
         text: Arc<str>,
+        ignore_trailing_whitespace: bool,
+        blank_lines: BlankLines,
     },
     SplitWhitespace {
         text: Arc<str>,
@@ -446,22 +1426,204 @@ pub enum DeterministicExpectedOutput {
 }
 
 impl DeterministicExpectedOutput {
+    pub(crate) fn trim_cr(&mut self) {
+        match self {
+            Self::Pass => {}
+            Self::Exact { text, .. }
+            | Self::SplitWhitespace { text }
+            | Self::Lines { text }
+            | Self::Float { text, .. } => *text = crlf_to_lf(text),
+        }
+    }
+
+    fn text_and_match(&self) -> (Option<&str>, Option<Match>) {
+        match self {
+            Self::Pass => (None, None),
+            Self::Exact {
+                text,
+                ignore_trailing_whitespace,
+                blank_lines,
+            } => (
+                Some(text),
+                Some(Match::Exact {
+                    ignore_trailing_whitespace: *ignore_trailing_whitespace,
+                    blank_lines: *blank_lines,
+                }),
+            ),
+            Self::SplitWhitespace { text } => (Some(text), Some(Match::SplitWhitespace)),
+            Self::Lines { text } => (Some(text), Some(Match::Lines)),
+            Self::Float {
+                text,
+                relative_error,
+                absolute_error,
+            } => (
+                Some(text),
+                Some(Match::Float {
+                    relative_error: *relative_error,
+                    absolute_error: *absolute_error,
+                }),
+            ),
+        }
+    }
+
     pub(crate) fn accepts(&self, actual: &str) -> bool {
+        self.matcher().judge(actual) == MatchOutcome::Accepted
+    }
+
+    fn matcher(&self) -> Box<dyn OutputMatcher + '_> {
         match self {
-            Self::Pass => true,
-            Self::Exact { text } => &**text == actual,
-            Self::SplitWhitespace { text } => text.split_whitespace().eq(actual.split_whitespace()),
-            Self::Lines { text } => text.lines().eq(actual.lines()),
+            Self::Pass => Box::new(matcher::Pass),
+            Self::Exact {
+                text,
+                ignore_trailing_whitespace,
+                blank_lines,
+            } => Box::new(matcher::Exact {
+                expected: text,
+                ignore_trailing_whitespace: *ignore_trailing_whitespace,
+                blank_lines: *blank_lines,
+            }),
+            Self::SplitWhitespace { text } => Box::new(matcher::SplitWhitespace { expected: text }),
+            Self::Lines { text } => Box::new(matcher::Lines { expected: text }),
             Self::Float {
                 text,
                 relative_error,
                 absolute_error,
-            } => {
-                let (text, actual) = (text.lines(), actual.lines());
-                let relative_error = relative_error.map(PositiveFinite::get).unwrap_or(0.0);
-                let absolute_error = absolute_error.map(PositiveFinite::get).unwrap_or(0.0);
+            } => Box::new(matcher::Float {
+                expected: text,
+                relative_error: relative_error.map(PositiveFinite::get).unwrap_or(0.0),
+                absolute_error: absolute_error.map(PositiveFinite::get).unwrap_or(0.0),
+            }),
+        }
+    }
+
+    pub(crate) fn expected_stdout(&self) -> Option<&str> {
+        match self {
+            Self::Pass => None,
+            Self::Exact { text, .. }
+            | Self::SplitWhitespace { text }
+            | Self::Lines { text }
+            | Self::Float { text, .. } => Some(text),
+        }
+    }
+}
+
+/// Result of [`OutputMatcher::judge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MatchOutcome {
+    Accepted,
+    WrongAnswer,
+}
+
+impl From<bool> for MatchOutcome {
+    fn from(accepted: bool) -> Self {
+        if accepted {
+            Self::Accepted
+        } else {
+            Self::WrongAnswer
+        }
+    }
+}
+
+/// Compares the actual output with the expected one. Each variant of [`Match`] except `Checker`
+/// has an implementation in `matcher`.
+pub(crate) trait OutputMatcher {
+    fn judge(&self, actual: &str) -> MatchOutcome;
+}
+
+mod matcher {
+    use super::{BlankLines, MatchOutcome, OutputMatcher};
+    use itertools::{EitherOrBoth, Itertools as _};
+
+    /// Accepts any output.
+    pub(super) struct Pass;
+
+    impl OutputMatcher for Pass {
+        fn judge(&self, _: &str) -> MatchOutcome {
+            MatchOutcome::Accepted
+        }
+    }
+
+    pub(super) struct Exact<'a> {
+        pub(super) expected: &'a str,
+        pub(super) ignore_trailing_whitespace: bool,
+        pub(super) blank_lines: BlankLines,
+    }
+
+    impl Exact<'_> {
+        fn lines<'b>(&self, text: &'b str) -> Vec<&'b str> {
+            let mut lines = vec![];
+            for line in text.split('\n') {
+                let line = if self.ignore_trailing_whitespace {
+                    line.trim_end()
+                } else {
+                    line
+                };
+                let skip = line.is_empty()
+                    && match self.blank_lines {
+                        BlankLines::Keep => false,
+                        BlankLines::Collapse => lines.last() == Some(&""),
+                        BlankLines::Remove => true,
+                    };
+                if !skip {
+                    lines.push(line);
+                }
+            }
+            lines
+        }
+    }
+
+    impl OutputMatcher for Exact<'_> {
+        fn judge(&self, actual: &str) -> MatchOutcome {
+            if self.ignore_trailing_whitespace || self.blank_lines != BlankLines::Keep {
+                (self.lines(self.expected) == self.lines(actual)).into()
+            } else {
+                (self.expected == actual).into()
+            }
+        }
+    }
+
+    pub(super) struct SplitWhitespace<'a> {
+        pub(super) expected: &'a str,
+    }
+
+    impl OutputMatcher for SplitWhitespace<'_> {
+        fn judge(&self, actual: &str) -> MatchOutcome {
+            self.expected
+                .split_whitespace()
+                .eq(actual.split_whitespace())
+                .into()
+        }
+    }
 
-                text.zip_longest(actual).all(|zip| {
+    pub(super) struct Lines<'a> {
+        pub(super) expected: &'a str,
+    }
+
+    impl OutputMatcher for Lines<'_> {
+        fn judge(&self, actual: &str) -> MatchOutcome {
+            self.expected.lines().eq(actual.lines()).into()
+        }
+    }
+
+    /// Compares the words that can be parsed as `f64` with the errors, and the others exactly.
+    pub(super) struct Float<'a> {
+        pub(super) expected: &'a str,
+        pub(super) relative_error: f64,
+        pub(super) absolute_error: f64,
+    }
+
+    impl OutputMatcher for Float<'_> {
+        fn judge(&self, actual: &str) -> MatchOutcome {
+            let Self {
+                expected,
+                relative_error,
+                absolute_error,
+            } = *self;
+
+            expected
+                .lines()
+                .zip_longest(actual.lines())
+                .all(|zip| {
                     if let EitherOrBoth::Both(line1, line2) = zip {
                         let (words1, words2) = (line1.split_whitespace(), line2.split_whitespace());
                         words1.zip_longest(words2).all(|zip| match zip {
@@ -480,17 +1642,7 @@ impl DeterministicExpectedOutput {
                         false
                     }
                 })
-            }
-        }
-    }
-
-    pub(crate) fn expected_stdout(&self) -> Option<&str> {
-        match self {
-            Self::Pass => None,
-            Self::Exact { text }
-            | Self::SplitWhitespace { text }
-            | Self::Lines { text }
-            | Self::Float { text, .. } => Some(text),
+                .into()
         }
     }
 }
@@ -578,15 +1730,93 @@ mod serde_fn {
     }
 }
 
+/// `humantime_serde` for `Option<Duration>`, except that a bare number is also accepted as seconds.
+mod timelimit {
+    use serde::{de, Deserializer};
+    use std::{fmt, time::Duration};
+
+    pub(super) use humantime_serde::serialize;
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        return deserializer.deserialize_option(OptionVisitor);
+
+        struct OptionVisitor;
+
+        impl<'de> de::Visitor<'de> for OptionVisitor {
+            type Value = Option<Duration>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter
+                    .write_str("a duration (e.g. `2s`, `1500ms`), a number of seconds, or null")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                deserializer.deserialize_any(Visitor).map(Some)
+            }
+        }
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Duration;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a duration (e.g. `2s`, `1500ms`) or a number of seconds")
+            }
+
+            fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Self::Value, E> {
+                Ok(Duration::from_secs(secs))
+            }
+
+            fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Self::Value, E> {
+                if secs < 0 {
+                    return Err(E::invalid_value(de::Unexpected::Signed(secs), &self));
+                }
+                Ok(Duration::from_secs(secs as _))
+            }
+
+            fn visit_f64<E: de::Error>(self, secs: f64) -> Result<Self::Value, E> {
+                if !(secs.is_finite() && secs >= 0.0) {
+                    return Err(E::invalid_value(de::Unexpected::Float(secs), &self));
+                }
+                Ok(Duration::from_secs_f64(secs))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                humantime::parse_duration(s).map_err(E::custom)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::testsuite::scale_timelimit;
     use crate::testsuite::{
-        Additional, BatchTestSuite, DeterministicExpectedOutput, Match, PartialBatchTestCase,
-        PositiveFinite, TestSuite,
+        matcher, Additional, BatchTestCase, BatchTestSuite, BlankLines, CheckerShell,
+        DeterministicExpectedOutput, ExpectedOutput, ExpectedOutputKind, Interaction,
+        InteractiveTestSuite, LineEnding, Match, MatchOutcome, OutputMatcher, PartialBatchTestCase,
+        PositiveFinite, TestCaseMeta, TestCases, TestSuite, Tester, TesterArgs,
     };
     use difference::assert_diff;
+    use indexmap::indexmap;
+    use maplit::hashset;
     use pretty_assertions::assert_eq;
-    use std::time::Duration;
+    use std::{collections::HashSet, fs, path::Path, time::Duration};
 
     #[test]
     fn atcoder_abc162_a() {
@@ -616,31 +1846,47 @@ cases:
 extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
+                description: None,
+                editorial: None,
                 timelimit: Some(Duration::from_secs(2)),
                 r#match: Match::Lines,
+                reference: None,
+                generator: None,
+                input_filter: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
+                        note: None,
                         r#in: "117\n".into(),
                         out: Some("Yes\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                     PartialBatchTestCase {
                         name: Some("Sample 2".to_owned()),
+                        note: None,
                         r#in: "123\n".into(),
                         out: Some("No\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                     PartialBatchTestCase {
                         name: Some("Sample 3".to_owned()),
+                        note: None,
                         r#in: "777\n".into(),
                         out: Some("Yes\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                 ],
+                cases_from: None,
+                exclude: vec![],
                 extend: vec![],
             }),
         );
@@ -660,9 +1906,16 @@ extend:
     out: /out/*.txt
 "#,
             &TestSuite::Batch(BatchTestSuite {
+                description: None,
+                editorial: None,
                 timelimit: Some(Duration::from_secs(2)),
                 r#match: Match::Lines,
+                reference: None,
+                generator: None,
+                input_filter: None,
                 cases: vec![],
+                cases_from: None,
+                exclude: vec![],
                 extend: vec![Additional::Text {
                     path: "./a".into(),
                     r#in: "/in/*.txt".into(),
@@ -700,27 +1953,40 @@ cases:
 extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
+                description: None,
+                editorial: None,
                 timelimit: Some(Duration::from_secs(2)),
                 r#match: Match::Float {
                     relative_error: Some(PositiveFinite(0.01)),
                     absolute_error: Some(PositiveFinite(0.01)),
                 },
+                reference: None,
+                generator: None,
+                input_filter: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
+                        note: None,
                         r#in: "1\n".into(),
                         out: Some("6.28318530717958623200\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                     PartialBatchTestCase {
                         name: Some("Sample 2".to_owned()),
+                        note: None,
                         r#in: "73\n".into(),
                         out: Some("458.67252742410977361942\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                 ],
+                cases_from: None,
+                exclude: vec![],
                 extend: vec![],
             }),
         );
@@ -754,24 +2020,37 @@ cases:
 extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
+                description: None,
+                editorial: None,
                 timelimit: Some(Duration::from_secs(2)),
                 r#match: Match::Lines,
+                reference: None,
+                generator: None,
+                input_filter: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
+                        note: None,
                         r#in: "3\ncbaa\ndaacc\nacacac\n".into(),
                         out: Some("aac\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                     PartialBatchTestCase {
                         name: Some("Sample 2".to_owned()),
+                        note: None,
                         r#in: "3\na\naa\nb\n".into(),
                         out: Some("\n".into()),
                         timelimit: None,
                         r#match: None,
+                        stderr: None,
+                        stderr_match: None,
                     },
                 ],
+                cases_from: None,
+                exclude: vec![],
                 extend: vec![],
             }),
         );
@@ -783,24 +2062,593 @@ extend: []
         assert_diff!(yaml, &actual.to_yaml_pretty(), "\n", 0);
     }
 
+    /// Asserts that `test_suite` is reloaded equal from both `to_yaml_pretty` and `serde_yaml`.
+    fn assert_roundtrips(test_suite: &TestSuite) {
+        let pretty = test_suite.to_yaml_pretty();
+        assert_eq!(
+            *test_suite,
+            serde_yaml::from_str::<TestSuite>(&pretty).unwrap(),
+            "{}",
+            pretty,
+        );
+
+        let plain = serde_yaml::to_string(test_suite).unwrap();
+        assert_eq!(
+            *test_suite,
+            serde_yaml::from_str::<TestSuite>(&plain).unwrap(),
+            "{}",
+            plain,
+        );
+    }
+
     #[test]
-    fn expected_output_accepts() {
-        assert!(DeterministicExpectedOutput::Pass.accepts("ミ゙"));
+    fn roundtrip() {
+        let batch = |cases: &[(&str, Option<&str>)]| {
+            TestSuite::Batch(BatchTestSuite {
+                description: None,
+                editorial: None,
+                timelimit: None,
+                r#match: Match::Exact {
+                    ignore_trailing_whitespace: true,
+                    blank_lines: BlankLines::Keep,
+                },
+                reference: None,
+                generator: None,
+                input_filter: None,
+                cases: cases
+                    .iter()
+                    .map(|&(r#in, out)| PartialBatchTestCase {
+                        name: None,
+                        note: None,
+                        r#in: r#in.into(),
+                        out: out.map(Into::into),
+                        timelimit: Some(Duration::from_millis(1500)),
+                        r#match: None,
+                        stderr: None,
+                        stderr_match: None,
+                    })
+                    .collect(),
+                cases_from: None,
+                exclude: vec![],
+                extend: vec![],
+            })
+        };
+
+        assert_roundtrips(&batch(&[]));
+        assert_roundtrips(&batch(&[
+            ("", Some("")),
+            ("\n", Some("\n\n")),
+            ("1 2", Some("3")),
+            ("  indented\n", None),
+            ("a\n\nb\n", Some("trailing \n")),
+            ("tab\there\n", Some("crlf\r\n")),
+            ("---\n", Some("# not a comment\n")),
+            ("ミ゙\n", Some("~\n")),
+        ]));
+
+        assert_roundtrips(&TestSuite::Interactive(InteractiveTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            tester: None,
+            each_args: vec![],
+            example: vec![],
+        }));
+        assert_roundtrips(&TestSuite::Interactive(InteractiveTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: Some(Duration::from_secs(2)),
+            tester: Some(Tester {
+                cmd: "python3 ./tester.py \"$@\"".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            each_args: vec![
+                TesterArgs::Positional(vec!["".to_owned(), "2".to_owned()]),
+                TesterArgs::Named(indexmap!("n".to_owned() => "1\n2".to_owned())),
+            ],
+            example: vec![],
+        }));
+
+        assert_roundtrips(&TestSuite::Unsubmittable);
+    }
 
-        assert!(DeterministicExpectedOutput::Exact {
-            text: "1 2\n".into()
+    #[test]
+    fn to_yaml_pretty_with_line_ending() {
+        let test_suite = TestSuite::Batch(BatchTestSuite {
+            description: Some("multi\nline\n".to_owned()),
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: Some("Sample 1".to_owned()),
+                note: None,
+                r#in: "1 2\n3\n".into(),
+                out: Some("6\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        });
+
+        let native = if cfg!(windows) { "\r\n" } else { "\n" };
+
+        for (line_ending, eol) in &[
+            (LineEnding::Lf, "\n"),
+            (LineEnding::Crlf, "\r\n"),
+            (LineEnding::Native, native),
+        ] {
+            let yaml = test_suite.to_yaml_pretty_with(*line_ending);
+            assert!(yaml.ends_with(&format!("[]{}", eol)), "{:?}", yaml);
+            assert_eq!(yaml.matches('\n').count(), yaml.matches(eol).count());
+            assert_eq!(test_suite, serde_yaml::from_str(&yaml).unwrap());
         }
-        .accepts("1 2\n"));
 
-        assert!(!DeterministicExpectedOutput::Exact {
-            text: "1  2\n".into()
+        for test_suite in &[TestSuite::Unsubmittable, test_suite] {
+            let yaml = test_suite.to_yaml_pretty();
+            assert!(
+                yaml.ends_with('\n') && !yaml.ends_with("\n\n"),
+                "{:?}",
+                yaml
+            );
+            assert!(!yaml.contains('\r'));
         }
-        .accepts("1 2\n"));
+    }
 
-        assert!(!DeterministicExpectedOutput::Exact {
-            text: "1 2\n".into()
+    #[test]
+    fn to_yaml_compact_with() {
+        let case = |name: Option<&str>, r#in: &str, out: &str| PartialBatchTestCase {
+            name: name.map(ToOwned::to_owned),
+            note: None,
+            r#in: r#in.into(),
+            out: Some(out.into()),
+            timelimit: None,
+            r#match: None,
+            stderr: None,
+            stderr_match: None,
+        };
+
+        let test_suite = TestSuite::Batch(BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![
+                case(Some("Sample 1"), "5\n", "25\n"),
+                case(None, "\"\n", "Yes\n"),
+                case(Some("Sample 2"), "2\n1 2\n", "3\n"),
+                case(None, "12345678901234567890\n", "1\n"),
+            ],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        });
+
+        let yaml = test_suite.to_yaml_compact_with(LineEnding::Lf);
+
+        assert_diff!(
+            r#"---
+type: Batch
+timelimit: ~
+match: Lines
+
+cases:
+  - {name: "Sample 1", in: "5\n", out: "25\n"}
+  - {in: "\"\n", out: "Yes\n"}
+  - name: Sample 2
+    in: |
+      2
+      1 2
+    out: |
+      3
+  - in: |
+      12345678901234567890
+    out: |
+      1
+
+extend: []
+"#,
+            &yaml,
+            "\n",
+            0
+        );
+        assert_eq!(test_suite, serde_yaml::from_str(&yaml).unwrap());
+    }
+
+    #[test]
+    fn test_cases_iter() {
+        let test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: Some(Duration::from_secs(2)),
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![
+                PartialBatchTestCase {
+                    name: Some("Sample 1".to_owned()),
+                    note: None,
+                    r#in: "1 2\n".into(),
+                    out: Some("3\n".into()),
+                    timelimit: None,
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                },
+                PartialBatchTestCase {
+                    name: Some("Sample 2".to_owned()),
+                    note: None,
+                    r#in: "3 4\n".into(),
+                    out: None,
+                    timelimit: Some(Duration::from_secs(1)),
+                    r#match: None,
+                    stderr: None,
+                    stderr_match: None,
+                },
+            ],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let test_cases = TestCases::Batch(
+            test_suite
+                .load_test_cases(".".as_ref(), None::<HashSet<String>>, |_| unreachable!())
+                .unwrap(),
+        );
+
+        assert_eq!(2, test_cases.len());
+        assert!(!test_cases.is_empty());
+        assert!(TestCases::Interactive(vec![]).is_empty());
+        assert_eq!(
+            vec![
+                TestCaseMeta {
+                    name: Some("Sample 1"),
+                    timelimit: Some(Duration::from_secs(2)),
+                    expected: Some(ExpectedOutputKind::Lines),
+                },
+                TestCaseMeta {
+                    name: Some("Sample 2"),
+                    timelimit: Some(Duration::from_secs(1)),
+                    expected: Some(ExpectedOutputKind::Pass),
+                },
+            ],
+            test_cases.iter().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn line_ending_normalize() {
+        assert_eq!("a\nb\n", LineEnding::Lf.normalize("a\r\nb\n\n\r\n"));
+        assert_eq!("a\r\nb\r\n", LineEnding::Crlf.normalize("a\nb"));
+        assert_eq!("\r\n", LineEnding::Crlf.normalize(""));
+    }
+
+    #[test]
+    fn to_interactive_and_to_batch() {
+        let batch = serde_yaml::from_str::<BatchTestSuite>(
+            r#"timelimit: 2s
+match: Lines
+cases:
+  - in: "1 2\n"
+    out: "3\n"
+  - in: "4 5\n"
+    out: "9\n"
+"#,
+        )
+        .unwrap();
+
+        let (interactive, dropped) = batch.to_interactive();
+        assert_eq!(Some(Duration::from_secs(2)), interactive.timelimit);
+        assert_eq!(None, interactive.tester);
+        assert_eq!(
+            vec![
+                Interaction::In("1 2".to_owned()),
+                Interaction::Out("3".to_owned()),
+                Interaction::In("4 5".to_owned()),
+                Interaction::Out("9".to_owned()),
+            ],
+            interactive.example,
+        );
+        assert!(dropped.is_empty());
+
+        let (batch, dropped) = interactive.to_batch();
+        assert_eq!(Some(Duration::from_secs(2)), batch.timelimit);
+        assert_eq!(1, batch.cases.len());
+        assert_eq!("1 2\n4 5\n", &*batch.cases[0].r#in);
+        assert_eq!(Some("3\n9\n"), batch.cases[0].out.as_deref());
+        assert!(dropped.is_empty());
+
+        let batch = serde_yaml::from_str::<BatchTestSuite>(
+            r#"match: Exact
+generator:
+  cmd: echo 1
+  shell: Bash
+cases:
+  - name: sample1
+    in: "1 2\n"
+    out: "3\n"
+extend:
+  - type: Text
+    path: ./a
+    in: /in/*.txt
+    out: /out/*.txt
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["match", "generator", "extend", "cases[].name"],
+            batch.to_interactive().1,
+        );
+    }
+
+    #[test]
+    fn content_hash() {
+        let mut test_suite = serde_yaml::from_str::<BatchTestSuite>(
+            r#"match: Lines
+cases:
+  - name: sample1
+    in: "1 2\n"
+    out: "3\n"
+  - name: sample2
+    in: "1 2\n"
+    out: "4\n"
+  - name: sample3
+    in: "1 2\n"
+  - name: sample4
+    in: "1 2\n"
+    out: "3\n"
+    match: SplitWhitespace
+  - name: sample5
+    in: "1 2\n"
+    out: "3\n"
+    stderr: "debug\n"
+"#,
+        )
+        .unwrap();
+
+        let hashes = |test_suite: &BatchTestSuite| -> Vec<_> {
+            test_suite
+                .cases
+                .iter()
+                .map(|case| case.content_hash(test_suite))
+                .collect()
+        };
+
+        let original = hashes(&test_suite);
+        assert_eq!(5, original.iter().collect::<HashSet<_>>().len());
+
+        let loaded = test_suite
+            .load_test_cases(Path::new(""), None::<HashSet<String>>, |_| unreachable!())
+            .unwrap()
+            .iter()
+            .map(BatchTestCase::content_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(original, loaded);
+
+        test_suite.cases.reverse();
+        for case in &mut test_suite.cases {
+            case.name = None;
         }
-        .accepts("1\n2\n"));
+        let mut reordered = hashes(&test_suite);
+        reordered.reverse();
+        assert_eq!(original, reordered);
+    }
+
+    #[test]
+    fn deserialize_timelimit() {
+        let timelimit = |yaml: &str| -> _ {
+            serde_yaml::from_str::<InteractiveTestSuite>(yaml).map(|suite| suite.timelimit)
+        };
+
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            timelimit("timelimit: 2s").unwrap()
+        );
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            timelimit("timelimit: 1500ms").unwrap(),
+        );
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            timelimit("timelimit: 2").unwrap()
+        );
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            timelimit("timelimit: 1.5").unwrap(),
+        );
+        assert_eq!(None, timelimit("timelimit: ~").unwrap());
+        assert_eq!(None, timelimit("{}").unwrap());
+        assert!(timelimit("timelimit: -1").is_err());
+        assert!(timelimit("timelimit: 2 apples").is_err());
+
+        assert_eq!(
+            "---\ntimelimit: 2s\n",
+            serde_yaml::to_string(&InteractiveTestSuite {
+                description: None,
+                editorial: None,
+                timelimit: serde_yaml::from_str::<InteractiveTestSuite>("timelimit: 2")
+                    .unwrap()
+                    .timelimit,
+                tester: None,
+                each_args: vec![],
+                example: vec![],
+            })
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn serialize_deserialize_exact() {
+        let exact = |ignore_trailing_whitespace| Match::Exact {
+            ignore_trailing_whitespace,
+            blank_lines: BlankLines::Keep,
+        };
+
+        assert_eq!(exact(false), serde_yaml::from_str("Exact").unwrap());
+        assert_eq!(exact(false), serde_yaml::from_str("Exact: {}").unwrap());
+        assert_eq!(
+            exact(true),
+            serde_yaml::from_str("Exact:\n  ignore_trailing_whitespace: true").unwrap(),
+        );
+
+        assert_eq!(
+            "---\nExact\n",
+            serde_yaml::to_string(&exact(false)).unwrap()
+        );
+        assert_eq!(
+            "---\nExact:\n  ignore_trailing_whitespace: true\n",
+            serde_yaml::to_string(&exact(true)).unwrap(),
+        );
+        assert_eq!(
+            Match::Exact {
+                ignore_trailing_whitespace: false,
+                blank_lines: BlankLines::Collapse,
+            },
+            serde_yaml::from_str("Exact:\n  blank_lines: Collapse").unwrap(),
+        );
+        assert_eq!(
+            Match::Lines,
+            serde_yaml::from_str(&serde_yaml::to_string(&Match::Lines).unwrap()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn pass_matcher() {
+        assert_eq!(MatchOutcome::Accepted, matcher::Pass.judge(""));
+        assert_eq!(MatchOutcome::Accepted, matcher::Pass.judge("ミ゙"));
+    }
+
+    #[test]
+    fn exact_matcher() {
+        let judge = |expected, ignore_trailing_whitespace, actual| {
+            matcher::Exact {
+                expected,
+                ignore_trailing_whitespace,
+                blank_lines: BlankLines::Keep,
+            }
+            .judge(actual)
+        };
+
+        assert_eq!(MatchOutcome::Accepted, judge("1 2\n", false, "1 2\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", false, "1 2"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", false, "1 2 \n"));
+        assert_eq!(MatchOutcome::Accepted, judge("1 2\n", true, "1 2 \n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", true, "1 2\n\n"));
+    }
+
+    #[test]
+    fn exact_matcher_blank_lines() {
+        let judge = |blank_lines, actual| {
+            matcher::Exact {
+                expected: "1\n\n2\n",
+                ignore_trailing_whitespace: true,
+                blank_lines,
+            }
+            .judge(actual)
+        };
+
+        assert_eq!(
+            MatchOutcome::WrongAnswer,
+            judge(BlankLines::Keep, "1\n\n\n2\n\n")
+        );
+        assert_eq!(
+            MatchOutcome::Accepted,
+            judge(BlankLines::Collapse, "1\n\n\n2\n\n")
+        );
+        assert_eq!(
+            MatchOutcome::Accepted,
+            judge(BlankLines::Collapse, "1\n \n\t\n2\n")
+        );
+        assert_eq!(
+            MatchOutcome::WrongAnswer,
+            judge(BlankLines::Collapse, "1\n2\n")
+        );
+        assert_eq!(
+            MatchOutcome::Accepted,
+            judge(BlankLines::Remove, "\n1\n2\n\n\n")
+        );
+        assert_eq!(
+            MatchOutcome::WrongAnswer,
+            judge(BlankLines::Remove, "1 2\n")
+        );
+    }
+
+    #[test]
+    fn split_whitespace_matcher() {
+        let judge = |expected, actual| matcher::SplitWhitespace { expected }.judge(actual);
+
+        assert_eq!(MatchOutcome::Accepted, judge("1 2\n", " 1\n\n2"));
+        assert_eq!(MatchOutcome::Accepted, judge("", "\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", "12\n"));
+    }
+
+    #[test]
+    fn lines_matcher() {
+        let judge = |expected, actual| matcher::Lines { expected }.judge(actual);
+
+        assert_eq!(MatchOutcome::Accepted, judge("1 2\n", "1 2"));
+        assert_eq!(MatchOutcome::Accepted, judge("1\n2\n", "1\r\n2\r\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", "1  2\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge("1 2\n", "1\n2\n"));
+    }
+
+    #[test]
+    fn float_matcher() {
+        let judge = |relative_error, absolute_error, actual| {
+            matcher::Float {
+                expected: "1 10000.0\n",
+                relative_error,
+                absolute_error,
+            }
+            .judge(actual)
+        };
+
+        assert_eq!(MatchOutcome::Accepted, judge(0.0, 0.0, "1 10000\n"));
+        assert_eq!(MatchOutcome::Accepted, judge(0.01, 0.0, "1 10099.0\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge(0.01, 0.0, "1 10200.0\n"));
+        assert_eq!(MatchOutcome::Accepted, judge(0.0, 1.0, "1 9999.0\n"));
+        assert_eq!(MatchOutcome::WrongAnswer, judge(0.0, 1.0, "1 9998.0\n"));
+        assert_eq!(
+            MatchOutcome::WrongAnswer,
+            judge(0.01, 0.01, "1 10000.0 1\n")
+        );
+        assert_eq!(MatchOutcome::WrongAnswer, judge(0.01, 0.01, "x 10000.0\n"));
+    }
+
+    #[test]
+    fn expected_output_accepts() {
+        assert!(DeterministicExpectedOutput::Pass.accepts("ミ゙"));
+
+        let exact = |text: &str, ignore_trailing_whitespace| DeterministicExpectedOutput::Exact {
+            text: text.into(),
+            ignore_trailing_whitespace,
+            blank_lines: BlankLines::Keep,
+        };
+
+        assert!(exact("1 2\n", false).accepts("1 2\n"));
+
+        assert!(!exact("1  2\n", false).accepts("1 2\n"));
+
+        assert!(!exact("1 2\n", false).accepts("1\n2\n"));
+
+        assert!(!exact("1 2\n3\n", false).accepts("1 2 \n3\t\n"));
+
+        assert!(exact("1 2\n3\n", true).accepts("1 2 \n3\t\n"));
+
+        assert!(!exact("1  2\n", true).accepts("1 2\n"));
+
+        assert!(!exact("1\n", true).accepts("1\n\n"));
 
         assert!(DeterministicExpectedOutput::SplitWhitespace { text: "".into() }.accepts(""));
 
@@ -851,4 +2699,498 @@ extend: []
         }
         .accepts("0\n"));
     }
+
+    #[test]
+    fn expected_output_with_match() {
+        let lines = ExpectedOutput::new(Some("1  2\n".into()), Match::Lines);
+
+        assert_eq!(
+            ExpectedOutput::Deterministic(DeterministicExpectedOutput::SplitWhitespace {
+                text: "1  2\n".into(),
+            }),
+            lines.clone().with_match(Some(Match::SplitWhitespace)),
+        );
+
+        assert_eq!(
+            ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+            lines.with_match(None),
+        );
+    }
+
+    #[test]
+    fn interactive_without_tester() {
+        let test_suite = serde_yaml::from_str::<TestSuite>(
+            r#"---
+type: Interactive
+timelimit: 2s
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            TestSuite::Interactive(InteractiveTestSuite {
+                description: None,
+                editorial: None,
+                timelimit: Some(Duration::from_secs(2)),
+                tester: None,
+                each_args: vec![],
+                example: vec![],
+            }),
+            test_suite,
+        );
+
+        if let TestSuite::Interactive(test_suite) = test_suite {
+            let err = test_suite
+                .load_test_cases(None, None::<HashSet<String>>)
+                .unwrap_err();
+            assert!(err.to_string().starts_with("`tester` is not set."));
+
+            let default_tester = Tester {
+                cmd: "./tester".to_owned(),
+                shell: CheckerShell::Bash,
+            };
+            let test_cases = test_suite
+                .load_test_cases(Some(&default_tester), None::<HashSet<String>>)
+                .unwrap();
+            assert_eq!(1, test_cases.len());
+            assert_eq!(default_tester, test_cases[0].tester);
+        }
+    }
+
+    #[test]
+    fn interactive_with_example() {
+        let yaml = r#"---
+type: Interactive
+timelimit: 2s
+example:
+  - in: "3"
+  - out: "? 1 2"
+  - in: "<"
+  - out: "! 2 1 3"
+"#;
+
+        let test_suite = serde_yaml::from_str::<TestSuite>(yaml).unwrap();
+        assert_roundtrips(&test_suite);
+
+        if let TestSuite::Interactive(InteractiveTestSuite { example, .. }) = test_suite {
+            assert_eq!(
+                ["< 3", "> ? 1 2", "< <", "> ! 2 1 3"],
+                *example.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            );
+        } else {
+            unreachable!();
+        }
+    }
+
+    #[test]
+    fn interactive_with_tester() {
+        let test_suite = InteractiveTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: Some(Duration::from_secs(2)),
+            tester: Some(Tester {
+                cmd: "python3 ./a/tester.py \"$@\"".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            each_args: vec![
+                TesterArgs::Positional(vec!["1".to_owned()]),
+                TesterArgs::Positional(vec!["2".to_owned()]),
+            ],
+            example: vec![],
+        };
+
+        let default_tester = Tester {
+            cmd: "./default-tester".to_owned(),
+            shell: CheckerShell::Bash,
+        };
+
+        let test_cases = test_suite
+            .load_test_cases(Some(&default_tester), Some(hashset!("2")))
+            .unwrap();
+
+        assert_eq!(1, test_cases.len());
+        assert_eq!(Some("2"), test_cases[0].name.as_deref());
+        assert_eq!(["2"], *test_cases[0].args);
+        assert_eq!("python3 ./a/tester.py \"$@\"", test_cases[0].tester.cmd);
+
+        assert!(test_suite
+            .load_test_cases(None, Some(hashset!("3")))
+            .is_err());
+    }
+
+    #[test]
+    fn expand_tester_args() {
+        let args = TesterArgs::Positional((1..=10).map(|i| i.to_string()).collect());
+
+        assert_eq!(
+            "./tester '1' '2' '3' '4' '5' '6' '7' '8' '9' '10'",
+            args.expand("./tester ${args}").unwrap(),
+        );
+        assert_eq!(
+            "./tester '10' '1' $1 ${HOME}",
+            args.expand("./tester ${arg[10]} ${arg[1]} $1 ${HOME}")
+                .unwrap(),
+        );
+        assert_eq!(
+            "`${arg[11]}` is out of range (10 argument(s) provided)",
+            args.expand("./tester ${arg[11]}").unwrap_err().to_string(),
+        );
+        assert!(args.expand("./tester ${arg[0]}").is_err());
+
+        let args = serde_yaml::from_str::<TesterArgs>("{ n: '100', s: \"it's\" }").unwrap();
+
+        assert_eq!(
+            TesterArgs::Named(
+                indexmap!("n".to_owned() => "100".to_owned(), "s".to_owned() => "it's".to_owned())
+            ),
+            args,
+        );
+        assert_eq!(
+            r#"./tester --n '100' '100' 'it'\''s' ${m}"#,
+            args.expand("./tester --n ${n} ${arg[1]} ${s} ${m}")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn load_text_test_cases_in_one_walk() {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-test-")
+            .tempdir()
+            .unwrap();
+
+        let write = |path: &str, content: &[u8]| {
+            let path = tempdir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+
+        write("in/1.txt", b"1\n");
+        write("out/1.txt", b"2\n");
+        write("in/nested/2.txt", b"2\n");
+        write("README.md", b"\xff\xfe not UTF-8");
+
+        let cases = Additional::Text {
+            path: ".".into(),
+            r#in: "/in/**/*.txt".to_owned(),
+            out: "/out/**/*.txt".to_owned(),
+            timelimit: None,
+            r#match: None,
+        }
+        .load_test_cases(tempdir.path(), |_| unreachable!())
+        .unwrap();
+
+        assert_eq!(
+            [(Some("1"), "1\n", Some("2\n")), (Some("2"), "2\n", None)],
+            *cases
+                .iter()
+                .map(|case| (case.name.as_deref(), &*case.r#in, case.out.as_deref()))
+                .collect::<Vec<_>>(),
+        );
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn load_cases_from() {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-test-")
+            .tempdir()
+            .unwrap();
+
+        fs::write(
+            tempdir.path().join("a-cases.yml"),
+            "- name: \"1\"\n  in: \"1\\n\"\n  out: \"2\\n\"\n- in: \"2\\n\"\n",
+        )
+        .unwrap();
+
+        let test_suite = serde_yaml::from_str::<TestSuite>(
+            "type: Batch\ntimelimit: 2s\nmatch: Lines\ncases_from: ./a-cases.yml\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            test_suite,
+            serde_yaml::from_str(&test_suite.to_yaml_pretty()).unwrap(),
+        );
+
+        let mut test_suite = match test_suite {
+            TestSuite::Batch(test_suite) => test_suite,
+            _ => unreachable!(),
+        };
+
+        let cases = test_suite
+            .load_test_cases(tempdir.path(), None::<HashSet<String>>, |_| unreachable!())
+            .unwrap();
+
+        assert_eq!(
+            [(Some("1"), "1\n", Some("2\n")), (None, "2\n", None)],
+            *cases
+                .iter()
+                .map(|case| (
+                    case.name.as_deref(),
+                    &*case.input,
+                    case.output.expected_stdout(),
+                ))
+                .collect::<Vec<_>>(),
+        );
+
+        test_suite.cases.push(PartialBatchTestCase {
+            name: None,
+            note: None,
+            r#in: "3\n".into(),
+            out: None,
+            timelimit: None,
+            r#match: None,
+            stderr: None,
+            stderr_match: None,
+        });
+        test_suite
+            .load_test_cases(tempdir.path(), None::<HashSet<String>>, |_| unreachable!())
+            .unwrap_err();
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn exclude() {
+        let test_suite = serde_yaml::from_str::<TestSuite>(
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+exclude: ["*large*"]
+cases:
+  - name: small_1
+    in: "1\n"
+    out: "1\n"
+  - name: large_1
+    in: "100000\n"
+    out: "100000\n"
+  - name: small_2
+    in: "2\n"
+    out: "2\n"
+  - name: random_large_2
+    in: "99999\n"
+    out: "99999\n"
+  - in: "3\n"
+    out: "3\n"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            test_suite,
+            serde_yaml::from_str(&test_suite.to_yaml_pretty()).unwrap(),
+        );
+
+        let test_suite = match test_suite {
+            TestSuite::Batch(test_suite) => test_suite,
+            _ => unreachable!(),
+        };
+
+        let names = |cases: &[BatchTestCase]| {
+            cases
+                .iter()
+                .map(|case| case.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let (cases, num_excluded) = test_suite
+            .load_test_cases_with_num_excluded(
+                Path::new(""),
+                None::<HashSet<String>>,
+                |_| unreachable!(),
+            )
+            .unwrap();
+        assert_eq!(
+            [Some("small_1".to_owned()), Some("small_2".to_owned()), None],
+            *names(&cases),
+        );
+        assert_eq!(2, num_excluded);
+
+        let (cases, num_excluded) = test_suite
+            .load_test_cases_with_num_excluded(
+                Path::new(""),
+                Some(hashset!("large_1")),
+                |_| unreachable!(),
+            )
+            .unwrap();
+        assert_eq!([Some("large_1".to_owned()), None], *names(&cases));
+        assert_eq!(0, num_excluded);
+    }
+
+    #[test]
+    fn append_text_files() {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-test-")
+            .tempdir()
+            .unwrap();
+
+        for (name, r#in, out) in &[
+            ("1", "1\n", "2\n"),
+            ("2", "2\n", "4\n"),
+            ("3", "3\n", "6\n"),
+        ] {
+            fs::write(tempdir.path().join(name).with_extension("in"), r#in).unwrap();
+            fs::write(tempdir.path().join(name).with_extension("out"), out).unwrap();
+        }
+
+        let mut test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: Some("Sample 1".to_owned()),
+                note: None,
+                r#in: "0\n".into(),
+                out: Some("0\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let num_cases = test_suite
+            .append_text_files(tempdir.path(), "*.in", "*.out")
+            .unwrap();
+
+        assert_eq!(3, num_cases);
+        assert_eq!(
+            [
+                (Some("Sample 1"), "0\n", Some("0\n")),
+                (Some("1"), "1\n", Some("2\n")),
+                (Some("2"), "2\n", Some("4\n")),
+                (Some("3"), "3\n", Some("6\n")),
+            ],
+            *test_suite
+                .cases
+                .iter()
+                .map(|case| (case.name.as_deref(), &*case.r#in, case.out.as_deref()))
+                .collect::<Vec<_>>(),
+        );
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn append_text_files_with_cases_from() {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-test-")
+            .tempdir()
+            .unwrap();
+
+        fs::write(tempdir.path().join("1.in"), "1\n").unwrap();
+        fs::write(tempdir.path().join("1.out"), "2\n").unwrap();
+
+        let mut test_suite = BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![],
+            cases_from: Some("./a-cases.yml".into()),
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        let err = test_suite
+            .append_text_files(tempdir.path(), "*.in", "*.out")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("`./a-cases.yml`"), "{}", err);
+        assert!(test_suite.cases.is_empty());
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn description_survives_append_text_files() {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-test-")
+            .tempdir()
+            .unwrap();
+
+        fs::write(tempdir.path().join("1.in"), "1\n").unwrap();
+        fs::write(tempdir.path().join("1.out"), "2\n").unwrap();
+
+        let mut test_suite = BatchTestSuite {
+            description: Some("Sample 1 is the tricky one.\n".to_owned()),
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![PartialBatchTestCase {
+                name: Some("Sample 1".to_owned()),
+                note: Some("N = 0".to_owned()),
+                r#in: "0\n".into(),
+                out: Some("0\n".into()),
+                timelimit: None,
+                r#match: None,
+                stderr: None,
+                stderr_match: None,
+            }],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        };
+
+        test_suite
+            .append_text_files(tempdir.path(), "*.in", "*.out")
+            .unwrap();
+
+        let yaml = TestSuite::Batch(test_suite).to_yaml_pretty();
+
+        assert!(
+            yaml.contains("description: |\n  Sample 1 is the tricky one.\n"),
+            "{}",
+            yaml,
+        );
+
+        let test_suite = match serde_yaml::from_str(&yaml).unwrap() {
+            TestSuite::Batch(test_suite) => test_suite,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            Some("Sample 1 is the tricky one.\n"),
+            test_suite.description.as_deref(),
+        );
+        assert_eq!(
+            [Some("N = 0"), None],
+            *test_suite
+                .cases
+                .iter()
+                .map(|case| case.note.as_deref())
+                .collect::<Vec<_>>(),
+        );
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn scale_timelimit_overflow() {
+        let timelimit = Some(Duration::from_secs(2));
+
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            scale_timelimit(timelimit, 1.5)
+        );
+        assert_eq!(None, scale_timelimit(timelimit, 0.0));
+        assert_eq!(None, scale_timelimit(timelimit, 1e30));
+        assert_eq!(None, scale_timelimit(timelimit, f64::INFINITY));
+        assert_eq!(None, scale_timelimit(None, 2.0));
+    }
 }
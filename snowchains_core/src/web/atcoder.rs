@@ -4,13 +4,14 @@ use crate::{
         TestSuite,
     },
     web::{
-        AnsiColored, CaseConverted, CookieStorage, Exec, Login, LoginOutcome, LowerCase,
-        Participate, ParticipateOutcome, Platform, ProblemInContest, ProblemsInContest,
-        ResponseExt as _, RetrieveFullTestCases, RetrieveLanguages, RetrieveLanguagesOutcome,
+        AnsiColored, CaseConverted, ContestNotBegun, CookieStorage, ElementRefExt as _, Exec,
+        HtmlExt as _, Login, LoginOutcome, LowerCase, Participate, ParticipateOutcome, Platform,
+        ProblemInContest, ProblemsInContest, ResponseExt as _, RetrieveFullTestCases,
+        RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveProblemIndexes,
         RetrieveSubmissionSummaries, RetrieveTestCases, RetrieveTestCasesOutcome,
         RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest,
-        RetrieveTestCasesOutcomeProblemTextFiles, Session, SessionMut, Shell, Submit,
-        SubmitOutcome, WatchSubmissions,
+        RetrieveTestCasesOutcomeProblemTextFiles, ScrapeError, ServiceError, Session, SessionMut,
+        Shell, StatusCodeRange, Submit, SubmitOutcome, WatchSubmissions,
     },
 };
 use anyhow::{anyhow, bail, Context as _};
@@ -38,8 +39,9 @@ use std::{
     ops::Deref,
     path::Path,
     str::FromStr,
-    time::Duration,
+    time::{Duration, Instant},
 };
+use strum::{EnumString, EnumVariantNames};
 use termcolor::{Color, ColorSpec, WriteColor};
 use tokio::runtime::Runtime;
 use unicode_width::UnicodeWidthStr as _;
@@ -58,6 +60,16 @@ pub fn contest_id_from_url(url: &Url) -> anyhow::Result<String> {
         .with_context(|| "Could not extract contest ID of the problem")
 }
 
+/// Whether the task of `index` and `url` is `problem`, which is either the index (`A`) or the
+/// screen name (`abc300_a`).
+fn is_task(index: &str, url: &Url, problem: &str) -> bool {
+    index.eq_ignore_ascii_case(problem)
+        || url
+            .path_segments()
+            .and_then(Iterator::last)
+            .is_some_and(|screen_name| screen_name.eq_ignore_ascii_case(problem))
+}
+
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Atcoder<'closures> {
     Infallible(Infallible, PhantomData<fn() -> &'closures ()>),
@@ -102,10 +114,12 @@ impl<S: Shell> Exec<Login<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         if check_logged_in(&mut sess)? {
             Ok(LoginOutcome::AlreadyLoggedIn)
@@ -128,11 +142,13 @@ impl<S: Shell> Exec<Participate<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
         let contest = CaseConverted::new(contest);
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
         participate(sess, username_and_password, &contest, true)
     }
 }
@@ -149,6 +165,8 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
@@ -161,17 +179,17 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Atcoder<'_> {
             (CaseConverted::<LowerCase>::new("practice"), None)
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         if !check_logged_in(&mut sess)? {
             login(&mut sess, username_and_password)?;
         }
 
         let url = if let Some(problem) = problem {
-            let (_, url) = retrieve_tasks_page(&mut sess, || unreachable!(), &contest)?
+            let (_, url) = retrieve_tasks_page(&mut sess, || unreachable!(), false, &contest)?
                 .extract_task_indexes_and_urls()?
                 .into_iter()
-                .find(|(name, _)| name.eq_ignore_ascii_case(&problem))
+                .find(|(index, url)| is_task(index, url, &problem))
                 .with_context(|| "")?;
             url
         } else {
@@ -179,12 +197,13 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Atcoder<'_> {
         };
 
         let names_by_id = sess
-            .get(url)
+            .get(url.clone())
             .colorize_status_code(&[200], (), ..)
             .send()?
             .ensure_status(&[200])?
             .html()?
-            .extract_langs()?;
+            .extract_langs()
+            .map_err(|e| e.with_url(&url))?;
 
         Ok(RetrieveLanguagesOutcome { names_by_id })
     }
@@ -199,22 +218,39 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
             credentials:
                 AtcoderRetrieveSampleTestCasesCredentials {
                     username_and_password,
+                    retry_login,
                 },
             full,
+            strict,
+            statement,
+            statement_language,
+            editorial,
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
-
-        let mut outcome = retrieve_sample_test_cases(&mut sess, username_and_password, &targets)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
+
+        let mut outcome = retrieve_sample_test_cases(
+            &mut sess,
+            username_and_password,
+            retry_login,
+            strict,
+            statement,
+            editorial,
+            statement_language,
+            &targets,
+        )?;
 
         if let Some(RetrieveFullTestCases {
             credentials:
                 AtcoderRetrieveFullTestCasesCredentials {
                     dropbox_access_token,
                 },
+            max_download_size,
         }) = full
         {
             static DROPBOX_PATH_PREFIXES: Lazy<HashMap<String, String>> = Lazy::new(|| {
@@ -263,7 +299,12 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                 };
 
                 let mut retrieve_files = |file_paths| -> anyhow::Result<_> {
-                    retrieve_files(&mut sess, &dropbox_access_token, file_paths)
+                    retrieve_files(
+                        &mut sess,
+                        &dropbox_access_token,
+                        max_download_size,
+                        file_paths,
+                    )
                 };
                 let in_contents = retrieve_files(&in_file_paths)?;
                 let mut out_contents = retrieve_files(&out_file_paths)?;
@@ -427,10 +468,12 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
         fn retrieve_files(
             mut sess: impl SessionMut,
             access_token: &str,
+            max_size: Option<u64>,
             file_paths: &[String],
         ) -> anyhow::Result<IndexMap<String, String>> {
             let contents = super::download_with_progress(
                 sess.shell().progress_draw_target(),
+                max_size,
                 file_paths
                     .iter()
                     .map(|path| {
@@ -463,6 +506,36 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
     }
 }
 
+impl<S: Shell> Exec<RetrieveProblemIndexes<Self, S>> for Atcoder<'_> {
+    type Output = Vec<String>;
+
+    fn exec(args: RetrieveProblemIndexes<Self, S>) -> anyhow::Result<Vec<String>> {
+        let RetrieveProblemIndexes {
+            contest,
+            credentials:
+                AtcoderRetrieveSampleTestCasesCredentials {
+                    username_and_password,
+                    retry_login,
+                },
+            cookie_storage,
+            timeout,
+            headers,
+            tls,
+            shell,
+        } = args;
+
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
+
+        let contest = CaseConverted::<LowerCase>::new(contest);
+        let indexes = retrieve_tasks_page(&mut sess, username_and_password, retry_login, &contest)?
+            .extract_task_indexes_and_urls()?
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        Ok(indexes)
+    }
+}
+
 impl<S: Shell> Exec<RetrieveSubmissionSummaries<Self, S>> for Atcoder<'_> {
     type Output = AtcoderRetrieveSubmissionSummariesOutcome;
 
@@ -477,19 +550,21 @@ impl<S: Shell> Exec<RetrieveSubmissionSummaries<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
         let contest = CaseConverted::<LowerCase>::new(contest);
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         let (mut summaries, num_pages) =
             retrieve_submission_summaries(&mut sess, &contest, 1, username_and_password)?;
 
         for page in 2..=num_pages {
             let (extend, _) = retrieve_submission_summaries(&mut sess, &contest, page, || {
-                bail!("should be logged in");
+                Err(ServiceError::new("should be logged in").into())
             })?;
             summaries.extend(extend);
         }
@@ -506,28 +581,38 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
             target,
             credentials:
                 AtcoderSubmitCredentials {
-                    username_and_password,
+                    mut username_and_password,
+                    retry_login,
                 },
             language_id,
+            language_name,
             code,
             watch_submission,
+            watch_timeout,
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         let (contest, url) = match target {
             ProblemInContest::Index { contest, problem } => {
                 let contest = CaseConverted::<LowerCase>::new(contest);
 
-                let tasks_page = retrieve_tasks_page(&mut sess, username_and_password, &contest)?;
+                let tasks_page = retrieve_tasks_page(
+                    &mut sess,
+                    &mut username_and_password,
+                    retry_login,
+                    &contest,
+                )?;
 
                 let (_, url) = tasks_page
                     .extract_task_indexes_and_urls()?
                     .into_iter()
-                    .find(|(name, _)| name.eq_ignore_ascii_case(&problem))
+                    .find(|(index, url)| is_task(index, url, &problem))
                     .with_context(|| format!("No such problem: `{}`", problem))?;
 
                 (contest, url)
@@ -544,25 +629,17 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
                 .map(|cs| cs[1].to_owned())
                 .with_context(|| "Could not extract screen name of the problem")?;
 
-        let csrf_token = sess
-            .get(url)
-            .colorize_status_code(&[200], (), ..)
-            .send()?
-            .ensure_status(&[200])?
-            .html()?
-            .extract_csrf_token()?;
-
-        let res = sess
-            .post(url!("/contests/{}/submit", contest))
-            .form(&hashmap! {
-                "data.TaskScreenName" => &*problem_screen_name,
-                "data.LanguageId" => language_id.as_ref(),
-                "sourceCode" => code.as_ref(),
-                "csrf_token" => &csrf_token,
-            })
-            .colorize_status_code(&[302], (), ..)
-            .send()?
-            .ensure_status(&[200, 302])?;
+        let res = post_submission(
+            &mut sess,
+            url,
+            url!("/contests/{}/submit", contest),
+            &problem_screen_name,
+            &language_id,
+            language_name.as_deref(),
+            &code,
+            retry_login,
+            |sess| login(sess, &mut username_and_password),
+        )?;
 
         if res.status() == 302 {
             let loc = res.location_url()?;
@@ -570,26 +647,30 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
             if loc.path().starts_with("/contests/") && loc.path().ends_with("/submissions/me") {
                 let (submission_summaries, _) =
                     retrieve_submission_summaries(&mut sess, &contest, 1, || {
-                        bail!("Should be logged in")
+                        Err(ServiceError::new("Should be logged in").into())
                     })?;
 
-                let outcome = SubmitOutcome {
+                let verdict = if watch_submission {
+                    let deadline = watch_timeout.map(|t| Instant::now() + t);
+                    let verdicts =
+                        watch_submissions(sess, &contest, &submission_summaries, deadline)?;
+                    Some(verdicts[0].to_string())
+                } else {
+                    None
+                };
+
+                Ok(SubmitOutcome {
                     problem_screen_name: Some(problem_screen_name),
                     submission_url: submission_summaries[0].detail.clone(),
                     submissions_url: url!("/contests/{}/submissions/me", contest),
-                };
-
-                if watch_submission {
-                    watch_submissions(sess, &contest, &submission_summaries)?;
-                }
-
-                Ok(outcome)
+                    verdict,
+                })
             } else {
                 sess.get(loc).colorize_status_code((), (), ..).send()?;
-                bail!("Submission rejected");
+                Err(ServiceError::new("Submission rejected").into())
             }
         } else {
-            bail!("Submission rejected");
+            Err(ServiceError::new("Submission rejected").into())
         }
     }
 }
@@ -606,12 +687,14 @@ impl<S: Shell> Exec<WatchSubmissions<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             mut shell,
         } = args;
 
         let contest = CaseConverted::<LowerCase>::new(contest);
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), &mut shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), &mut shell)?;
 
         let (summaries, _) =
             retrieve_submission_summaries(&mut sess, &contest, 1, username_and_password)?;
@@ -621,7 +704,7 @@ impl<S: Shell> Exec<WatchSubmissions<Self, S>> for Atcoder<'_> {
         });
 
         if any_incomplete {
-            watch_submissions(sess, &contest, &summaries)?;
+            watch_submissions(sess, &contest, &summaries, None)?;
         } else {
             let content = AnsiColored::new(|w| print_submissions(w, &summaries))?;
             shell.print_ansi(content.get())?;
@@ -654,6 +737,19 @@ pub struct AtcoderRetrieveLanguagesCredentials<'closures> {
 
 pub struct AtcoderRetrieveSampleTestCasesCredentials<'closures> {
     pub username_and_password: &'closures mut dyn FnMut() -> anyhow::Result<(String, String)>,
+    /// Logs in again and retries a `GET` request once if the session has expired on the way.
+    pub retry_login: bool,
+}
+
+/// Language of the statements. Most of the tasks have both.
+#[derive(EnumString, EnumVariantNames, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum AtcoderStatementLanguage {
+    /// Japanese, falling back to English.
+    #[default]
+    Ja,
+    /// English, falling back to Japanese.
+    En,
 }
 
 #[derive(Debug)]
@@ -693,17 +789,27 @@ pub struct AtcoderWatchSubmissionsCredentials<'closures> {
 
 pub struct AtcoderSubmitCredentials<'closures> {
     pub username_and_password: &'closures mut dyn FnMut() -> anyhow::Result<(String, String)>,
+    /// Logs in again and retries a `GET` request once if the session has expired on the way.
+    /// The submission itself is never retried.
+    pub retry_login: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn retrieve_sample_test_cases(
     mut sess: impl SessionMut,
     mut username_and_password: impl FnMut() -> anyhow::Result<(String, String)>,
+    retry_login: bool,
+    strict: bool,
+    statement: bool,
+    editorial: bool,
+    statement_language: AtcoderStatementLanguage,
     targets: &ProblemsInContest,
 ) -> anyhow::Result<RetrieveTestCasesOutcome> {
     let problems = match targets.clone() {
         ProblemsInContest::Indexes { contest, problems } => {
             let contest = CaseConverted::<LowerCase>::new(contest);
-            let html = retrieve_tasks_page(&mut sess, username_and_password, &contest)?;
+            let html =
+                retrieve_tasks_page(&mut sess, &mut username_and_password, retry_login, &contest)?;
 
             let contest_display_name = html
                 .extract_title()?
@@ -717,9 +823,11 @@ fn retrieve_sample_test_cases(
             let indexes_and_urls = html
                 .extract_task_indexes_and_urls()?
                 .into_iter()
-                .filter(|(index, _)| {
+                .filter(|(index, url)| {
                     if let Some(only) = only {
+                        let screen_name = url.path_segments().and_then(Iterator::last);
                         only.remove(&index.to_lowercase())
+                            | screen_name.is_some_and(|s| only.remove(&s.to_lowercase()))
                     } else {
                         true
                     }
@@ -743,8 +851,12 @@ fn retrieve_sample_test_cases(
                 if let Some((_, _, only)) = problems.get_mut(&contest) {
                     only.insert(url);
                 } else {
-                    let html =
-                        retrieve_tasks_page(&mut sess, &mut username_and_password, &contest)?;
+                    let html = retrieve_tasks_page(
+                        &mut sess,
+                        &mut username_and_password,
+                        retry_login,
+                        &contest,
+                    )?;
                     let contest_display_name = html
                         .extract_title()?
                         .trim_start_matches("Tasks - ")
@@ -774,22 +886,18 @@ fn retrieve_sample_test_cases(
 
     let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
 
-    for (contest, (contest_display_name, mut indexes_and_urls)) in problems {
-        let test_suites = sess
-            .get(url!("/contests/{}/tasks_print", contest))
-            .colorize_status_code(&[200], (), ..)
-            .send()?
-            .ensure_status(&[200])?
-            .html()?
-            .extract_samples();
-
-        if indexes_and_urls.len() > test_suites.len() {
-            sess.shell().warn(format!(
-                "Found {} task(s) in `tasks`, {} task(s) in `tasks_print`",
-                indexes_and_urls.len(),
-                test_suites.len(),
-            ))?;
-        }
+    for (contest, (contest_display_name, indexes_and_urls)) in problems {
+        let test_suites = get_retrying_login(
+            &mut sess,
+            url!("/contests/{}/tasks_print", contest),
+            &[200],
+            (),
+            retry_login,
+            |sess| login(sess, &mut username_and_password),
+        )?
+        .ensure_status(&[200])?
+        .html()?
+        .extract_samples(statement_language);
 
         let contest = &RetrieveTestCasesOutcomeProblemContest {
             id: (*contest).to_owned(),
@@ -798,54 +906,127 @@ fn retrieve_sample_test_cases(
             submissions_url: url!("/contests/{}/submissions/me", contest),
         };
 
-        for result in test_suites {
-            match result {
-                Ok((index, display_name, test_suite)) => {
-                    if let Some(url) = indexes_and_urls.shift_remove(&*index) {
-                        let screen_name = url
-                            .path_segments()
-                            .and_then(Iterator::last)
-                            .with_context(|| "Empty URL")?
-                            .to_owned();
-
-                        let test_suite = match test_suite {
-                            Ok(test_suite) => test_suite,
-                            Err(err) => {
-                                sess.shell().warn(err)?;
-
-                                TestSuite::Batch(BatchTestSuite {
-                                    timelimit: None,
-                                    r#match: Match::Lines,
-                                    cases: vec![],
-                                    extend: vec![],
-                                })
-                            }
-                        };
+        let problems =
+            pair_samples_with_tasks(sess.shell(), strict, contest, indexes_and_urls, test_suites)?;
 
-                        outcome.problems.push(RetrieveTestCasesOutcomeProblem {
-                            contest: Some(contest.clone()),
-                            url,
-                            index,
-                            screen_name: Some(screen_name),
-                            display_name,
-                            test_suite,
-                            text_files: indexmap![],
-                        });
-                    }
+        for mut problem in problems {
+            if !statement {
+                problem.statement = None;
+            }
+
+            // `tasks_print` does not have the links to the editorials.
+            if editorial {
+                let url = problem.url.clone();
+                let html =
+                    get_retrying_login(&mut sess, url.clone(), &[200], (), retry_login, |sess| {
+                        login(sess, &mut username_and_password)
+                    })?
+                    .ensure_status(&[200])?
+                    .html()?;
+
+                if let Some(editorial) = html.extract_editorial_url(&url) {
+                    problem.test_suite.set_editorial(editorial);
                 }
-                Err(err) => sess.shell().warn(err)?,
             }
-        }
 
-        for (index, _) in indexes_and_urls {
-            sess.shell()
-                .warn(format!("Could not find `{}` in `tasks_print`", index))?;
+            outcome.problems.push(problem);
         }
     }
 
     Ok(outcome)
 }
 
+/// Pairs the samples in `tasks_print` with the tasks in `tasks`.
+///
+/// Whatever could not be extracted or paired is warned and skipped, or is an error if `strict`.
+fn pair_samples_with_tasks(
+    mut shell: impl Shell,
+    strict: bool,
+    contest: &RetrieveTestCasesOutcomeProblemContest,
+    mut indexes_and_urls: IndexMap<String, Url>,
+    test_suites: Vec<anyhow::Result<TaskSamples>>,
+) -> anyhow::Result<Vec<RetrieveTestCasesOutcomeProblem>> {
+    let mut warn = |message: &dyn fmt::Display| -> anyhow::Result<()> {
+        if strict {
+            bail!("{}", message);
+        }
+        shell.warn(message).map_err(Into::into)
+    };
+
+    if indexes_and_urls.len() > test_suites.len() {
+        warn(&format!(
+            "Found {} task(s) in `tasks`, {} task(s) in `tasks_print`",
+            indexes_and_urls.len(),
+            test_suites.len(),
+        ))?;
+    }
+
+    let mut problems = vec![];
+
+    for result in test_suites {
+        match result {
+            Ok(TaskSamples {
+                index,
+                display_name,
+                test_suite,
+                warnings,
+                statement,
+            }) => {
+                for warning in warnings {
+                    warn(&warning)?;
+                }
+
+                if let Some(url) = indexes_and_urls.shift_remove(&*index) {
+                    let screen_name = url
+                        .path_segments()
+                        .and_then(Iterator::last)
+                        .with_context(|| "Empty URL")?
+                        .to_owned();
+
+                    let test_suite = match test_suite {
+                        Ok(test_suite) => test_suite,
+                        Err(err) => {
+                            warn(&err)?;
+
+                            TestSuite::Batch(BatchTestSuite {
+                                description: None,
+                                editorial: None,
+                                timelimit: None,
+                                r#match: Match::Lines,
+                                reference: None,
+                                generator: None,
+                                input_filter: None,
+                                cases: vec![],
+                                cases_from: None,
+                                exclude: vec![],
+                                extend: vec![],
+                            })
+                        }
+                    };
+
+                    problems.push(RetrieveTestCasesOutcomeProblem {
+                        contest: Some(contest.clone()),
+                        url,
+                        index,
+                        screen_name: Some(screen_name),
+                        display_name,
+                        test_suite,
+                        text_files: indexmap![],
+                        statement,
+                    });
+                }
+            }
+            Err(err) => warn(&err)?,
+        }
+    }
+
+    for (index, _) in indexes_and_urls {
+        warn(&format!("Could not find `{}` in `tasks_print`", index))?;
+    }
+
+    Ok(problems)
+}
+
 fn login(
     mut sess: impl SessionMut,
     mut username_and_password: impl FnMut() -> anyhow::Result<(String, String)>,
@@ -853,13 +1034,16 @@ fn login(
     while {
         let (username, password) = username_and_password()?;
 
+        let url = url!("/login");
+
         let csrf_token = sess
-            .get(url!("/login"))
+            .get(url.clone())
             .colorize_status_code(&[200], (), ..)
             .send()?
             .ensure_status(&[200])?
             .html()?
-            .extract_csrf_token()?;
+            .extract_csrf_token()
+            .map_err(|e| e.with_url(&url))?;
 
         let payload = hashmap!(
             "csrf_token" => csrf_token,
@@ -890,28 +1074,71 @@ fn check_logged_in(mut sess: impl SessionMut) -> anyhow::Result<bool> {
     Ok(status == 200)
 }
 
+/// Sends a `GET` request to `url`.
+///
+/// If `retry_login` is `true` and the session seems to have expired (a redirect to `/login`, or
+/// `403`), logs in again with `login` and retries the request once. Do not use this for requests
+/// with side effects.
+fn get_retrying_login<S: SessionMut>(
+    mut sess: S,
+    url: Url,
+    ok: impl StatusCodeRange + Copy,
+    warn: impl StatusCodeRange + Copy,
+    retry_login: bool,
+    login: impl FnOnce(&mut S) -> anyhow::Result<()>,
+) -> anyhow::Result<reqwest::blocking::Response> {
+    let res = sess
+        .get(url.clone())
+        .colorize_status_code(ok, warn, ..)
+        .send()?;
+
+    if !(retry_login && is_session_expired(&res)) {
+        return Ok(res);
+    }
+
+    sess.shell()
+        .warn("The session seems to have expired. Logging in again")?;
+    login(&mut sess)?;
+
+    sess.get(url).colorize_status_code(ok, warn, ..).send()
+}
+
+fn is_session_expired(res: &reqwest::blocking::Response) -> bool {
+    match res.status().as_u16() {
+        403 => true,
+        301..=303 | 307 | 308 => res.location_url().is_ok_and(|url| url.path() == "/login"),
+        _ => false,
+    }
+}
+
 fn participate(
     mut sess: impl SessionMut,
     credentials: impl FnMut() -> anyhow::Result<(String, String)>,
     contest: &CaseConverted<LowerCase>,
     explicit: bool,
 ) -> anyhow::Result<ParticipateOutcome> {
+    let url = url!("/contests/{}", contest);
+
     let res = sess
-        .get(url!("/contests/{}", contest))
+        .get(url.clone())
         .colorize_status_code(&[200], (), ..)
         .send()?
         .ensure_status(&[200, 404])?;
 
     if res.status() == 404 {
-        bail!(
+        return Err(ServiceError::new(format_args!(
             "The contest `{}` does not exist, or your are not authorized",
             contest,
-        );
+        ))
+        .into());
     }
 
     let html = res.html()?;
 
-    let status = ContestStatus::now(html.extract_contest_duration()?, contest);
+    let duration = html
+        .extract_contest_duration()
+        .map_err(|e| e.with_url(&url))?;
+    let status = ContestStatus::now(duration, contest);
 
     if !explicit {
         status.raise_if_not_begun()?;
@@ -925,14 +1152,17 @@ fn participate(
         Ok(ParticipateOutcome::ContestIsFinished)
     } else {
         let html = sess
-            .get(url!("/contests/{}", contest))
+            .get(url.clone())
             .colorize_status_code(&[200], (), ..)
             .send()?
             .ensure_status(&[200])?
             .html()?;
 
-        if html.contains_registration_button()? {
-            let csrf_token = html.extract_csrf_token()?;
+        if html
+            .contains_registration_button()
+            .map_err(|e| e.with_url(&url))?
+        {
+            let csrf_token = html.extract_csrf_token().map_err(|e| e.with_url(&url))?;
 
             sess.post(url!("/contests/{}/register", contest))
                 .form(&hashmap!("csrf_token" => csrf_token))
@@ -949,17 +1179,40 @@ fn participate(
 
 fn retrieve_tasks_page(
     mut sess: impl SessionMut,
-    username_and_password: impl FnMut() -> anyhow::Result<(String, String)>,
+    mut username_and_password: impl FnMut() -> anyhow::Result<(String, String)>,
+    retry_login: bool,
     contest: &CaseConverted<LowerCase>,
 ) -> anyhow::Result<Html> {
-    let res = sess
-        .get(url!("/contests/{}/tasks", contest))
-        .colorize_status_code(&[200], &[404], ..)
-        .send()?
-        .ensure_status(&[200, 404])?;
+    let res = get_retrying_login(
+        &mut sess,
+        url!("/contests/{}/tasks", contest),
+        &[200],
+        &[404],
+        retry_login,
+        |sess| login(sess, &mut username_and_password),
+    )?
+    .ensure_status(&[200, 404])?;
 
     if res.status() == 200 {
-        res.html().map_err(Into::into)
+        let html = res.html()?;
+
+        // The task list can be empty before the contest begins.
+        if matches!(html.extract_task_indexes_and_urls(), Ok(tasks) if tasks.is_empty()) {
+            let url = url!("/contests/{}", contest);
+
+            let duration = sess
+                .get(url.clone())
+                .colorize_status_code(&[200], (), ..)
+                .send()?
+                .ensure_status(&[200])?
+                .html()?
+                .extract_contest_duration()
+                .map_err(|e| e.with_url(&url))?;
+
+            ContestStatus::now(duration, contest).raise_if_not_begun()?;
+        }
+
+        Ok(html)
     } else {
         participate(&mut sess, username_and_password, contest, false)?;
 
@@ -1061,11 +1314,13 @@ fn print_submissions(mut wtr: impl WriteColor, summaries: &[SubmissionSummary])
     Ok(())
 }
 
+/// Watches the submissions until all of them are judged, and returns their final verdicts.
 fn watch_submissions(
     mut sess: impl SessionMut,
     contest: &CaseConverted<LowerCase>,
     summaries: &[SubmissionSummary],
-) -> anyhow::Result<()> {
+    deadline: Option<Instant>,
+) -> anyhow::Result<Vec<Verdict>> {
     let rt = Runtime::new()?;
     let mut handles = vec![];
 
@@ -1102,10 +1357,7 @@ fn watch_submissions(
             align_left(&summary.language, lang_max_width),
         ));
 
-        if matches!(
-            summary.status,
-            Verdict::Wj | Verdict::Wr | Verdict::Judging(..)
-        ) {
+        if summary.status.is_incomplete() {
             let id = summary.id().to_owned();
 
             let mut url = url!("/contests/{}/submissions/me/status/json", contest);
@@ -1132,20 +1384,6 @@ fn watch_submissions(
                 });
 
                 loop {
-                    #[derive(Deserialize)]
-                    #[serde(rename_all = "PascalCase")]
-                    struct VerdictProgress {
-                        interval: Option<u64>,
-                        result: IndexMap<String, VerdictProgressResult>,
-                    }
-
-                    #[derive(Deserialize)]
-                    #[serde(rename_all = "PascalCase")]
-                    struct VerdictProgressResult {
-                        html: String,
-                        //score: String,
-                    }
-
                     let res = trap!(
                         client
                             .get(url.clone())
@@ -1154,65 +1392,52 @@ fn watch_submissions(
                             .await,
                     );
 
-                    let VerdictProgress { interval, result } = trap!(res.json().await);
-
-                    let VerdictProgressResult { html } = trap!(result
-                        .get(&id)
-                        .with_context(|| format!("Not found: `{}`", id)));
-
-                    let text = tokio::task::block_in_place(|| {
-                        Html::parse_fragment(html)
-                            .root_element()
-                            .text()
-                            .map(ToOwned::to_owned)
-                            .collect::<Vec<_>>()
-                    });
-
-                    if let Some(interval) = interval {
-                        let verdict = match &text[..] {
-                            [verdict] => verdict,
-                            _ => trap!(Err(anyhow!("Could not extract information"))),
-                        };
-
-                        tokio::task::block_in_place(|| {
-                            if let Some(caps) = JUDGING.captures(verdict) {
-                                let position = caps[1].parse().unwrap();
-                                let length = caps[2].parse().unwrap();
-                                let verdict = &caps[3];
-
-                                pb.set_style(ProgressStyle::default_bar().template(&format!(
-                                    "{{prefix}}{{msg:3{style}}} {{pos:>3{style}}}/\
-                                     {{len:>3{style}}} {{bar:15{style}}} │",
-                                    style = style(verdict),
-                                )));
-
-                                pb.set_message(verdict);
-                                pb.set_length(length);
-                                pb.set_position(position);
-                            } else {
-                                pb.set_message(verdict);
+                    let json = trap!(res.text().await);
+
+                    match trap!(tokio::task::block_in_place(|| parse_submission_status(
+                        &json, &id
+                    ))) {
+                        SubmissionStatus::Judging { verdict, interval } => {
+                            if matches!(deadline, Some(d) if Instant::now() + interval > d) {
+                                finish_pb();
+                                return Err(anyhow!(
+                                    "Timed out while waiting for the verdict of `{}`",
+                                    id,
+                                ));
                             }
-                        });
 
-                        tokio::time::sleep(Duration::from_millis(interval)).await;
-                    } else {
-                        let (verdict, time_and_memory) = match &text[..] {
-                            [verdict, time_and_memory] => (verdict, time_and_memory),
-                            _ => trap!(Err(anyhow!("Could not extract information"))),
-                        };
-
-                        let verdict = Verdict::new(verdict);
-
-                        let (exec_time, memory) =
-                            match *time_and_memory.split(" ms").collect::<Vec<_>>() {
-                                [time, memory] => (format!("{} ms", time), memory),
-                                _ => trap!(Err(anyhow!("Could not extract information"))),
-                            };
-
-                        tokio::task::block_in_place(|| {
-                            finish(&pb, &verdict, &exec_time, memory);
-                        });
-                        break Result::<(), anyhow::Error>::Ok(());
+                            tokio::task::block_in_place(|| {
+                                if let Some(caps) = JUDGING.captures(&verdict) {
+                                    let position = caps[1].parse().unwrap();
+                                    let length = caps[2].parse().unwrap();
+                                    let verdict = &caps[3];
+
+                                    pb.set_style(ProgressStyle::default_bar().template(&format!(
+                                        "{{prefix}}{{msg:3{style}}} {{pos:>3{style}}}/\
+                                         {{len:>3{style}}} {{bar:15{style}}} │",
+                                        style = style(verdict),
+                                    )));
+
+                                    pb.set_message(verdict);
+                                    pb.set_length(length);
+                                    pb.set_position(position);
+                                } else {
+                                    pb.set_message(&verdict);
+                                }
+                            });
+
+                            tokio::time::sleep(interval).await;
+                        }
+                        SubmissionStatus::Finished {
+                            verdict,
+                            exec_time,
+                            memory,
+                        } => {
+                            tokio::task::block_in_place(|| {
+                                finish(&pb, &verdict, &exec_time, &memory);
+                            });
+                            break Result::<_, anyhow::Error>::Ok(verdict);
+                        }
                     }
                 }
             }));
@@ -1228,11 +1453,18 @@ fn watch_submissions(
 
     mp.join()?;
 
-    for handle in handles {
-        rt.block_on(handle)??;
+    let mut verdicts = vec![];
+    let mut handles = handles.into_iter();
+
+    for summary in summaries {
+        verdicts.push(if summary.status.is_incomplete() {
+            rt.block_on(handles.next().expect("should be pushed"))??
+        } else {
+            summary.status.clone()
+        });
     }
 
-    return Ok(());
+    return Ok(verdicts);
 
     static JUDGING: Lazy<Regex> = lazy_regex!(r"\A\s*([0-9]{1,3})/([0-9]{1,3})\s*(\S*)\s*\z");
 
@@ -1265,6 +1497,85 @@ fn watch_submissions(
     }
 }
 
+/// Gets the problem page and posts the submission form with the CSRF token in it. If `language_id`
+/// is not in the form, warns and submits it anyway since the page may be out of date.
+#[allow(clippy::too_many_arguments)]
+fn post_submission<S: SessionMut>(
+    mut sess: S,
+    problem_url: Url,
+    submit_url: Url,
+    problem_screen_name: &str,
+    language_id: &str,
+    language_name: Option<&str>,
+    code: &str,
+    retry_login: bool,
+    login: impl FnOnce(&mut S) -> anyhow::Result<()>,
+) -> anyhow::Result<reqwest::blocking::Response> {
+    // Only this `GET` is retried. The submission itself is never sent twice.
+    let problem_page = get_retrying_login(
+        &mut sess,
+        problem_url.clone(),
+        &[200],
+        (),
+        retry_login,
+        |sess| login(sess),
+    )?
+    .ensure_status(&[200])?
+    .html()?;
+
+    let csrf_token = problem_page
+        .extract_csrf_token()
+        .map_err(|e| e.with_url(&problem_url))?;
+
+    if let Ok(names_by_id) = problem_page.extract_langs() {
+        if let Some(warning) = language_id_mismatch(&names_by_id, language_id, language_name) {
+            sess.shell().warn(warning)?;
+        }
+    }
+
+    sess.post(submit_url)
+        .form(&hashmap! {
+            "data.TaskScreenName" => problem_screen_name,
+            "data.LanguageId" => language_id,
+            "sourceCode" => code,
+            "csrf_token" => &csrf_token,
+        })
+        .colorize_status_code(&[302], (), ..)
+        .send()?
+        .ensure_status(&[200, 302])
+}
+
+/// A warning if `language_id` is not in `names_by_id`, suggesting the language whose name is the
+/// closest to `language_name`.
+fn language_id_mismatch(
+    names_by_id: &IndexMap<String, String>,
+    language_id: &str,
+    language_name: Option<&str>,
+) -> Option<String> {
+    if names_by_id.contains_key(language_id) {
+        return None;
+    }
+
+    let mut msg = format!(
+        "`{}` is not in the submission form. Submitting it anyway",
+        language_id,
+    );
+
+    if let Some((id, name)) = language_name.and_then(|language_name| {
+        let language_name = language_name.to_lowercase();
+
+        // "C++ (GCC 9.2.1)" is compared as "c++".
+        names_by_id.iter().min_by_key(|(_, name)| {
+            let name = name.split(" (").next().unwrap_or(name).to_lowercase();
+            strsim::levenshtein(&name, &language_name)
+        })
+    }) {
+        msg += &format!(". Did you mean `{}` ({})?", id, name);
+    }
+
+    Some(msg)
+}
+
 fn align_left(s: &str, n: usize) -> String {
     let spaces = n.saturating_sub(s.width());
     s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
@@ -1301,7 +1612,7 @@ impl ContestStatus {
 
     fn raise_if_not_begun(&self) -> anyhow::Result<()> {
         if let ContestStatus::NotBegun(contest, time) = self {
-            bail!("`{}` will begin at {}", contest, time)
+            return Err(ContestNotBegun::new(&**contest, *time).into());
         }
         Ok(())
     }
@@ -1343,6 +1654,78 @@ struct SubmissionSummaryUser {
 }
 
 #[derive(Debug, PartialEq)]
+enum SubmissionStatus {
+    Judging {
+        verdict: String,
+        interval: Duration,
+    },
+    Finished {
+        verdict: Verdict,
+        exec_time: String,
+        memory: String,
+    },
+}
+
+/// Parses a response from `/contests/{}/submissions/me/status/json`.
+fn parse_submission_status(json: &str, id: &str) -> anyhow::Result<SubmissionStatus> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct VerdictProgress {
+        interval: Option<u64>,
+        result: IndexMap<String, VerdictProgressResult>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct VerdictProgressResult {
+        html: String,
+        //score: String,
+    }
+
+    let VerdictProgress { interval, result } = serde_json::from_str(json)?;
+
+    let VerdictProgressResult { html } = result
+        .get(id)
+        .with_context(|| format!("Not found: `{}`", id))?;
+
+    let text = Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+
+    if let Some(interval) = interval {
+        let verdict = match &text[..] {
+            [verdict] => verdict.clone(),
+            _ => bail!("Could not extract information"),
+        };
+
+        Ok(SubmissionStatus::Judging {
+            verdict,
+            interval: Duration::from_millis(interval),
+        })
+    } else {
+        let (verdict, time_and_memory) = match &text[..] {
+            [verdict, time_and_memory] => (verdict, time_and_memory),
+            _ => bail!("Could not extract information"),
+        };
+
+        let verdict = Verdict::new(verdict);
+
+        let (exec_time, memory) = match *time_and_memory.split(" ms").collect::<Vec<_>>() {
+            [time, memory] => (format!("{} ms", time), memory.to_owned()),
+            _ => bail!("Could not extract information"),
+        };
+
+        Ok(SubmissionStatus::Finished {
+            verdict,
+            exec_time,
+            memory,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 enum Verdict {
     Ac,
     Ce,
@@ -1390,6 +1773,10 @@ impl Verdict {
         static JUDGING: Lazy<Regex> = lazy_regex!(r"\A\s*([0-9]{1,3})/([0-9]{1,3})\s*(\S*)\s*\z");
     }
 
+    fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Wj | Self::Wr | Self::Judging(..))
+    }
+
     fn color_spec(&self) -> ColorSpec {
         return match self {
             Self::Ac => from_fg_and_bold(Some(Color::Green), true),
@@ -1448,17 +1835,29 @@ impl Serialize for Verdict {
     }
 }
 
-#[ext]
+/// Samples of a task in `tasks_print`.
+struct TaskSamples {
+    index: String,
+    display_name: String,
+    test_suite: anyhow::Result<TestSuite>,
+    /// Set if the samples are not in the requested language, or if the statement mentions more
+    /// samples than extracted.
+    warnings: Vec<String>,
+    /// The statement in Markdown.
+    statement: Option<String>,
+}
+
+#[ext(HtmlExt)]
 impl Html {
-    fn extract_title(&self) -> anyhow::Result<&str> {
+    fn extract_title(&self) -> Result<&str, ScrapeError> {
         self.select(static_selector!(":root > head > title"))
             .flat_map(|r| r.text())
             .exactly_one()
             .ok()
-            .with_context(|| "Could not find `<title>`")
+            .ok_or_else(|| ScrapeError::new("title"))
     }
 
-    fn extract_csrf_token(&self) -> anyhow::Result<String> {
+    fn extract_csrf_token(&self) -> Result<String, ScrapeError> {
         (|| -> _ {
             let token = self
                 .select(static_selector!("[name=\"csrf_token\"]"))
@@ -1473,10 +1872,10 @@ impl Html {
                 Some(token)
             }
         })()
-        .with_context(|| "Could not find the CSRF token")
+        .ok_or_else(|| ScrapeError::new("CSRF token"))
     }
 
-    fn extract_contest_duration(&self) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    fn extract_contest_duration(&self) -> Result<(DateTime<Utc>, DateTime<Utc>), ScrapeError> {
         (|| -> _ {
             static FORMAT: &str = "%F %T%z";
             let mut it = self.select(static_selector!("time"));
@@ -1486,14 +1885,14 @@ impl Html {
             let t2 = DateTime::parse_from_str(t2, FORMAT).ok()?;
             Some((t1.with_timezone(&Utc), t2.with_timezone(&Utc)))
         })()
-        .with_context(|| "Could not find the contest duration")
+        .ok_or_else(|| ScrapeError::new("contest duration"))
     }
 
-    fn contains_registration_button(&self) -> anyhow::Result<bool> {
+    fn contains_registration_button(&self) -> Result<bool, ScrapeError> {
         let insert_participant_box = self
             .select(static_selector!("#main-container .insert-participant-box"))
             .next()
-            .with_context(|| "Could not find the registration button")?;
+            .ok_or_else(|| ScrapeError::new("registration button"))?;
 
         Ok(insert_participant_box
             .select(static_selector!("form"))
@@ -1503,7 +1902,7 @@ impl Html {
             .any(|s| ["参加登録", "Register"].contains(&s)))
     }
 
-    fn extract_task_indexes_and_urls(&self) -> anyhow::Result<IndexMap<String, Url>> {
+    fn extract_task_indexes_and_urls(&self) -> Result<IndexMap<String, Url>, ScrapeError> {
         self.select(static_selector!(
             "#main-container > div.row > div.col-sm-12 > div.panel > table.table > tbody > tr",
         ))
@@ -1515,10 +1914,13 @@ impl Html {
         })
         .collect::<Option<IndexMap<_, _>>>()
         .filter(|m| !m.is_empty())
-        .with_context(|| "Could not extract task indexes and URLs")
+        .ok_or_else(|| ScrapeError::new("task indexes and URLs"))
     }
 
-    fn extract_samples(&self) -> Vec<anyhow::Result<(String, String, anyhow::Result<TestSuite>)>> {
+    fn extract_samples(
+        &self,
+        language: AtcoderStatementLanguage,
+    ) -> Vec<anyhow::Result<TaskSamples>> {
         return self
             .select(static_selector!(
                 "#main-container > div.row div[class=\"col-sm-12\"]",
@@ -1540,6 +1942,14 @@ impl Html {
                     (caps[1].to_owned(), caps[2].to_owned())
                 };
 
+                let mut warnings = vec![];
+
+                // In `tasks_print`, there are multiple `#task-statement`s.
+                let task_statement = div
+                    .select(static_selector!(":scope > div[id=\"task-statement\"]"))
+                    .exactly_one()
+                    .ok();
+
                 let test_suite = (|| {
                     let timelimit = div
                         .select(static_selector!(":scope > p"))
@@ -1548,42 +1958,104 @@ impl Html {
                         .exactly_one()
                         .map_err(|_| "Could not extract the timelimit")?;
 
-                    // In `tasks_print`, there are multiple `#task-statement`s.
-                    let samples = div
-                        .select(static_selector!(":scope > div[id=\"task-statement\"]"))
-                        .exactly_one()
-                        .ok()
-                        .and_then(extract_samples)
+                    let mut fell_back = false;
+                    let samples = task_statement
+                        .and_then(|stmt| extract_samples(stmt, language, &mut fell_back))
                         .ok_or("Could not extract the sample cases")?;
 
+                    if fell_back {
+                        let (requested, extracted) = match language {
+                            AtcoderStatementLanguage::Ja => ("Japanese", "English"),
+                            AtcoderStatementLanguage::En => ("English", "Japanese"),
+                        };
+                        warnings.push(format!(
+                            "{}: The statement has no samples in {}. Extracted the ones in {}",
+                            index, requested, extracted,
+                        ));
+                    }
+
                     Ok::<_, &str>(if timelimit == Duration::new(0, 0) {
                         TestSuite::Unsubmittable
-                    } else if let Samples::Batch(r#match, samples) = samples {
+                    } else if let Samples::Batch(r#match, samples, num_mentioned) = samples {
+                        if num_mentioned > samples.len() {
+                            warnings.push(format!(
+                                "{}: The statement mentions {} sample(s), but only {} were \
+                                 extracted",
+                                index,
+                                num_mentioned,
+                                samples.len(),
+                            ));
+                        }
+
                         TestSuite::Batch(BatchTestSuite {
+                            description: None,
+                            editorial: None,
                             timelimit: Some(timelimit),
                             r#match,
+                            reference: None,
+                            generator: None,
+                            input_filter: None,
                             cases: samples
                                 .into_iter()
                                 .enumerate()
                                 .map(|(i, (input, output))| PartialBatchTestCase {
                                     name: Some(format!("sample{}", i + 1)),
+                                    note: None,
                                     r#in: input.into(),
                                     out: Some(output.into()),
                                     timelimit: None,
                                     r#match: None,
+                                    stderr: None,
+                                    stderr_match: None,
                                 })
                                 .collect(),
+                            cases_from: None,
+                            exclude: vec![],
+                            extend: vec![],
+                        })
+                    } else if let Samples::NotText = samples {
+                        warnings.push(format!(
+                            "{}: The statement has samples, but none of them are text (e.g. \
+                             images). Add the test cases by hand",
+                            index,
+                        ));
+
+                        TestSuite::Batch(BatchTestSuite {
+                            description: Some(
+                                "The samples could not be extracted as text.".to_owned(),
+                            ),
+                            editorial: None,
+                            timelimit: Some(timelimit),
+                            r#match: Match::Lines,
+                            reference: None,
+                            generator: None,
+                            input_filter: None,
+                            cases: vec![],
+                            cases_from: None,
+                            exclude: vec![],
                             extend: vec![],
                         })
                     } else {
                         TestSuite::Interactive(InteractiveTestSuite {
+                            description: None,
+                            editorial: None,
                             timelimit: Some(timelimit),
+                            tester: None,
+                            each_args: vec![],
+                            example: vec![],
                         })
                     })
                 })()
                 .map_err(|e| anyhow!("{}: {}", index, e));
 
-                Ok((index, display_name, test_suite))
+                Ok(TaskSamples {
+                    index,
+                    display_name,
+                    test_suite,
+                    warnings,
+                    statement: task_statement
+                        .map(|stmt| statement_in(stmt, language).to_markdown()),
+                })
             })
             .collect();
 
@@ -1608,7 +2080,35 @@ impl Html {
             Some(Duration::from_millis(timelimit))
         }
 
-        fn extract_samples(task_statement: ElementRef<'_>) -> Option<Samples> {
+        /// The statement in `language`, or the one in the other language if there is not. Most of the
+        /// tasks have both in `span.lang`, and the old ones have only the Japanese one outside it.
+        fn statement_in(
+            task_statement: ElementRef<'_>,
+            language: AtcoderStatementLanguage,
+        ) -> ElementRef<'_> {
+            let ja = || {
+                task_statement
+                    .select(static_selector!(":scope > span.lang > span.lang-ja"))
+                    .next()
+            };
+            let en = || {
+                task_statement
+                    .select(static_selector!(":scope > span.lang > span.lang-en"))
+                    .next()
+            };
+            match language {
+                AtcoderStatementLanguage::Ja => ja().or_else(en),
+                AtcoderStatementLanguage::En => en().or_else(ja),
+            }
+            .unwrap_or(task_statement)
+        }
+
+        /// Sets `fell_back` if the samples are extracted from the statement in the other language.
+        fn extract_samples(
+            task_statement: ElementRef<'_>,
+            language: AtcoderStatementLanguage,
+            fell_back: &mut bool,
+        ) -> Option<Samples> {
             // TODO:
             // - https://atcoder.jp/contests/arc019/tasks/arc019_4 (interactive)
             // - https://atcoder.jp/contests/arc021/tasks/arc021_4 (interactive)
@@ -1656,14 +2156,29 @@ impl Html {
                 lazy_selector!("span.lang > span.lang-ja > div.part > section > pre");
 
             let stmt = task_statement;
-            try_extract_samples(stmt, &P1_HEAD, &P1_CONTENT, &IN_JA, &OUT_JA)
-                .or_else(|| try_extract_samples(stmt, &P2_HEAD, &P2_CONTENT, &IN_EN, &OUT_EN))
+            let ja = || try_extract_samples(stmt, &P1_HEAD, &P1_CONTENT, &IN_JA, &OUT_JA);
+            let en = || try_extract_samples(stmt, &P2_HEAD, &P2_CONTENT, &IN_EN, &OUT_EN);
+            let extract = |in_ja: bool| if in_ja { ja() } else { en() };
+            let prefers_ja = matches!(language, AtcoderStatementLanguage::Ja);
+            extract(prefers_ja)
+                .or_else(|| {
+                    let samples = extract(!prefers_ja);
+                    *fell_back = samples.is_some();
+                    samples
+                })
                 .or_else(|| try_extract_samples(stmt, &P3_HEAD, &P3_CONTENT, &IN_JA, &OUT_JA))
                 .or_else(|| try_extract_samples(stmt, &P4_HEAD, &P4_CONTENT, &IN_JA, &OUT_JA))
                 .or_else(|| try_extract_samples(stmt, &P5_HEAD, &P5_CONTENT, &IN_JA, &OUT_JA))
                 .or_else(|| try_extract_samples(stmt, &P6_HEAD, &P6_CONTENT, &IN_JA, &OUT_JA))
                 .or_else(|| try_extract_samples(stmt, &P7_HEAD, &P7_CONTENT, &IN_JA, &OUT_JA))
                 .or_else(|| try_extract_samples(stmt, &P8_HEAD, &P8_CONTENT, &IN_JA, &OUT_JA))
+                .or_else(|| {
+                    // e.g. samples of geometry problems given as images
+                    stmt.select(static_selector!("h3"))
+                        .map(|r| r.collect_text())
+                        .any(|text| IN_JA.is_match(&text) || IN_EN.is_match(&text))
+                        .then_some(Samples::NotText)
+                })
         }
 
         fn try_extract_samples(
@@ -1720,6 +2235,7 @@ impl Html {
 
             let mut inputs = BTreeMap::<usize, _>::new();
             let mut outputs = BTreeMap::<usize, _>::new();
+            let mut num_mentioned = 0;
             let mut next = None;
             let selector = or(selector_for_header, selector_for_content);
             for elem_ref in task_statement.select(&selector) {
@@ -1730,9 +2246,12 @@ impl Html {
                     } else if let Some(caps) = re_output.captures(&text) {
                         next = Some((false, parse_zenkaku(&caps[1]).ok()?));
                     }
+                    if let Some((_, n)) = next {
+                        num_mentioned = num_mentioned.max(n);
+                    }
                 } else if ["pre", "section"].contains(&elem_ref.value().name()) {
                     if let Some((is_input, n)) = next {
-                        let text = elem_ref.collect_text();
+                        let text = elem_ref.sample_text();
                         if is_input {
                             inputs.insert(n, text);
                         } else {
@@ -1769,7 +2288,7 @@ impl Html {
                 return None;
             }
 
-            Some(Samples::Batch(matching, samples))
+            Some(Samples::Batch(matching, samples, num_mentioned))
         }
 
         fn or(selector1: &Selector, selector2: &Selector) -> Selector {
@@ -1820,22 +2339,27 @@ impl Html {
         }
 
         enum Samples {
-            Batch(Match, Vec<(String, String)>),
+            /// The last one is the largest number of the samples mentioned in the headers.
+            Batch(Match, Vec<(String, String)>, usize),
             Interactive,
+            /// There are headers for samples, but no text under them.
+            NotText,
         }
     }
 
-    fn extract_langs(&self) -> anyhow::Result<IndexMap<String, String>> {
-        self.select(static_selector!("#select-lang option"))
-            .filter(|r| r.text().next().is_some()) // Ignores `<option></option>`
-            .map(|option| {
-                let id = option.value().attr("value")?.to_owned();
-                let name = option.text().next()?.to_owned();
-                Some((id, name))
-            })
-            .collect::<Option<IndexMap<_, _>>>()
-            .filter(|m| !m.is_empty())
-            .with_context(|| "Could not extract the available languages")
+    fn extract_langs(&self) -> Result<IndexMap<String, String>, ScrapeError> {
+        self.select(static_selector!(
+            "#select-lang option, select[name=\"data.LanguageId\"] option",
+        ))
+        .filter(|r| r.text().next().is_some()) // Ignores `<option></option>`
+        .map(|option| {
+            let id = option.value().attr("value")?.to_owned();
+            let name = option.text().next()?.to_owned();
+            Some((id, name))
+        })
+        .collect::<Option<IndexMap<_, _>>>()
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| ScrapeError::new("available languages"))
     }
 
     fn extract_submissions(&self) -> anyhow::Result<(Vec<SubmissionSummary>, u32)> {
@@ -1970,3 +2494,619 @@ impl Html {
         .with_context(|| "Could not parse the submissions page")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testsuite::{BatchTestSuite, Match, TestSuite},
+        web::{
+            atcoder::{
+                get_retrying_login, is_task, language_id_mismatch, pair_samples_with_tasks,
+                parse_submission_status, post_submission, AtcoderStatementLanguage, ContestStatus,
+                HtmlExt as _, SubmissionStatus, TaskSamples, Verdict,
+            },
+            testing::{serve_once, NoShell},
+            CaseConverted, ContestNotBegun, CookieStorage, RetrieveLanguagesOutcome,
+            RetrieveTestCasesOutcomeProblemContest, Session, SessionMut as _, Shell, TlsConfig,
+        },
+    };
+    use chrono::Utc;
+    use indexmap::indexmap;
+    use pretty_assertions::assert_eq;
+    use reqwest::header::HeaderMap;
+    use scraper::Html;
+    use std::{collections::HashMap, fmt, io, time::Duration};
+
+    #[test]
+    fn extract_langs_and_language_id_mismatch() {
+        let html = Html::parse_document(
+            r#"<html>
+<body>
+<form>
+<select class="form-control" name="data.LanguageId">
+<option></option>
+<option value="4003">C++ (GCC 9.2.1)</option>
+<option value="4004">C++ (Clang 10.0.0)</option>
+<option value="4050">Rust (1.42.0)</option>
+</select>
+</form>
+</body>
+</html>
+"#,
+        );
+
+        let names_by_id = html.extract_langs().unwrap();
+
+        assert_eq!(
+            indexmap!(
+                "4003".to_owned() => "C++ (GCC 9.2.1)".to_owned(),
+                "4004".to_owned() => "C++ (Clang 10.0.0)".to_owned(),
+                "4050".to_owned() => "Rust (1.42.0)".to_owned(),
+            ),
+            names_by_id,
+        );
+
+        assert_eq!(
+            None,
+            language_id_mismatch(&names_by_id, "4050", Some("rust")),
+        );
+
+        assert_eq!(
+            Some(
+                "`4051` is not in the submission form. Submitting it anyway. Did you mean \
+                 `4050` (Rust (1.42.0))?"
+            ),
+            language_id_mismatch(&names_by_id, "4051", Some("rust")).as_deref(),
+        );
+
+        // Not `4050`, whose ID is the closest.
+        assert_eq!(
+            Some(
+                "`4051` is not in the submission form. Submitting it anyway. Did you mean \
+                 `4003` (C++ (GCC 9.2.1))?"
+            ),
+            language_id_mismatch(&names_by_id, "4051", Some("cpp")).as_deref(),
+        );
+
+        assert_eq!(
+            Some("`4051` is not in the submission form. Submitting it anyway"),
+            language_id_mismatch(&names_by_id, "4051", None).as_deref(),
+        );
+    }
+
+    #[test]
+    fn print_extracted_langs() {
+        let html = Html::parse_document(
+            r#"<html>
+<body>
+<form>
+<select class="form-control" name="data.LanguageId">
+<option></option>
+<option value="4003">C++ (GCC 9.2.1)</option>
+<option value="4050">Rust (1.42.0)</option>
+</select>
+</form>
+</body>
+</html>
+"#,
+        );
+
+        let outcome = RetrieveLanguagesOutcome {
+            names_by_id: html.extract_langs().unwrap(),
+        };
+
+        assert_eq!(
+            r#"{"names_by_id":{"4003":"C++ (GCC 9.2.1)","4050":"Rust (1.42.0)"}}"#,
+            outcome.to_json(),
+        );
+
+        assert_eq!(
+            "\
+┌──────┬─────────────────┐
+│ ID   │ Name            │
+├──────┼─────────────────┤
+│ 4003 │ C++ (GCC 9.2.1) │
+├──────┼─────────────────┤
+│ 4050 │ Rust (1.42.0)   │
+└──────┴─────────────────┘
+",
+            outcome.to_table().to_string(),
+        );
+    }
+
+    #[test]
+    fn missing_csrf_token() {
+        let html = Html::parse_document(
+            r#"<html>
+<head><title>Login - AtCoder</title></head>
+<body><form action="" method="POST"></form></body>
+</html>
+"#,
+        );
+
+        let err = html
+            .extract_csrf_token()
+            .unwrap_err()
+            .with_url(&"https://atcoder.jp/login".parse().unwrap());
+
+        assert_eq!("CSRF token", err.what());
+        assert_eq!(
+            "Could not extract the CSRF token from https://atcoder.jp/login",
+            err.to_string(),
+        );
+        assert!(err.to_string().to_lowercase().contains("csrf"));
+    }
+
+    #[test]
+    fn parse_submission_status_judging() {
+        let json = r#"{"Result":{"19123456":{"Html":"<td colspan='3' class='waiting-judge text-center'><span class='label label-default' title='Judging'>3/12</span></td>","Score":"0"}},"Interval":1000}"#;
+
+        assert_eq!(
+            SubmissionStatus::Judging {
+                verdict: "3/12".to_owned(),
+                interval: Duration::from_secs(1),
+            },
+            parse_submission_status(json, "19123456").unwrap(),
+        );
+    }
+
+    #[test]
+    fn parse_submission_status_accepted() {
+        let json = r#"{"Result":{"19123456":{"Html":"<td class='text-center'><span class='label label-success' title='Accepted'>AC</span></td><td class='text-right'>5 ms</td><td class='text-right'>3580 KB</td>","Score":"100"}}}"#;
+
+        assert_eq!(
+            SubmissionStatus::Finished {
+                verdict: Verdict::Ac,
+                exec_time: "5 ms".to_owned(),
+                memory: "3580 KB".to_owned(),
+            },
+            parse_submission_status(json, "19123456").unwrap(),
+        );
+    }
+
+    #[test]
+    fn parse_submission_status_wrong_answer() {
+        let json = r#"{"Result":{"19123456":{"Html":"<td class='text-center'><span class='label label-warning' title='Wrong Answer'>WA</span></td><td class='text-right'>12 ms</td><td class='text-right'>3612 KB</td>","Score":"0"}}}"#;
+
+        assert_eq!(
+            SubmissionStatus::Finished {
+                verdict: Verdict::Wa,
+                exec_time: "12 ms".to_owned(),
+                memory: "3612 KB".to_owned(),
+            },
+            parse_submission_status(json, "19123456").unwrap(),
+        );
+    }
+
+    #[test]
+    fn extract_samples_warns_on_missing_samples() {
+        let html = Html::parse_document(
+            r#"<html>
+<body>
+<div id="main-container">
+<div class="row">
+<div class="col-sm-12">
+<span class="h2">A - Foo</span>
+<p>Time Limit: 2 sec / Memory Limit: 1024 MB</p>
+<div id="task-statement">
+<span class="lang"><span class="lang-ja">
+<div class="part"><section><h3>入力例 1</h3><pre>1
+</pre></section></div>
+<div class="part"><section><h3>出力例 1</h3><pre>2
+</pre></section></div>
+<div class="part"><section><h3>入力例 2</h3><pre>2
+</pre></section></div>
+<div class="part"><section><h3>出力例 2</h3><pre>4
+</pre></section></div>
+<div class="part"><section><h3>入力例 3</h3></section></div>
+<div class="part"><section><h3>出力例 3</h3></section></div>
+</span></span>
+</div>
+</div>
+</div>
+</div>
+</body>
+</html>
+"#,
+        );
+
+        let samples = html.extract_samples(AtcoderStatementLanguage::Ja);
+        assert_eq!(1, samples.len());
+        let samples = samples.into_iter().next().unwrap().unwrap();
+
+        assert_eq!("A", samples.index);
+        assert!(matches!(
+            samples.test_suite.unwrap(),
+            TestSuite::Batch(BatchTestSuite { cases, .. }) if cases.len() == 2
+        ));
+        assert_eq!(
+            ["A: The statement mentions 3 sample(s), but only 2 were extracted"],
+            *samples.warnings,
+        );
+
+        let samples = html.extract_samples(AtcoderStatementLanguage::En);
+        let samples = samples.into_iter().next().unwrap().unwrap();
+        assert_eq!(
+            [
+                "A: The statement has no samples in English. Extracted the ones in Japanese",
+                "A: The statement mentions 3 sample(s), but only 2 were extracted",
+            ],
+            *samples.warnings,
+        );
+    }
+
+    #[test]
+    fn extract_samples_not_text() {
+        let html = Html::parse_document(
+            r#"<html>
+<body>
+<div id="main-container">
+<div class="row">
+<div class="col-sm-12">
+<span class="h2">A - Foo</span>
+<p>Time Limit: 2 sec / Memory Limit: 1024 MB</p>
+<div id="task-statement">
+<span class="lang"><span class="lang-ja">
+<div class="part"><section><h3>入力例 1</h3><img src="sample1-in.png"></section></div>
+<div class="part"><section><h3>出力例 1</h3><img src="sample1-out.png"></section></div>
+</span></span>
+</div>
+</div>
+</div>
+</div>
+</body>
+</html>
+"#,
+        );
+
+        let samples = html.extract_samples(AtcoderStatementLanguage::Ja);
+        assert_eq!(1, samples.len());
+        let samples = samples.into_iter().next().unwrap().unwrap();
+
+        assert!(matches!(
+            samples.test_suite.unwrap(),
+            TestSuite::Batch(BatchTestSuite { description: Some(_), cases, .. })
+                if cases.is_empty()
+        ));
+        assert_eq!(
+            [
+                "A: The statement has samples, but none of them are text (e.g. images). Add the \
+              test cases by hand"
+            ],
+            *samples.warnings,
+        );
+    }
+
+    #[test]
+    fn extract_samples_in_english() {
+        let html = Html::parse_document(
+            r#"<html>
+<body>
+<div id="main-container">
+<div class="row">
+<div class="col-sm-12">
+<span class="h2">A - Foo</span>
+<p>Time Limit: 2 sec / Memory Limit: 1024 MB</p>
+<div id="task-statement">
+<span class="lang">
+<span class="lang-ja">
+<div class="part"><section><h3>入力例 1</h3><pre>1
+</pre></section></div>
+<div class="part"><section><h3>出力例 1</h3><pre>2
+</pre></section></div>
+</span>
+<span class="lang-en">
+<div class="part"><section><h3>Sample Input 1</h3><pre>10
+</pre></section></div>
+<div class="part"><section><h3>Sample Output 1</h3><pre>20
+</pre></section></div>
+</span>
+</span>
+</div>
+</div>
+</div>
+</div>
+</body>
+</html>
+"#,
+        );
+
+        let first_input = |language| {
+            let samples = html.extract_samples(language);
+            match samples
+                .into_iter()
+                .next()
+                .unwrap()
+                .unwrap()
+                .test_suite
+                .unwrap()
+            {
+                TestSuite::Batch(BatchTestSuite { cases, .. }) => cases[0].r#in.clone(),
+                _ => panic!("should be batch"),
+            }
+        };
+
+        assert_eq!("1\n", &*first_input(AtcoderStatementLanguage::Ja));
+        assert_eq!("10\n", &*first_input(AtcoderStatementLanguage::En));
+
+        let statement = |language| {
+            let samples = html.extract_samples(language);
+            samples
+                .into_iter()
+                .next()
+                .unwrap()
+                .unwrap()
+                .statement
+                .unwrap()
+        };
+
+        assert_eq!(
+            "### 入力例 1\n\n```\n1\n```\n\n### 出力例 1\n\n```\n2\n```\n",
+            statement(AtcoderStatementLanguage::Ja),
+        );
+        assert_eq!(
+            "### Sample Input 1\n\n```\n10\n```\n\n### Sample Output 1\n\n```\n20\n```\n",
+            statement(AtcoderStatementLanguage::En),
+        );
+    }
+
+    #[test]
+    fn is_task_by_index_or_screen_name() {
+        let html = Html::parse_document(
+            r#"<div id="main-container"><div class="row"><div class="col-sm-12">
+<div class="panel"><table class="table"><tbody>
+<tr><td class="text-center no-break"><a href="/contests/abc300/tasks/abc300_a">A</a></td></tr>
+<tr><td class="text-center no-break"><a href="/contests/abc300/tasks/abc300_b">B</a></td></tr>
+</tbody></table></div>
+</div></div></div>"#,
+        );
+
+        let tasks = html.extract_task_indexes_and_urls().unwrap();
+
+        let find = |problem| {
+            tasks
+                .iter()
+                .find(|(index, url)| is_task(index, url, problem))
+                .map(|(_, url)| url.as_str())
+        };
+
+        let url = Some("https://atcoder.jp/contests/abc300/tasks/abc300_a");
+        assert_eq!(url, find("a"));
+        assert_eq!(url, find("A"));
+        assert_eq!(url, find("abc300_a"));
+        assert_eq!(None, find("abc300_c"));
+        assert_eq!(None, find("abc300"));
+    }
+
+    #[test]
+    fn pair_samples_with_tasks_strict() {
+        fn test_suites() -> Vec<anyhow::Result<TaskSamples>> {
+            vec![Ok(TaskSamples {
+                index: "A".to_owned(),
+                display_name: "Foo".to_owned(),
+                test_suite: Ok(TestSuite::Batch(BatchTestSuite {
+                    description: None,
+                    editorial: None,
+                    timelimit: None,
+                    r#match: Match::Lines,
+                    reference: None,
+                    generator: None,
+                    input_filter: None,
+                    cases: vec![],
+                    cases_from: None,
+                    exclude: vec![],
+                    extend: vec![],
+                })),
+                warnings: vec![
+                    "A: The statement mentions 3 sample(s), but only 2 were extracted".to_owned(),
+                ],
+                statement: None,
+            })]
+        }
+
+        let contest = &RetrieveTestCasesOutcomeProblemContest {
+            id: "practice".to_owned(),
+            display_name: "Practice Contest".to_owned(),
+            url: "https://atcoder.jp/contests/practice".parse().unwrap(),
+            submissions_url: "https://atcoder.jp/contests/practice/submissions/me"
+                .parse()
+                .unwrap(),
+        };
+
+        let indexes_and_urls = || {
+            indexmap!(
+                "A".to_owned() => "https://atcoder.jp/contests/practice/tasks/practice_1".parse().unwrap(),
+                "B".to_owned() => "https://atcoder.jp/contests/practice/tasks/practice_2".parse().unwrap(),
+            )
+        };
+
+        let mut shell = RecordingShell(vec![]);
+        let problems = pair_samples_with_tasks(
+            &mut shell,
+            false,
+            contest,
+            indexes_and_urls(),
+            test_suites(),
+        )
+        .unwrap();
+        assert_eq!(1, problems.len());
+        assert_eq!(
+            [
+                "Found 2 task(s) in `tasks`, 1 task(s) in `tasks_print`",
+                "A: The statement mentions 3 sample(s), but only 2 were extracted",
+                "Could not find `B` in `tasks_print`",
+            ],
+            &*shell.0,
+        );
+
+        let mut shell = RecordingShell(vec![]);
+        let err =
+            pair_samples_with_tasks(&mut shell, true, contest, indexes_and_urls(), test_suites())
+                .unwrap_err();
+        assert_eq!(
+            "Found 2 task(s) in `tasks`, 1 task(s) in `tasks_print`",
+            err.to_string(),
+        );
+        assert!(shell.0.is_empty());
+    }
+
+    #[test]
+    fn post_submission_with_language_id_override() {
+        let (base, server) = serve_once(&[
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Length: 175\r\n\
+              Connection: close\r\n\
+              \r\n\
+              <html><body><form><input type=\"hidden\" name=\"csrf_token\" value=\"token\">\
+              <select name=\"data.LanguageId\"><option value=\"4050\">Rust (1.42.0)</option>\
+              </select></form></body></html>",
+            b"HTTP/1.1 302 Found\r\n\
+              Location: /contests/practice/submissions/me\r\n\
+              Content-Length: 0\r\n\
+              Connection: close\r\n\
+              \r\n",
+        ]);
+
+        let mut sess = Session::new(
+            Some(Duration::from_secs(10)),
+            HeaderMap::new(),
+            TlsConfig::default(),
+            None,
+            RecordingShell(vec![]),
+        )
+        .unwrap();
+
+        let res = post_submission(
+            &mut sess,
+            base.join("contests/practice/tasks/practice_1").unwrap(),
+            base.join("contests/practice/submit").unwrap(),
+            "practice_1",
+            "5054",
+            Some("rust"),
+            "fn main() {}",
+            false,
+            |_| unreachable!(),
+        )
+        .unwrap();
+        assert_eq!(302, res.status());
+
+        assert_eq!(
+            [
+                "`5054` is not in the submission form. Submitting it anyway. Did you mean `4050` \
+                 (Rust (1.42.0))?",
+            ],
+            *sess.shell().0,
+        );
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].starts_with("POST /contests/practice/submit "));
+        let form = requests[1].split("\r\n\r\n").nth(1).unwrap();
+        let form = url::form_urlencoded::parse(form.as_ref()).collect::<HashMap<_, _>>();
+        assert_eq!("5054", form["data.LanguageId"]);
+        assert_eq!("practice_1", form["data.TaskScreenName"]);
+        assert_eq!("fn main() {}", form["sourceCode"]);
+        assert_eq!("token", form["csrf_token"]);
+    }
+
+    struct RecordingShell(Vec<String>);
+
+    impl Shell for RecordingShell {
+        fn warn<T: fmt::Display>(&mut self, message: T) -> io::Result<()> {
+            self.0.push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn contest_not_begun() {
+        let html = Html::parse_document(
+            r#"<html>
+<head><title>AtCoder Beginner Contest 999 - AtCoder</title></head>
+<body>
+<small class="contest-duration">
+  Contest Duration:
+  <a href="http://www.timeanddate.com/worldclock/fixedtime.html?iso=20991231T2100&p1=248" target="blank"><time class="fixtime fixtime-full">2099-12-31 21:00:00+0900</time></a>
+  ~
+  <a href="http://www.timeanddate.com/worldclock/fixedtime.html?iso=20991231T2240&p1=248" target="blank"><time class="fixtime fixtime-full">2099-12-31 22:40:00+0900</time></a>
+  (local time)
+  (100 minutes)
+</small>
+</body>
+</html>
+"#,
+        );
+
+        let duration = html.extract_contest_duration().unwrap();
+        let err = ContestStatus::now(duration, &CaseConverted::new("abc999"))
+            .raise_if_not_begun()
+            .unwrap_err();
+
+        let err = err.downcast_ref::<ContestNotBegun>().unwrap();
+        assert_eq!("abc999", err.contest());
+        assert_eq!(
+            "2099-12-31T12:00:00+00:00",
+            err.begins_at().with_timezone(&Utc).to_rfc3339(),
+        );
+    }
+
+    #[test]
+    fn get_retrying_login_after_login_redirect() {
+        let (base, server) = serve_once(&[
+            b"HTTP/1.1 302 Found\r\n\
+              Location: /login?continue=%2Fcontests%2Fpractice%2Ftasks_print\r\n\
+              Content-Length: 0\r\n\
+              Connection: close\r\n\
+              \r\n",
+            b"HTTP/1.1 200 OK\r\n\
+              Set-Cookie: REVEL_SESSION=foo\r\n\
+              Content-Length: 0\r\n\
+              Connection: close\r\n\
+              \r\n",
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Length: 4\r\n\
+              Connection: close\r\n\
+              \r\n\
+              page",
+        ]);
+
+        let cookie_storage = CookieStorage {
+            cookie_store: Default::default(),
+            on_update: Box::new(|_| Ok(())),
+        };
+
+        let mut sess = Session::new(
+            Some(Duration::from_secs(10)),
+            HeaderMap::new(),
+            TlsConfig::default(),
+            Some(cookie_storage),
+            NoShell,
+        )
+        .unwrap();
+
+        let url = base.join("contests/practice/tasks_print").unwrap();
+
+        let mut logins = 0;
+
+        let res = get_retrying_login(&mut sess, url, &[200], (), true, |sess| {
+            logins += 1;
+            let url = base.join("login").unwrap();
+            sess.get(url).send()?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(200, res.status());
+        assert_eq!("page", res.text().unwrap());
+        assert_eq!(1, logins);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /contests/practice/tasks_print "));
+        assert!(requests[1].starts_with("GET /login "));
+        assert!(requests[2].starts_with("GET /contests/practice/tasks_print "));
+        assert!(
+            requests[2].contains("\r\ncookie: REVEL_SESSION=foo\r\n"),
+            "{}",
+            requests[2],
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use crate::{
     testsuite::{BatchTestSuite, Match, PartialBatchTestCase, TestSuite},
     web::{
-        codeforces::api::SessionMutExt as _, CookieStorage, Exec, Login, LoginOutcome, Participate,
-        ParticipateOutcome, Platform, ProblemInContest, ProblemsInContest, ResponseExt as _,
-        RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveTestCases, RetrieveTestCasesOutcome,
-        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest, Session,
-        SessionMut, Shell, Submit, SubmitOutcome,
+        codeforces::api::SessionMutExt as _, CookieStorage, ElementRefExt as _, Exec, HtmlExt as _,
+        Login, LoginOutcome, Participate, ParticipateOutcome, Platform, ProblemInContest,
+        ProblemsInContest, ResponseExt as _, RetrieveLanguages, RetrieveLanguagesOutcome,
+        RetrieveProblemIndexes, RetrieveTestCases, RetrieveTestCasesOutcome,
+        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest, ScrapeError,
+        ServiceError, Session, SessionMut, Shell, Submit, SubmitOutcome,
     },
 };
 use anyhow::{bail, Context as _};
@@ -14,7 +15,7 @@ use indexmap::{indexmap, IndexMap};
 use itertools::Itertools as _;
 use maplit::btreemap;
 use once_cell::sync::Lazy;
-use scraper::{ElementRef, Html, Node, Selector};
+use scraper::{Html, Node, Selector};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     convert::Infallible,
@@ -73,10 +74,12 @@ impl<S: Shell> Exec<Login<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
         let (outcome, _) = login(sess, username_and_password)?;
         Ok(outcome)
     }
@@ -94,11 +97,13 @@ impl<S: Shell> Exec<Participate<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
         let contest = parse_contest_id(&contest)?;
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
         let (outcome, _, _) = participate(sess, username_and_password, contest)?;
         Ok(outcome)
     }
@@ -116,12 +121,14 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
         let contest = parse_contest_id(&contest)?;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         participate(&mut sess, username_and_password, contest)?;
 
@@ -148,8 +155,14 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
                     mut username_and_password,
                 },
             full: _,
+            strict: _,
+            statement,
+            statement_language: _,
+            editorial,
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
@@ -167,7 +180,7 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
             }
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
         let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
 
         for (contest, problems) in targets {
@@ -202,12 +215,20 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
                             }
                         }
 
-                        let test_suite = sess
+                        let html = sess
                             .get(url.clone())
                             .colorize_status_code(&[200], (), ..)
                             .send()?
-                            .html()?
-                            .extract_test_cases()?;
+                            .html()?;
+
+                        let mut test_suite = html.extract_test_cases()?;
+
+                        if let Some(editorial) = editorial
+                            .then(|| html.extract_editorial_url(&url))
+                            .flatten()
+                        {
+                            test_suite.set_editorial(editorial);
+                        }
 
                         Ok(Some(RetrieveTestCasesOutcomeProblem {
                             contest: Some(contest.clone()),
@@ -217,6 +238,7 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
                             display_name,
                             test_suite,
                             text_files: indexmap!(),
+                            statement: statement.then(|| html.extract_statement()).flatten(),
                         }))
                     })
                     .flat_map(Result::transpose)
@@ -234,6 +256,43 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
     }
 }
 
+impl<S: Shell> Exec<RetrieveProblemIndexes<Self, S>> for Codeforces<'_> {
+    type Output = Vec<String>;
+
+    fn exec(args: RetrieveProblemIndexes<Self, S>) -> anyhow::Result<Vec<String>> {
+        let RetrieveProblemIndexes {
+            contest,
+            credentials:
+                CodeforcesRetrieveSampleTestCasesCredentials {
+                    mut username_and_password,
+                },
+            cookie_storage,
+            timeout,
+            headers,
+            tls,
+            shell,
+        } = args;
+
+        let contest = parse_contest_id(&contest)?;
+
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
+
+        participate(&mut sess, &mut username_and_password, contest)?;
+
+        let indexes = sess
+            .get(url!("/contest/{}", contest))
+            .colorize_status_code(&[200], (), ..)
+            .send()?
+            .ensure_status(&[200])?
+            .html()?
+            .extract_problems()?
+            .into_iter()
+            .map(|(index, _, _)| index)
+            .collect();
+        Ok(indexes)
+    }
+}
+
 impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
     type Output = SubmitOutcome;
 
@@ -247,10 +306,14 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
                     api_secret,
                 },
             language_id,
+            language_name: _,
             code,
             watch_submission,
+            watch_timeout: _,
             cookie_storage,
             timeout,
+            headers,
+            tls,
             mut shell,
         } = args;
 
@@ -263,7 +326,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
             ProblemInContest::Url { url } => parse_problem_url(&url)?,
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(timeout, headers, tls, Some(cookie_storage), shell)?;
 
         let (_, _, handle) = participate(&mut sess, username_and_password, contest_id)?;
 
@@ -300,7 +363,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
             .ensure_status(&[200, 302])?;
 
         if res.status() == 200 {
-            bail!("Submission rejected");
+            Err(ServiceError::new("Submission rejected").into())
         } else {
             let submissions_url = res.location_url()?;
 
@@ -317,6 +380,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
                 problem_screen_name: None,
                 submission_url,
                 submissions_url,
+                verdict: None,
             })
         }
     }
@@ -491,7 +555,7 @@ impl Html {
         Ok(values)
     }
 
-    fn extract_available_langs(&self) -> anyhow::Result<IndexMap<String, String>> {
+    fn extract_available_langs(&self) -> Result<IndexMap<String, String>, ScrapeError> {
         self.select(static_selector!(
             "form.submit-form > table > tbody > tr > td"
         ))
@@ -500,7 +564,7 @@ impl Html {
                 .next()
                 .is_some()
         })
-        .with_context(|| "Could not find `select[name=\"programTypeId\"]`")?
+        .ok_or_else(|| ScrapeError::new("`select[name=\"programTypeId\"]`"))?
         .select(static_selector!("option"))
         .map(|option| {
             let id = option.value().attr("value")?.to_owned();
@@ -509,10 +573,10 @@ impl Html {
         })
         .collect::<Option<IndexMap<_, _>>>()
         .filter(|ls| !ls.is_empty())
-        .with_context(|| "Could not extract the available languages")
+        .ok_or_else(|| ScrapeError::new("available languages"))
     }
 
-    fn extract_problems(&self) -> anyhow::Result<Vec<(String, String, Url)>> {
+    fn extract_problems(&self) -> Result<Vec<(String, String, Url)>, ScrapeError> {
         self.select(static_selector!("table.problems > tbody > tr"))
             .skip(1)
             .map(|tr| {
@@ -538,7 +602,13 @@ impl Html {
             })
             .collect::<Option<Vec<_>>>()
             .filter(|ss| !ss.is_empty())
-            .with_context(|| "Could not extract problem names")
+            .ok_or_else(|| ScrapeError::new("problem names"))
+    }
+
+    fn extract_statement(&self) -> Option<String> {
+        self.select(static_selector!("#pageContent div.problem-statement"))
+            .next()
+            .map(|r| r.to_markdown())
     }
 
     fn extract_test_cases(&self) -> anyhow::Result<TestSuite> {
@@ -589,12 +659,12 @@ impl Html {
 
         let ins = sample_test
             .select(static_selector!("div.input > pre"))
-            .map(|p| p.fold_text_and_br())
+            .map(|p| p.sample_text())
             .collect::<Vec<_>>();
 
         let outs = sample_test
             .select(static_selector!("div.output > pre"))
-            .map(|p| p.fold_text_and_br())
+            .map(|p| p.sample_text())
             .collect::<Vec<_>>();
 
         if ins.is_empty() || ins.len() != outs.len() {
@@ -607,47 +677,42 @@ impl Html {
             .enumerate()
             .map(|(i, (r#in, out))| PartialBatchTestCase {
                 name: Some(format!("example{}", i + 1)),
+                note: None,
                 r#in: r#in.into(),
                 out: Some(out.into()),
                 r#match: None,
                 timelimit: None,
+                stderr: None,
+                stderr_match: None,
             })
             .collect();
 
-        return Ok(TestSuite::Batch(BatchTestSuite {
+        Ok(TestSuite::Batch(BatchTestSuite {
+            description: None,
+            editorial: None,
             timelimit: Some(timelimit),
             r#match,
+            reference: None,
+            generator: None,
+            input_filter: None,
             cases,
+            cases_from: None,
+            exclude: vec![],
             extend: vec![],
-        }));
-
-        #[ext]
-        impl ElementRef<'_> {
-            fn fold_text_and_br(&self) -> String {
-                self.children().fold("".to_owned(), |mut ret, node| {
-                    match node.value() {
-                        Node::Text(t) => ret += t,
-                        Node::Element(e) if e.name() == "br" => ret.push('\n'),
-                        _ => {}
-                    }
-                    ret
-                })
-            }
-        }
+        }))
     }
 
-    fn extract_meta_x_csrf_token(&self) -> anyhow::Result<String> {
+    fn extract_meta_x_csrf_token(&self) -> Result<String, ScrapeError> {
         self.select(static_selector!("meta[name=\"X-Csrf-Token\"]"))
             .next()
             .and_then(|r| r.value().attr("content").map(ToOwned::to_owned))
-            .with_context(|| "Could not extract the `X-Csrf-Token`")
+            .ok_or_else(|| ScrapeError::new("`X-Csrf-Token`"))
     }
 }
 
 /// <https://codeforces.com/apiHelp>
 mod api {
-    use crate::web::SessionMut;
-    use anyhow::anyhow;
+    use crate::web::{ServiceError, SessionMut};
     use rand::Rng as _;
     use serde::{
         de::{DeserializeOwned, Deserializer, Error as _},
@@ -850,7 +915,7 @@ mod api {
             Ok(ok)
         } else {
             let ApiErr(msg) = res.json()?;
-            Err(anyhow!("API error: {:?}", msg))
+            Err(ServiceError::new(format_args!("API error: {:?}", msg)).into())
         };
 
         struct ApiOk<T: DeserializeOwned>(T);
@@ -55,6 +55,8 @@ macro_rules! url {
 
 mod atcoder;
 mod codeforces;
+#[cfg(test)]
+mod testing;
 mod yukicoder;
 
 pub use crate::web::{
@@ -64,8 +66,9 @@ pub use crate::web::{
         AtcoderRetrieveFullTestCasesCredentials, AtcoderRetrieveLanguagesCredentials,
         AtcoderRetrieveLanguagesTarget, AtcoderRetrieveSampleTestCasesCredentials,
         AtcoderRetrieveSubmissionSummariesCredentials, AtcoderRetrieveSubmissionSummariesOutcome,
-        AtcoderRetrieveSubmissionSummariesTarget, AtcoderSubmitCredentials,
-        AtcoderWatchSubmissionsCredentials, AtcoderWatchSubmissionsTarget,
+        AtcoderRetrieveSubmissionSummariesTarget, AtcoderStatementLanguage,
+        AtcoderSubmitCredentials, AtcoderWatchSubmissionsCredentials,
+        AtcoderWatchSubmissionsTarget,
     },
     codeforces::{
         contest_id_from_url as codeforces_contest_id, Codeforces, CodeforcesLoginCredentials,
@@ -81,6 +84,7 @@ pub use crate::web::{
 
 use crate::testsuite::TestSuite;
 use anyhow::{anyhow, bail, Context as _};
+use chrono::{DateTime, Local};
 use cookie_store::CookieStore;
 use derivative::Derivative;
 use derive_more::{Display, From};
@@ -90,13 +94,12 @@ use futures_util::StreamExt as _;
 use indexmap::IndexMap;
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use itertools::Itertools as _;
-use prettytable::{
-    cell,
-    format::{FormatBuilder, LinePosition, LineSeparator},
-    row, Table,
+use reqwest::{
+    header::{self, HeaderMap},
+    redirect::Policy,
+    Method, StatusCode,
 };
-use reqwest::{header, redirect::Policy, Method, StatusCode};
-use scraper::Html;
+use scraper::{ElementRef, Html, Node};
 use serde::{Deserialize, Serialize, Serializer};
 use std::{
     any,
@@ -108,6 +111,7 @@ use std::{
     fs::File,
     hash::Hash,
     io::{self, BufReader, Seek as _, SeekFrom},
+    iter,
     marker::PhantomData,
     ops::{Deref, RangeFull, RangeInclusive},
     path::{Path, PathBuf},
@@ -192,6 +196,19 @@ impl PlatformKind {
     }
 }
 
+/// TLS settings of the HTTP clients.
+///
+/// The default verifies the certificates with the built-in root certificates only.
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// PEM-encoded certificates to trust in addition to the built-in ones.
+    pub root_certificates: Vec<Vec<u8>>,
+    /// Accepts invalid certificates, including expired ones and ones for other hosts.
+    ///
+    /// This is dangerous.
+    pub danger_accept_invalid_certs: bool,
+}
+
 pub trait Exec<A>: Platform {
     type Output;
     fn exec(args: A) -> anyhow::Result<Self::Output>;
@@ -201,6 +218,8 @@ pub struct Login<P: Platform, S: Shell> {
     pub credentials: P::LoginCredentials,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -221,6 +240,8 @@ pub struct Participate<P: Platform, S: Shell> {
     pub credentials: P::ParticipateCredentials,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -251,6 +272,8 @@ pub struct RetrieveLanguages<P: Platform, S: Shell> {
     pub credentials: P::RetrieveLanguagesCredentials,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -265,26 +288,49 @@ impl RetrieveLanguagesOutcome {
         serde_json::to_string(self).expect("should not fail")
     }
 
-    pub fn to_table(&self) -> impl fmt::Display {
-        let mut table = Table::new();
+    pub fn to_table(&self) -> impl fmt::Display + '_ {
+        return Table(&self.names_by_id);
 
-        *table.get_format() = FormatBuilder::new()
-            .padding(1, 1)
-            .column_separator('│')
-            .borders('│')
-            .separator(LinePosition::Top, LineSeparator::new('─', '┬', '┌', '┐'))
-            .separator(LinePosition::Title, LineSeparator::new('─', '┼', '├', '┤'))
-            .separator(LinePosition::Intern, LineSeparator::new('─', '┼', '├', '┤'))
-            .separator(LinePosition::Bottom, LineSeparator::new('─', '┴', '└', '┘'))
-            .build();
+        struct Table<'a>(&'a IndexMap<String, String>);
 
-        table.set_titles(row!["ID", "Name"]);
+        impl fmt::Display for Table<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let rows = iter::once(("ID", "Name"))
+                    .chain(self.0.iter().map(|(id, name)| (&**id, &**name)))
+                    .collect::<Vec<_>>();
 
-        for (id, name) in &self.names_by_id {
-            table.add_row(row![id, name]);
-        }
+                let id_width = rows.iter().map(|(id, _)| id.width()).max().unwrap_or(0);
+                let name_width = rows.iter().map(|(_, name)| name.width()).max().unwrap_or(0);
+
+                let separator = |f: &mut fmt::Formatter<'_>, left, middle, right| {
+                    writeln!(
+                        f,
+                        "{}{}{}{}{}",
+                        left,
+                        "─".repeat(id_width + 2),
+                        middle,
+                        "─".repeat(name_width + 2),
+                        right,
+                    )
+                };
 
-        table
+                separator(f, '┌', '┬', '┐')?;
+                for (i, (id, name)) in rows.iter().enumerate() {
+                    if i > 0 {
+                        separator(f, '├', '┼', '┤')?;
+                    }
+                    writeln!(
+                        f,
+                        "│ {}{} │ {}{} │",
+                        id,
+                        " ".repeat(id_width - id.width()),
+                        name,
+                        " ".repeat(name_width - name.width()),
+                    )?;
+                }
+                separator(f, '└', '┴', '┘')
+            }
+        }
     }
 }
 
@@ -292,13 +338,42 @@ pub struct RetrieveTestCases<P: Platform, S: Shell> {
     pub targets: P::RetrieveTestCasesTargets,
     pub credentials: P::RetrieveTestCasesCredentials,
     pub full: Option<RetrieveFullTestCases<P>>,
+    /// Fails instead of warning when a page is only partially extracted, or when the samples are
+    /// not in the requested language. Only AtCoder supports this, and the others ignore it.
+    pub strict: bool,
+    /// Also retrieves the problem statements as Markdown.
+    pub statement: bool,
+    /// Language of the statements in which the samples are looked for first, and in which the
+    /// statements are retrieved. Only AtCoder has two, and the others ignore it.
+    pub statement_language: AtcoderStatementLanguage,
+    /// Also retrieves the links to the editorials into [`BatchTestSuite::editorial`] and
+    /// [`InteractiveTestSuite::editorial`]. The editorials themselves are not retrieved.
+    ///
+    /// [`BatchTestSuite::editorial`]: crate::testsuite::BatchTestSuite::editorial
+    /// [`InteractiveTestSuite::editorial`]: crate::testsuite::InteractiveTestSuite::editorial
+    pub editorial: bool,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
+    pub shell: S,
+}
+
+/// Lists the indexes of the problems in a contest, without retrieving the test cases.
+pub struct RetrieveProblemIndexes<P: Platform, S: Shell> {
+    pub contest: String,
+    pub credentials: P::RetrieveTestCasesCredentials,
+    pub cookie_storage: P::CookieStorage,
+    pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
 pub struct RetrieveFullTestCases<P: Platform> {
     pub credentials: P::RetrieveFullTestCasesCredentials,
+    /// Fails if any of the files is larger than this, in bytes.
+    pub max_download_size: Option<u64>,
 }
 
 #[non_exhaustive]
@@ -317,6 +392,9 @@ pub struct RetrieveTestCasesOutcomeProblem {
     pub display_name: String,
     pub test_suite: TestSuite,
     pub text_files: IndexMap<String, RetrieveTestCasesOutcomeProblemTextFiles>,
+    /// The statement in Markdown. Set if [`RetrieveTestCases::statement`] is `true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
 }
 
 #[non_exhaustive]
@@ -333,6 +411,8 @@ pub struct RetrieveSubmissionSummaries<P: Platform, S: Shell> {
     pub credentials: P::RetrieveSubmissionSummariesCredentials,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -348,6 +428,10 @@ pub struct WatchSubmissions<P: Platform, S: Shell> {
     pub credentials: P::WatchSubmissionsCredentials,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    #[serde(skip)]
+    pub headers: HeaderMap,
+    #[serde(skip)]
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -365,15 +449,117 @@ impl AnsiColored {
     }
 }
 
+/// An error that something could not be extracted from a web page.
+#[derive(Debug)]
+pub struct ScrapeError {
+    what: &'static str,
+    url: Option<Url>,
+}
+
+impl ScrapeError {
+    fn new(what: &'static str) -> Self {
+        Self { what, url: None }
+    }
+
+    fn with_url(self, url: &Url) -> Self {
+        Self {
+            url: Some(url.clone()),
+            ..self
+        }
+    }
+
+    /// What could not be extracted (e.g. "CSRF token").
+    pub fn what(&self) -> &'static str {
+        self.what
+    }
+
+    /// URL of the page, if known.
+    pub fn url(&self) -> Option<&Url> {
+        self.url.as_ref()
+    }
+}
+
+impl fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not extract the {}", self.what)?;
+        if let Some(url) = &self.url {
+            write!(f, " from {}", url)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScrapeError {}
+
+/// An error that the service responded unexpectedly, such as with an unexpected status code, or
+/// rejected the login or the submission.
+#[derive(Debug)]
+pub struct ServiceError(String);
+
+impl ServiceError {
+    fn new(message: impl fmt::Display) -> Self {
+        Self(message.to_string())
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+/// An error that the contest has not begun yet.
+#[derive(Debug)]
+pub struct ContestNotBegun {
+    contest: String,
+    begins_at: DateTime<Local>,
+}
+
+impl ContestNotBegun {
+    fn new(contest: impl Into<String>, begins_at: DateTime<Local>) -> Self {
+        Self {
+            contest: contest.into(),
+            begins_at,
+        }
+    }
+
+    /// ID of the contest.
+    pub fn contest(&self) -> &str {
+        &self.contest
+    }
+
+    /// When the contest begins.
+    pub fn begins_at(&self) -> DateTime<Local> {
+        self.begins_at
+    }
+}
+
+impl fmt::Display for ContestNotBegun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` will begin at {}", self.contest, self.begins_at)
+    }
+}
+
+impl std::error::Error for ContestNotBegun {}
+
 #[derive(Debug)]
 pub struct Submit<P: Platform, S: Shell> {
     pub target: P::SubmitTarget,
     pub credentials: P::SubmitCredentials,
     pub language_id: String,
+    /// Name of the language (e.g. `rust`), which AtCoder uses to suggest the closest language if
+    /// `language_id` is not found.
+    pub language_name: Option<String>,
     pub code: String,
     pub watch_submission: bool,
+    /// Gives up watching the submission after this duration.
+    pub watch_timeout: Option<Duration>,
     pub cookie_storage: P::CookieStorage,
     pub timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub tls: TlsConfig,
     pub shell: S,
 }
 
@@ -383,9 +569,15 @@ pub struct SubmitOutcome {
     pub problem_screen_name: Option<String>,
     pub submission_url: Url,
     pub submissions_url: Url,
+    /// Final verdict of the submission, if it has been watched.
+    pub verdict: Option<String>,
 }
 
 impl SubmitOutcome {
+    pub fn is_accepted(&self) -> bool {
+        self.verdict.as_deref() == Some("AC")
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).expect("should not fail")
     }
@@ -615,21 +807,51 @@ pub enum StatusCodeColor {
 struct Session<S> {
     async_client: reqwest::Client,
     blocking_client: reqwest::blocking::Client,
-    cookie_storage: Option<CookieStorage>,
+    cookie_storage: Option<CookieStorageGuard>,
     shell: S,
 }
 
+/// Retries saving the cookies on `drop` if `on_update` has not succeeded for the last ones.
+struct CookieStorageGuard {
+    inner: CookieStorage,
+    unsaved: bool,
+}
+
+impl Drop for CookieStorageGuard {
+    fn drop(&mut self) {
+        if self.unsaved {
+            let _ = (self.inner.on_update)(&self.inner.cookie_store);
+        }
+    }
+}
+
 impl<S: Shell> Session<S> {
+    /// `headers` are sent with every request. `User-Agent` in it overrides the default one.
     fn new(
         timeout: Option<Duration>,
+        headers: HeaderMap,
+        tls: TlsConfig,
         cookie_storage: Option<CookieStorage>,
         shell: S,
     ) -> anyhow::Result<Self> {
+        let root_certificates = tls
+            .root_certificates
+            .iter()
+            .map(|pem| reqwest::Certificate::from_pem(pem))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| "Could not parse a root certificate")?;
+
         macro_rules! client(($builder:path) => {{
-            let client = $builder()
+            let mut client = $builder()
                 .user_agent(USER_AGENT)
+                .default_headers(headers.clone())
                 .cookie_store(false)
-                .redirect(Policy::none());
+                .redirect(Policy::none())
+                .danger_accept_invalid_certs(tls.danger_accept_invalid_certs);
+
+            for certificate in &root_certificates {
+                client = client.add_root_certificate(certificate.clone());
+            }
 
             if let Some(timeout) = timeout {
                 client.timeout(timeout).build()
@@ -641,6 +863,11 @@ impl<S: Shell> Session<S> {
         let async_client = client!(reqwest::ClientBuilder::new)?;
         let blocking_client = client!(reqwest::blocking::ClientBuilder::new)?;
 
+        let cookie_storage = cookie_storage.map(|inner| CookieStorageGuard {
+            inner,
+            unsaved: false,
+        });
+
         return Ok(Self {
             async_client,
             blocking_client,
@@ -648,7 +875,11 @@ impl<S: Shell> Session<S> {
             shell,
         });
 
-        static USER_AGENT: &str = "snowchains <https://github.com/qryxip/snowchains>";
+        static USER_AGENT: &str = concat!(
+            "snowchains/",
+            env!("CARGO_PKG_VERSION"),
+            " <https://github.com/qryxip/snowchains>",
+        );
 
         trait DummyMethod: Sized {
             fn cookie_store(self, _: bool) -> Self {
@@ -702,7 +933,7 @@ impl<S: Shell> SessionMut for Session<S> {
     fn cookie_store(&self) -> Option<&CookieStore> {
         self.cookie_storage
             .as_ref()
-            .map(|CookieStorage { cookie_store, .. }| cookie_store)
+            .map(|CookieStorageGuard { inner, .. }| &inner.cookie_store)
     }
 
     fn request(&mut self, method: Method, url: Url) -> SessionRequestBuilder<'_, S> {
@@ -816,20 +1047,42 @@ impl<S: Shell> SessionRequestBuilder<'_, S> {
         sess.shell
             .on_response(&res, colorize_status_code(res.status()))?;
 
-        if let Some(CookieStorage {
-            cookie_store,
-            on_update,
+        if let Some(CookieStorageGuard {
+            inner:
+                CookieStorage {
+                    cookie_store,
+                    on_update,
+                },
+            unsaved,
         }) = &mut sess.cookie_storage
         {
+            // Stores every valid cookie before returning an error for an invalid one, so that
+            // a session that has been obtained is not lost.
+            let mut invalid = None;
+
             for set_cookie in res.headers().get_all(header::SET_COOKIE) {
-                let set_cookie = str::from_utf8(set_cookie.as_bytes())
-                    .map_err(|e| anyhow!("{}: {}", e, &url))?;
-                let cookie = cookie_store::Cookie::parse(set_cookie, &url)?.into_owned();
-                cookie_store.insert(cookie, &url)?;
+                let result = (|| -> anyhow::Result<_> {
+                    let set_cookie = str::from_utf8(set_cookie.as_bytes())?;
+                    let cookie = cookie_store::Cookie::parse(set_cookie, &url)?.into_owned();
+                    cookie_store.insert(cookie, &url)?;
+                    Ok(())
+                })();
+
+                match result {
+                    Ok(()) => *unsaved = true,
+                    Err(err) => {
+                        invalid.get_or_insert(err.context(format!("Invalid cookie from {}", url)));
+                    }
+                }
             }
 
-            if res.headers().contains_key(header::SET_COOKIE) {
+            if *unsaved {
                 (on_update)(cookie_store)?;
+                *unsaved = false;
+            }
+
+            if let Some(invalid) = invalid {
+                return Err(invalid);
             }
         }
 
@@ -965,16 +1218,143 @@ where
 
     fn ensure_status(self, statuses: &'static [u16]) -> anyhow::Result<Self> {
         if !statuses.contains(&self.status().as_u16()) {
-            bail!("expected {:?}, got {}", statuses, self.status());
+            return Err(ServiceError::new(format_args!(
+                "expected {:?}, got {}",
+                statuses,
+                self.status(),
+            ))
+            .into());
         }
         Ok(self)
     }
 }
 
+#[ext(HtmlExt)]
+impl Html {
+    /// Finds the link to the editorial, such as `/contests/abc300/tasks/abc300_a/editorial` on
+    /// AtCoder, "Tutorial" in the sidebar on Codeforces, and "解説" on yukicoder.
+    fn extract_editorial_url(&self, base: &Url) -> Option<Url> {
+        self.select(static_selector!("a[href]")).find_map(|a| {
+            let url = base.join(a.value().attr("href")?).ok()?;
+            let text = a.text().collect::<String>();
+            let text = text.trim();
+            let is_editorial = url.path().trim_end_matches('/').ends_with("/editorial")
+                || ["Editorial", "Tutorial", "解説"]
+                    .iter()
+                    .any(|s| text.starts_with(s));
+            is_editorial.then_some(url)
+        })
+    }
+}
+
+#[ext(ElementRefExt)]
+impl ElementRef<'_> {
+    /// Collects the text of a sample such as a `<pre>`.
+    ///
+    /// Inline markup such as `<var>` is unwrapped, `<br>`s become `\n`, and `<script>`s and
+    /// `<style>`s (e.g. `<script type="math/tex">` of MathJax) are dropped.
+    fn sample_text(&self) -> String {
+        let mut ret = "".to_owned();
+        for node in self.children() {
+            match node.value() {
+                Node::Text(t) => ret += t,
+                Node::Element(e) if e.name() == "br" => ret.push('\n'),
+                Node::Element(e) if ["script", "style"].contains(&e.name()) => {}
+                Node::Element(_) => ret += &ElementRef::wrap(node).unwrap().sample_text(),
+                _ => {}
+            }
+        }
+        ret
+    }
+
+    /// Converts a problem statement into Markdown.
+    ///
+    /// Headings, paragraphs, lists, `<pre>`s (as fenced code blocks) and `<code>`s are kept,
+    /// `<script>`s and `<style>`s are dropped, and any other markup is unwrapped. The characters
+    /// in the text which Markdown would take as markup are escaped.
+    fn to_markdown(&self) -> String {
+        let mut blocks = vec![];
+        let mut inline = "".to_owned();
+        markdown_blocks(*self, &mut blocks, &mut inline);
+        flush_inline(&mut blocks, &mut inline);
+        blocks.join("\n\n") + "\n"
+    }
+}
+
+fn markdown_blocks(element: ElementRef<'_>, blocks: &mut Vec<String>, inline: &mut String) {
+    for node in element.children() {
+        match node.value() {
+            Node::Text(t) => {
+                for c in t.chars() {
+                    if !c.is_whitespace() {
+                        let starts_line = inline.trim_end_matches(' ').is_empty()
+                            || inline.trim_end_matches(' ').ends_with('\n');
+                        if r"\`*_[]<>#|".contains(c) || (starts_line && "-+".contains(c)) {
+                            inline.push('\\');
+                        }
+                        inline.push(c);
+                    } else if !(inline.is_empty() || inline.ends_with(char::is_whitespace)) {
+                        inline.push(' ');
+                    }
+                }
+            }
+            Node::Element(e) => {
+                let child = ElementRef::wrap(node).unwrap();
+                match e.name() {
+                    "script" | "style" => {}
+                    "br" => *inline += "\n",
+                    name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                        flush_inline(blocks, inline);
+                        let level = usize::from(name.as_bytes()[1] - b'0');
+                        let mut heading = "".to_owned();
+                        markdown_blocks(child, &mut vec![], &mut heading);
+                        blocks.push(format!("{} {}", "#".repeat(level), heading.trim()));
+                    }
+                    "pre" => {
+                        flush_inline(blocks, inline);
+                        let mut text = child.sample_text();
+                        if !text.ends_with('\n') {
+                            text.push('\n');
+                        }
+                        blocks.push(format!("```\n{}```", text));
+                    }
+                    "code" => *inline += &format!("`{}`", child.sample_text().trim()),
+                    "li" => {
+                        flush_inline(blocks, inline);
+                        let mut item = "".to_owned();
+                        markdown_blocks(child, &mut vec![], &mut item);
+                        blocks.push(format!("- {}", item.trim()));
+                    }
+                    "p" | "div" | "section" | "ul" | "ol" | "table" | "tr" | "blockquote" => {
+                        flush_inline(blocks, inline);
+                        markdown_blocks(child, blocks, inline);
+                        flush_inline(blocks, inline);
+                    }
+                    _ => markdown_blocks(child, blocks, inline),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn flush_inline(blocks: &mut Vec<String>, inline: &mut String) {
+    let paragraph = inline.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+    if !paragraph.trim().is_empty() {
+        blocks.push(paragraph.trim().to_owned());
+    }
+    inline.clear();
+}
+
+/// Downloads the files in parallel, showing the progress of each.
+///
+/// Each download is aborted as soon as it turns out to be larger than `max_size`, from either
+/// `Content-Length` or the bytes received so far.
 // https://github.com/rust-lang/rust-clippy/issues/5991
 #[allow(clippy::needless_collect)]
 fn download_with_progress(
     draw_target: ProgressDrawTarget,
+    max_size: Option<u64>,
     dl_targets: Vec<(String, reqwest::RequestBuilder)>,
 ) -> anyhow::Result<Vec<String>> {
     let rt = Runtime::new()?;
@@ -991,8 +1371,21 @@ fn download_with_progress(
             rt.spawn(async move {
                 let res = req.send().await?;
 
+                let content_len = res.content_length();
+
+                if let (Some(content_len), Some(max_size)) = (content_len, max_size) {
+                    if content_len > max_size {
+                        bail!(
+                            "`{}` is {} bytes, which exceeds the limit of {} bytes",
+                            name,
+                            content_len,
+                            max_size,
+                        );
+                    }
+                }
+
                 tokio::task::block_in_place(|| {
-                    if let Some(content_len) = res.content_length() {
+                    if let Some(content_len) = content_len {
                         pb.set_length(content_len);
                     }
 
@@ -1002,7 +1395,7 @@ fn download_with_progress(
                     ));
                 });
 
-                let mut content = vec![];
+                let mut content = buffer_with_capacity_hint(content_len);
                 let mut stream = res.bytes_stream();
 
                 while let Some(chunk) = stream.next().await {
@@ -1010,6 +1403,12 @@ fn download_with_progress(
 
                     content.extend_from_slice(chunk.as_ref());
 
+                    if let Some(max_size) = max_size {
+                        if content.len() as u64 > max_size {
+                            bail!("`{}` exceeded the limit of {} bytes", name, max_size);
+                        }
+                    }
+
                     tokio::task::block_in_place(|| {
                         pb.inc(chunk.len().try_into().unwrap_or(u64::MAX));
                     });
@@ -1017,7 +1416,7 @@ fn download_with_progress(
 
                 tokio::task::block_in_place(|| pb.finish_at_current_pos());
 
-                reqwest::Result::Ok(content)
+                anyhow::Ok(content)
             })
         })
         .collect::<Vec<_>>();
@@ -1040,3 +1439,345 @@ fn download_with_progress(
         s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
     }
 }
+
+/// Preallocates a buffer from `Content-Length` so that large test cases are not reallocated as they grow.
+///
+/// The hint is capped since the header is not trustworthy.
+fn buffer_with_capacity_hint(content_len: Option<u64>) -> Vec<u8> {
+    const MAX_CAPACITY_HINT: u64 = 256 * 1024 * 1024;
+
+    let capacity = content_len.unwrap_or(0).min(MAX_CAPACITY_HINT);
+    Vec::with_capacity(capacity.try_into().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testsuite::{BatchTestSuite, Match, TestSuite},
+        web::{
+            testing::{serve_once, NoShell},
+            CookieStorage, ElementRefExt as _, HtmlExt as _, ResponseExt as _, ServiceError,
+            Session, SessionMut as _, TlsConfig,
+        },
+    };
+    use indicatif::ProgressDrawTarget;
+    use pretty_assertions::assert_eq;
+    use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+    use scraper::Html;
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+    use url::Url;
+
+    #[test]
+    fn sample_text() {
+        let html = Html::parse_fragment(
+            "<pre><var>N</var> <var>M</var>\n1 2<br><span><var>A_1</var></span>\n\
+             <script type=\"math/tex\">\\le</script></pre>",
+        );
+        let pre = html.select(static_selector!("pre")).next().unwrap();
+        assert_eq!("N M\n1 2\nA_1\n", pre.sample_text());
+    }
+
+    #[test]
+    fn extract_editorial_url() {
+        let html = Html::parse_document(
+            "<ul class=\"nav nav-tabs\">\
+             <li><a href=\"/contests/abc300/tasks/abc300_a\">Problem</a></li>\
+             <li><a href=\"/contests/abc300/tasks/abc300_a/editorial\">Editorial</a></li>\
+             </ul>",
+        );
+        let base = "https://atcoder.jp/contests/abc300/tasks/abc300_a"
+            .parse::<Url>()
+            .unwrap();
+        let editorial = html.extract_editorial_url(&base).unwrap();
+        assert_eq!(
+            "https://atcoder.jp/contests/abc300/tasks/abc300_a/editorial",
+            editorial.as_str(),
+        );
+
+        let html = Html::parse_document(
+            "<div id=\"sidebar\"><ul>\
+             <li><a href=\"/blog/entry/100000\">Announcement</a></li>\
+             <li><a href=\"/blog/entry/100001\">Tutorial (en)</a></li>\
+             </ul></div>",
+        );
+        let base = "https://codeforces.com/contest/1000/problem/A"
+            .parse::<Url>()
+            .unwrap();
+        assert_eq!(
+            Some("https://codeforces.com/blog/entry/100001"),
+            html.extract_editorial_url(&base).as_ref().map(Url::as_str),
+        );
+
+        let html = Html::parse_document("<a href=\"/problems/no/1/submit\">提出</a>");
+        let base = "https://yukicoder.me/problems/no/1".parse::<Url>().unwrap();
+        assert_eq!(None, html.extract_editorial_url(&base));
+
+        let mut test_suite = TestSuite::Batch(BatchTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: None,
+            r#match: Match::Lines,
+            reference: None,
+            generator: None,
+            input_filter: None,
+            cases: vec![],
+            cases_from: None,
+            exclude: vec![],
+            extend: vec![],
+        });
+        test_suite.set_editorial(editorial);
+        assert!(test_suite.to_yaml_pretty().contains(
+            "\neditorial: \"https://atcoder.jp/contests/abc300/tasks/abc300_a/editorial\"\n",
+        ));
+    }
+
+    #[test]
+    fn to_markdown() {
+        let html = Html::parse_fragment(
+            "<div id=\"task-statement\"><style>.x{}</style>\
+             <div class=\"part\"><section><h3>Problem Statement</h3>\
+             <p>Given <var>N</var>,\n  print <code>N + 1</code>.</p>\
+             <ul><li>1 \\leq N \\leq 100</li><li><var>N</var> is an integer.</li></ul>\
+             <p>- <var>A_i</var> * 2 [sic] &lt;b&gt;</p>\
+             <script>alert(1)</script></section></div>\
+             <div class=\"part\"><section><h3>Sample Input 1</h3>\
+             <pre>1\n</pre></section></div></div>",
+        );
+        let stmt = html
+            .select(static_selector!("#task-statement"))
+            .next()
+            .unwrap();
+        assert_eq!(
+            "### Problem Statement\n\n\
+             Given N, print `N + 1`.\n\n\
+             - 1 \\\\leq N \\\\leq 100\n\n\
+             - N is an integer.\n\n\
+             \\- A\\_i \\* 2 \\[sic\\] \\<b\\>\n\n\
+             ### Sample Input 1\n\n\
+             ```\n1\n```\n",
+            stmt.to_markdown(),
+        );
+    }
+
+    #[test]
+    fn tls_root_certificates() {
+        static CERTIFICATE: &str = r#"-----BEGIN CERTIFICATE-----
+MIIBizCCATGgAwIBAgIUH4KIkloxO0XiTNvC+KkC7VTiICIwCgYIKoZIzj0EAwIw
+GjEYMBYGA1UEAwwPc25vd2NoYWlucy10ZXN0MCAXDTI2MTAxNTA1NTQ0OVoYDzIx
+MjYwOTIxMDU1NDQ5WjAaMRgwFgYDVQQDDA9zbm93Y2hhaW5zLXRlc3QwWTATBgcq
+hkjOPQIBBggqhkjOPQMBBwNCAART2+trOOoa3saXrbcJuJhIQ5L9BgnyD8Yxg2pb
+7RPktKoDuKIPQO6xujzAg2bOoFABJ9P7egdAGaPxsNy/67OLo1MwUTAdBgNVHQ4E
+FgQUUKPZNC/CU8bHd85ii3hRD88XlNEwHwYDVR0jBBgwFoAUUKPZNC/CU8bHd85i
+i3hRD88XlNEwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiBOGq3c
+YfRG+W29p1Cr+VUSlMGILz5Vln7yZJPc0WeLTgIhAK2tf+cdWWH6hAwcktZNM3Sg
+S0UuPtCSpsnPVVcMK0DS
+-----END CERTIFICATE-----
+"#;
+
+        let new = |tls| Session::new(None, HeaderMap::new(), tls, None, NoShell).map(drop);
+
+        new(TlsConfig {
+            root_certificates: vec![CERTIFICATE.as_bytes().to_owned()],
+            danger_accept_invalid_certs: false,
+        })
+        .unwrap();
+
+        new(TlsConfig {
+            root_certificates: vec![],
+            danger_accept_invalid_certs: true,
+        })
+        .unwrap();
+
+        let err = new(TlsConfig {
+            root_certificates: vec![b"not a certificate".to_vec()],
+            danger_accept_invalid_certs: false,
+        })
+        .unwrap_err();
+        assert_eq!("Could not parse a root certificate", err.to_string());
+    }
+
+    #[test]
+    fn cookies_are_saved_even_if_request_fails() {
+        let (url, server) = serve_once(&[b"HTTP/1.1 200 OK\r\n\
+                                           Set-Cookie: session=foo\r\n\
+                                           Set-Cookie: other=bar; Domain=example.com\r\n\
+                                           Content-Length: 0\r\n\
+                                           Connection: close\r\n\
+                                           \r\n"]);
+
+        let saved = Rc::new(RefCell::new(vec![]));
+
+        let cookie_storage = CookieStorage {
+            cookie_store: Default::default(),
+            on_update: Box::new({
+                let saved = saved.clone();
+                move |cookie_store| {
+                    saved.borrow_mut().push(
+                        cookie_store
+                            .iter_unexpired()
+                            .map(|c| c.name().to_owned())
+                            .collect::<Vec<_>>(),
+                    );
+                    Ok(())
+                }
+            }),
+        };
+
+        let mut sess = Session::new(
+            Some(Duration::from_secs(10)),
+            HeaderMap::new(),
+            TlsConfig::default(),
+            Some(cookie_storage),
+            NoShell,
+        )
+        .unwrap();
+
+        assert!(sess.get(url).send().is_err());
+        server.join().unwrap();
+
+        assert_eq!(vec![vec!["session".to_owned()]], *saved.borrow());
+    }
+
+    #[test]
+    fn unexpected_status_is_service_error() {
+        let (url, server) = serve_once(&[b"HTTP/1.1 503 Service Unavailable\r\n\
+                                           Content-Length: 0\r\n\
+                                           Connection: close\r\n\
+                                           \r\n"]);
+
+        let mut sess = Session::new(
+            Some(Duration::from_secs(10)),
+            HeaderMap::new(),
+            TlsConfig::default(),
+            None,
+            NoShell,
+        )
+        .unwrap();
+
+        let err = sess
+            .get(url)
+            .send()
+            .unwrap()
+            .ensure_status(&[200])
+            .unwrap_err();
+        server.join().unwrap();
+
+        assert!(err.downcast_ref::<ServiceError>().is_some(), "{:?}", err);
+        assert_eq!(
+            "expected [200], got 503 Service Unavailable",
+            err.to_string(),
+        );
+    }
+
+    #[test]
+    fn headers_are_sent() {
+        let request_headers = |headers: HeaderMap| -> String {
+            let (url, server) = serve_once(&[b"HTTP/1.1 200 OK\r\n\
+                                               Content-Length: 0\r\n\
+                                               Connection: close\r\n\
+                                               \r\n"]);
+
+            let mut sess = Session::new(
+                Some(Duration::from_secs(10)),
+                headers,
+                TlsConfig::default(),
+                None,
+                NoShell,
+            )
+            .unwrap();
+            let _ = sess.get(url).send();
+            server.join().unwrap()[0].to_lowercase()
+        };
+
+        let request = request_headers(HeaderMap::new());
+        assert!(
+            request.contains(&format!(
+                "\r\nuser-agent: snowchains/{} ",
+                env!("CARGO_PKG_VERSION"),
+            )),
+            "{}",
+            request,
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static("my-agent"));
+        headers.insert("x-foo", HeaderValue::from_static("bar"));
+        let request = request_headers(headers);
+        assert!(
+            request.contains("\r\nuser-agent: my-agent\r\n"),
+            "{}",
+            request
+        );
+        assert!(request.contains("\r\nx-foo: bar\r\n"), "{}", request);
+        assert_eq!(1, request.matches("user-agent:").count());
+    }
+
+    #[test]
+    fn download_with_progress_max_size() {
+        let download = |response: &'static [u8]| {
+            let (url, server) = serve_once(&[response]);
+
+            let result = super::download_with_progress(
+                ProgressDrawTarget::hidden(),
+                Some(4),
+                vec![(
+                    "a.txt".to_owned(),
+                    reqwest::Client::new().get(url.join("a.txt").unwrap()),
+                )],
+            );
+            server.join().unwrap();
+            result
+        };
+
+        let err = download(
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Length: 5\r\n\
+              Connection: close\r\n\
+              \r\n\
+              aaaaa",
+        )
+        .unwrap_err();
+        assert_eq!(
+            "`a.txt` is 5 bytes, which exceeds the limit of 4 bytes",
+            err.to_string(),
+        );
+
+        let err = download(
+            b"HTTP/1.1 200 OK\r\n\
+              Connection: close\r\n\
+              \r\n\
+              aaaaa",
+        )
+        .unwrap_err();
+        assert_eq!("`a.txt` exceeded the limit of 4 bytes", err.to_string());
+
+        let contents = download(
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Length: 4\r\n\
+              Connection: close\r\n\
+              \r\n\
+              aaaa",
+        )
+        .unwrap();
+        assert_eq!(["aaaa"], *contents);
+    }
+
+    #[test]
+    fn buffer_with_capacity_hint() {
+        const LEN: usize = 16 * 1024 * 1024;
+        const CHUNK_LEN: usize = 8 * 1024;
+
+        let mut buf = super::buffer_with_capacity_hint(Some(LEN as _));
+        let ptr = buf.as_ptr();
+
+        for _ in 0..LEN / CHUNK_LEN {
+            buf.extend_from_slice(&[0; CHUNK_LEN]);
+        }
+
+        assert_eq!(LEN, buf.len());
+        assert_eq!(ptr, buf.as_ptr());
+
+        assert_eq!(0, super::buffer_with_capacity_hint(None).capacity());
+        assert!(super::buffer_with_capacity_hint(Some(u64::MAX)).capacity() < LEN * 100);
+    }
+}
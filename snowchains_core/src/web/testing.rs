@@ -0,0 +1,68 @@
+use crate::web::Shell;
+use std::{
+    io::{Read as _, Write as _},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+use url::Url;
+
+pub(super) struct NoShell;
+
+impl Shell for NoShell {}
+
+/// Listens on a random port of `127.0.0.1` and answers each of the next `responses.len()`
+/// connections with one of `responses`, in order.
+///
+/// Returns the root URL of the server and a handle to the requests, with the bodies of the ones
+/// with `Content-Length`.
+pub(super) fn serve_once(responses: &[&'static [u8]]) -> (Url, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let url = format!("http://{}/", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    let responses = responses.to_owned();
+
+    let server = thread::spawn(move || {
+        let mut requests = vec![];
+
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            let mut len = None;
+            while len.is_none_or(|len| request.len() < len) {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+
+                if len.is_none() {
+                    if let Some(head_len) = request
+                        .windows(4)
+                        .position(|w| w == b"\r\n\r\n")
+                        .map(|i| i + 4)
+                    {
+                        let content_len = String::from_utf8_lossy(&request[..head_len])
+                            .lines()
+                            .flat_map(|line| line.split_once(':'))
+                            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                            .map_or(0, |(_, value)| value.trim().parse().unwrap());
+                        len = Some(head_len + content_len);
+                    }
+                }
+            }
+
+            // The client may have given up on the response, which is what some tests check.
+            let _ = stream.write_all(response);
+
+            requests.push(String::from_utf8(request).unwrap());
+        }
+        requests
+    });
+
+    (url, server)
+}
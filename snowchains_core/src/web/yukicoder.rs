@@ -4,11 +4,12 @@ use crate::{
         TestSuite,
     },
     web::{
-        yukicoder::api::SessionMutExt as _, Exec, Platform, ResponseExt as _,
-        RetrieveFullTestCases, RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveTestCases,
-        RetrieveTestCasesOutcome, RetrieveTestCasesOutcomeProblem,
-        RetrieveTestCasesOutcomeProblemContest, RetrieveTestCasesOutcomeProblemTextFiles, Session,
-        SessionMut, Shell, Submit, SubmitOutcome,
+        yukicoder::api::SessionMutExt as _, ElementRefExt as _, Exec, HtmlExt as _, Platform,
+        ResponseExt as _, RetrieveFullTestCases, RetrieveLanguages, RetrieveLanguagesOutcome,
+        RetrieveProblemIndexes, RetrieveTestCases, RetrieveTestCasesOutcome,
+        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest,
+        RetrieveTestCasesOutcomeProblemTextFiles, ServiceError, Session, SessionMut, Shell, Submit,
+        SubmitOutcome,
     },
 };
 use anyhow::{bail, Context as _};
@@ -17,7 +18,7 @@ use either::Either;
 use indexmap::indexmap;
 use itertools::Itertools as _;
 use once_cell::sync::Lazy;
-use scraper::{ElementRef, Html, Node};
+use scraper::Html;
 use std::{collections::BTreeSet, convert::Infallible, hash::Hash, time::Duration};
 use url::Url;
 
@@ -62,10 +63,12 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Yukicoder {
             credentials: (),
             cookie_storage: (),
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let names_by_id = Session::new(timeout, None, shell)?
+        let names_by_id = Session::new(timeout, headers, tls, None, shell)?
             .get_available_language()?
             .into_iter()
             .map(|api::Language { id, name, ver }| (id, format!("{} ({})", name, ver)))
@@ -83,17 +86,24 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
             targets,
             credentials: (),
             full,
+            strict: _,
+            statement,
+            statement_language: _,
+            editorial,
             cookie_storage: (),
             timeout,
+            headers,
+            tls,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, None, shell)?;
+        let mut sess = Session::new(timeout, headers, tls, None, shell)?;
 
-        let mut outcome = retrieve_samples(&mut sess, targets)?;
+        let mut outcome = retrieve_samples(&mut sess, statement, editorial, targets)?;
 
         if let Some(RetrieveFullTestCases {
             credentials: YukicoderRetrieveFullTestCasesCredentials { api_key },
+            max_download_size,
         }) = full
         {
             for outcome_problem in &mut outcome.problems {
@@ -109,6 +119,7 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
 
                 let in_contents = super::download_with_progress(
                     sess.shell.progress_draw_target(),
+                    max_download_size,
                     in_file_names
                         .iter()
                         .map(|file_name| {
@@ -128,6 +139,7 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
 
                 let out_contents = super::download_with_progress(
                     sess.shell.progress_draw_target(),
+                    max_download_size,
                     out_file_names
                         .iter()
                         .map(|file_name| {
@@ -161,6 +173,37 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
     }
 }
 
+impl<S: Shell> Exec<RetrieveProblemIndexes<Self, S>> for Yukicoder {
+    type Output = Vec<String>;
+
+    fn exec(args: RetrieveProblemIndexes<Self, S>) -> anyhow::Result<Vec<String>> {
+        let RetrieveProblemIndexes {
+            contest,
+            credentials: (),
+            cookie_storage: (),
+            timeout,
+            headers,
+            tls,
+            shell,
+        } = args;
+
+        let contest_id = parse_contest_id(&contest)?;
+
+        let mut sess = Session::new(timeout, headers, tls, None, shell)?;
+
+        let api::Contest {
+            problem_id_list, ..
+        } = sess.get_contest_by_contest_id(contest_id)?;
+
+        check_problem_count(contest_id, &problem_id_list)?;
+
+        let indexes = (0..problem_id_list.len())
+            .map(|i| char::from(i as u8 + b'A').to_string())
+            .collect();
+        Ok(indexes)
+    }
+}
+
 impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
     type Output = SubmitOutcome;
 
@@ -169,10 +212,14 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
             target,
             credentials: YukicoderSubmitCredentials { api_key },
             language_id,
+            language_name: _,
             code,
             watch_submission,
+            watch_timeout: _,
             cookie_storage: (),
             timeout,
+            headers,
+            tls,
             mut shell,
         } = args;
 
@@ -180,7 +227,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
             shell.warn("`watch_submissions` in yukicoder is not yet supported")?;
         }
 
-        let mut sess = Session::new(timeout, None, shell)?;
+        let mut sess = Session::new(timeout, headers, tls, None, shell)?;
 
         let problem_id = match target.parse()? {
             Either::Left(url) => match parse_problem_url(&url)? {
@@ -197,9 +244,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
                     problem_id_list, ..
                 } = sess.get_contest_by_contest_id(contest_id)?;
 
-                if problem_id_list.len() > 26 {
-                    unimplemented!("{} problems", problem_id_list.len());
-                }
+                check_problem_count(contest_id, &problem_id_list)?;
 
                 let (_, problem_id) = problem_id_list
                     .into_iter()
@@ -222,10 +267,13 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
                 problem_screen_name: Some(problem_id.to_string()),
                 submission_url: url!("/submissions/{}", submission_id),
                 submissions_url: url!("/problems/{}/submissions?my_submission=enabled", problem_id),
+                verdict: None,
             }),
-            Err((status_code, message)) => {
-                bail!("Submission rejected: ({}, {:?})", status_code, message);
-            }
+            Err((status_code, message)) => Err(ServiceError::new(format_args!(
+                "Submission rejected: ({}, {:?})",
+                status_code, message,
+            ))
+            .into()),
         }
     }
 }
@@ -294,6 +342,18 @@ fn parse_contest_id(s: &str) -> anyhow::Result<u64> {
     })
 }
 
+/// The problems in a contest are given the indexes `A`, `B`, ..., which run out at `Z`.
+fn check_problem_count(contest_id: u64, problem_id_list: &[u64]) -> anyhow::Result<()> {
+    if problem_id_list.len() > 26 {
+        bail!(
+            "The contest `{}` has {} problems, but indexes past `Z` are not supported",
+            contest_id,
+            problem_id_list.len(),
+        );
+    }
+    Ok(())
+}
+
 fn parse_problem_url(url: &Url) -> anyhow::Result<Either<u64, u64>> {
     if url.domain() != Some("yukicoder.me") {
         bail!("wrong domain. expected `yukicoder.me`: {}", url);
@@ -314,6 +374,8 @@ fn parse_problem_url(url: &Url) -> anyhow::Result<Either<u64, u64>> {
 
 fn retrieve_samples(
     mut sess: impl SessionMut,
+    statement: bool,
+    editorial: bool,
     targets: YukicoderRetrieveTestCasesTargets,
 ) -> anyhow::Result<RetrieveTestCasesOutcome> {
     let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
@@ -323,7 +385,8 @@ fn retrieve_samples(
             for problem_no in &problem_nos {
                 let problem_no = parse_problem_no(problem_no)?;
 
-                let (url, test_suite) = retrieve_samples(&mut sess, problem_no)?;
+                let (url, test_suite, statement) =
+                    retrieve_samples(&mut sess, statement, editorial, problem_no)?;
                 let api::Problem {
                     problem_id, title, ..
                 } = sess.get_problem_by_problem_no(problem_no)?;
@@ -336,6 +399,7 @@ fn retrieve_samples(
                     display_name: title.clone(),
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
                 });
             }
         }
@@ -366,9 +430,7 @@ fn retrieve_samples(
                 ..
             } = sess.get_contest_by_contest_id(contest_id)?;
 
-            if problem_id_list.len() > 26 {
-                unimplemented!("{} problems", problem_id_list.len());
-            }
+            check_problem_count(contest_id, &problem_id_list)?;
 
             let contest = &RetrieveTestCasesOutcomeProblemContest {
                 id: contest_id.to_string(),
@@ -389,7 +451,8 @@ fn retrieve_samples(
                 }
 
                 let api::Problem { no, title, .. } = sess.get_problem_by_problem_id(problem_id)?;
-                let (url, test_suite) = retrieve_samples(&mut sess, no)?;
+                let (url, test_suite, statement) =
+                    retrieve_samples(&mut sess, statement, editorial, no)?;
 
                 outcome.problems.push(RetrieveTestCasesOutcomeProblem {
                     contest: Some(contest.clone()),
@@ -399,6 +462,7 @@ fn retrieve_samples(
                     display_name: title,
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
                 });
             }
 
@@ -419,7 +483,8 @@ fn retrieve_samples(
                     Either::Right(problem_id) => sess.get_problem_by_problem_id(problem_id)?,
                 };
 
-                let (_, test_suite) = retrieve_samples(&mut sess, no)?;
+                let (_, test_suite, statement) =
+                    retrieve_samples(&mut sess, statement, editorial, no)?;
 
                 outcome.problems.push(RetrieveTestCasesOutcomeProblem {
                     contest: None,
@@ -429,6 +494,7 @@ fn retrieve_samples(
                     display_name: title.clone(),
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
                 });
             }
         }
@@ -438,24 +504,41 @@ fn retrieve_samples(
 
     fn retrieve_samples(
         mut sess: impl SessionMut,
+        statement: bool,
+        editorial: bool,
         problem_no: u64,
-    ) -> anyhow::Result<(Url, TestSuite)> {
+    ) -> anyhow::Result<(Url, TestSuite, Option<String>)> {
         let url = url!("/problems/no/{}", problem_no);
 
-        let test_suite = sess
+        let html = sess
             .get(url.clone())
             .colorize_status_code(&[200], (), ..)
             .send()?
             .ensure_status(&[200])?
-            .html()?
-            .extract_samples()?;
+            .html()?;
+
+        let mut test_suite = html.extract_samples()?;
+        let statement = statement.then(|| html.extract_statement()).flatten();
+
+        if let Some(editorial) = editorial
+            .then(|| html.extract_editorial_url(&url))
+            .flatten()
+        {
+            test_suite.set_editorial(editorial);
+        }
 
-        Ok((url, test_suite))
+        Ok((url, test_suite, statement))
     }
 }
 
 #[ext]
 impl Html {
+    fn extract_statement(&self) -> Option<String> {
+        self.select(static_selector!("#content"))
+            .next()
+            .map(|r| r.to_markdown())
+    }
+
     fn extract_samples(&self) -> anyhow::Result<TestSuite> {
         let (timelimit, kind) = self
             .select(static_selector!("#content > div"))
@@ -514,9 +597,16 @@ impl Html {
                 };
 
                 let mut test_suite = BatchTestSuite {
+                    description: None,
+                    editorial: None,
                     timelimit: Some(timelimit),
                     r#match,
+                    reference: None,
+                    generator: None,
+                    input_filter: None,
                     cases: vec![],
+                    cases_from: None,
+                    exclude: vec![],
                     extend: vec![],
                 };
 
@@ -532,15 +622,18 @@ impl Html {
                     {
                         test_suite.cases.push(PartialBatchTestCase {
                             name: Some(format!("sample{}", i + 1)),
-                            r#in: input.fold_text_and_br().into(),
+                            note: None,
+                            r#in: input.sample_text().into(),
                             out: match kind {
                                 Kind::Regular | Kind::Floating { .. } => {
-                                    Some(output.fold_text_and_br().into())
+                                    Some(output.sample_text().into())
                                 }
                                 _ => None,
                             },
                             timelimit: None,
                             r#match: None,
+                            stderr: None,
+                            stderr_match: None,
                         });
                     } else {
                         bail!("Could not extract sample cases");
@@ -550,7 +643,12 @@ impl Html {
                 TestSuite::Batch(test_suite)
             }
             Kind::Reactive => TestSuite::Interactive(InteractiveTestSuite {
+                description: None,
+                editorial: None,
                 timelimit: Some(timelimit),
+                tester: None,
+                each_args: vec![],
+                example: vec![],
             }),
         };
 
@@ -566,28 +664,13 @@ impl Html {
                 absolute_error: Option<PositiveFinite<f64>>,
             },
         }
-
-        #[ext]
-        impl ElementRef<'_> {
-            fn fold_text_and_br(&self) -> String {
-                self.children().fold("".to_owned(), |mut ret, node| {
-                    match node.value() {
-                        Node::Text(t) => ret += t,
-                        Node::Element(e) if e.name() == "br" => ret.push('\n'),
-                        _ => {}
-                    }
-                    ret
-                })
-            }
-        }
     }
 }
 
 mod api {
     //! <https://petstore.swagger.io/?url=https://yukicoder.me/api/swagger.yaml>
 
-    use crate::web::{ResponseExt as _, SessionMut};
-    use anyhow::bail;
+    use crate::web::{ResponseExt as _, ServiceError, SessionMut};
     use maplit::hashmap;
     use once_cell::sync::Lazy;
     use reqwest::StatusCode;
@@ -619,7 +702,7 @@ mod api {
                 res.json().map_err(Into::into)
             } else {
                 let res = res.json::<serde_json::Value>()?;
-                bail!("{}", serde_json::to_string_pretty(&res).unwrap());
+                Err(ServiceError::new(serde_json::to_string_pretty(&res).unwrap()).into())
             }
         }
 
@@ -655,7 +738,7 @@ mod api {
                 res.json().map_err(Into::into)
             } else {
                 let res = res.json::<serde_json::Value>()?;
-                bail!("{}", serde_json::to_string_pretty(&res).unwrap());
+                Err(ServiceError::new(serde_json::to_string_pretty(&res).unwrap()).into())
             }
         }
 
@@ -673,7 +756,7 @@ mod api {
                 res.json().map_err(Into::into)
             } else {
                 let res = res.json::<serde_json::Value>()?;
-                bail!("{}", serde_json::to_string_pretty(&res).unwrap());
+                Err(ServiceError::new(serde_json::to_string_pretty(&res).unwrap()).into())
             }
         }
 
@@ -721,7 +804,7 @@ mod api {
                 res.json().map_err(Into::into)
             } else {
                 let res = res.json::<serde_json::Value>()?;
-                bail!("{}", serde_json::to_string_pretty(&res).unwrap());
+                Err(ServiceError::new(serde_json::to_string_pretty(&res).unwrap()).into())
             }
         }
 
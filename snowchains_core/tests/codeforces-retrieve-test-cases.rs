@@ -92,11 +92,17 @@ fn test(
             username_and_password: &mut { username_and_password },
         },
         full: None,
+        strict: false,
+        statement: false,
+        statement_language: Default::default(),
+        editorial: false,
         cookie_storage: CookieStorage {
             cookie_store: CookieStore::default(),
             on_update: Box::new(|_| Ok(())),
         },
         timeout: TIMEOUT,
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell(&mut messages),
     })?;
 
@@ -2,7 +2,8 @@ use insta::{assert_debug_snapshot, assert_json_snapshot};
 use maplit::btreeset;
 use reqwest::{Method, StatusCode};
 use snowchains_core::web::{
-    RetrieveTestCases, StatusCodeColor, Yukicoder, YukicoderRetrieveTestCasesTargets,
+    RetrieveTestCases, RetrieveTestCasesOutcome, StatusCodeColor, Yukicoder,
+    YukicoderRetrieveTestCasesTargets,
 };
 use std::{
     fmt,
@@ -21,7 +22,40 @@ fn contest_281_samples() -> anyhow::Result<()> {
     test(Target::Contest(281))
 }
 
+#[test]
+fn problem_no_1_by_url() -> anyhow::Result<()> {
+    let (outcome, _) = retrieve(YukicoderRetrieveTestCasesTargets::Urls(btreeset!(
+        "https://yukicoder.me/problems/no/1".parse()?
+    )))?;
+
+    // Should be the same as the one from the problem number.
+    assert_json_snapshot!("problem_no_1_samples_outcome", outcome);
+    Ok(())
+}
+
 fn test(target: Target) -> anyhow::Result<()> {
+    let (outcome, messages) = retrieve(match target {
+        Target::Contest(contest) => {
+            YukicoderRetrieveTestCasesTargets::Contest(contest.to_string(), None)
+        }
+        Target::ProblemNo(problem_no) => {
+            YukicoderRetrieveTestCasesTargets::ProblemNos(btreeset!(problem_no.to_string()))
+        }
+    })?;
+
+    let target = match target {
+        Target::Contest(contest) => format!("contest_{}", contest),
+        Target::ProblemNo(problem_no) => format!("problem_no_{}", problem_no),
+    };
+
+    assert_json_snapshot!(format!("{}_samples_outcome", target), outcome);
+    assert_debug_snapshot!(format!("{}_samples_messages", target), messages);
+    Ok(())
+}
+
+fn retrieve(
+    targets: YukicoderRetrieveTestCasesTargets,
+) -> anyhow::Result<(RetrieveTestCasesOutcome, Vec<Message>)> {
     const TIMEOUT: Option<Duration> = Some(Duration::from_secs(30));
 
     struct Shell<'a>(&'a mut Vec<Message>);
@@ -60,40 +94,32 @@ fn test(target: Target) -> anyhow::Result<()> {
         }
     }
 
-    #[derive(Debug)]
-    enum Message {
-        PrintAnsi(String),
-        Warn(String),
-        OnRequest(Method, Url),
-        OnResponse(StatusCode, StatusCodeColor),
-    }
-
     let mut messages = vec![];
 
     let outcome = Yukicoder::exec(RetrieveTestCases {
-        targets: match target {
-            Target::Contest(contest) => {
-                YukicoderRetrieveTestCasesTargets::Contest(contest.to_string(), None)
-            }
-            Target::ProblemNo(problem_no) => {
-                YukicoderRetrieveTestCasesTargets::ProblemNos(btreeset!(problem_no.to_string()))
-            }
-        },
+        targets,
         credentials: (),
         full: None,
+        strict: false,
+        statement: false,
+        statement_language: Default::default(),
+        editorial: false,
         cookie_storage: (),
         timeout: TIMEOUT,
+        headers: Default::default(),
+        tls: Default::default(),
         shell: Shell(&mut messages),
     })?;
 
-    let target = match target {
-        Target::Contest(contest) => format!("contest_{}", contest),
-        Target::ProblemNo(problem_no) => format!("problem_no_{}", problem_no),
-    };
+    Ok((outcome, messages))
+}
 
-    assert_json_snapshot!(format!("{}_samples_outcome", target), outcome);
-    assert_debug_snapshot!(format!("{}_samples_messages", target), messages);
-    Ok(())
+#[derive(Debug)]
+enum Message {
+    PrintAnsi(String),
+    Warn(String),
+    OnRequest(Method, Url),
+    OnResponse(StatusCode, StatusCodeColor),
 }
 
 #[derive(Clone, Copy)]
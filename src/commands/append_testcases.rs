@@ -0,0 +1,117 @@
+use anyhow::bail;
+use snowchains_core::{color_spec, testsuite::TestSuite, web::PlatformKind};
+use std::{io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptAppendTestcases {
+    /// Directory containing the input and output files
+    #[structopt(long, value_name("DIR"))]
+    pub from: PathBuf,
+
+    /// Glob for the input files. They are paired with the output files by their file stems
+    #[structopt(long, value_name("GLOB"), default_value("*.in"))]
+    pub r#in: String,
+
+    /// Glob for the output files
+    #[structopt(long, value_name("GLOB"), default_value("*.out"))]
+    pub out: String,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptAppendTestcases,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptAppendTestcases {
+        from,
+        r#in,
+        out,
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (
+        crate::config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        _,
+    ) = crate::config::target(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+    )?;
+
+    let test_suite_path = crate::config::test_suites(&cwd, config.as_deref())?.path(
+        service,
+        contest.as_deref(),
+        &problem,
+    )?;
+
+    let line_ending = crate::config::test_suite_line_ending(&cwd, config.as_deref())?;
+
+    let mut test_suite = match crate::judge::read_test_suite(&test_suite_path)? {
+        TestSuite::Batch(test_suite) => test_suite,
+        _ => bail!("{} is not a batch test suite", test_suite_path.display()),
+    };
+
+    let num_cases = test_suite.append_text_files(&cwd.join(from), &r#in, &out)?;
+
+    if num_cases == 0 {
+        bail!("No input files matched `{}`", r#in);
+    }
+
+    crate::fs::write_atomically(
+        &test_suite_path,
+        TestSuite::Batch(test_suite).to_yaml_pretty_with(line_ending),
+        false,
+    )?;
+
+    write!(shell.stderr, "Appended {} test case(s) to ", num_cases)?;
+    shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(shell.stderr, "{}", test_suite_path.display())?;
+    shell.stderr.reset()?;
+    writeln!(shell.stderr)?;
+    shell.stderr.flush()?;
+
+    Ok(())
+}
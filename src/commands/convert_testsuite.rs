@@ -0,0 +1,134 @@
+use anyhow::bail;
+use snowchains_core::{color_spec, testsuite::TestSuite, web::PlatformKind};
+use std::{io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::{EnumString, EnumVariantNames, VariantNames as _};
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptConvertTestsuite {
+    /// Type to convert the test suite into
+    #[structopt(long, value_name("TYPE"), possible_values(TestSuiteType::VARIANTS))]
+    pub to: TestSuiteType,
+
+    /// Converts even if some of the fields are dropped
+    #[structopt(long)]
+    pub force: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+#[derive(EnumVariantNames, EnumString, Debug, Clone, Copy)]
+#[strum(serialize_all = "lowercase")]
+pub enum TestSuiteType {
+    Batch,
+    Interactive,
+}
+
+pub(crate) fn run(
+    opt: OptConvertTestsuite,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptConvertTestsuite {
+        to,
+        force,
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (
+        crate::config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        _,
+    ) = crate::config::target(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+    )?;
+
+    let test_suite_path = crate::config::test_suites(&cwd, config.as_deref())?.path(
+        service,
+        contest.as_deref(),
+        &problem,
+    )?;
+
+    let line_ending = crate::config::test_suite_line_ending(&cwd, config.as_deref())?;
+
+    let (test_suite, dropped) = match (crate::judge::read_test_suite(&test_suite_path)?, to) {
+        (TestSuite::Batch(test_suite), TestSuiteType::Interactive) => {
+            let (test_suite, dropped) = test_suite.to_interactive();
+            (TestSuite::Interactive(test_suite), dropped)
+        }
+        (TestSuite::Interactive(test_suite), TestSuiteType::Batch) => {
+            let (test_suite, dropped) = test_suite.to_batch();
+            (TestSuite::Batch(test_suite), dropped)
+        }
+        (TestSuite::Batch(_), TestSuiteType::Batch)
+        | (TestSuite::Interactive(_), TestSuiteType::Interactive) => {
+            bail!("{} is already {:?}", test_suite_path.display(), to)
+        }
+        (TestSuite::Unsubmittable, _) => {
+            bail!("{} is unsubmittable", test_suite_path.display())
+        }
+    };
+
+    if !dropped.is_empty() && !force {
+        bail!(
+            "Converting {} drops [{}]. Run with `--force` to convert anyway",
+            test_suite_path.display(),
+            dropped.join(", "),
+        );
+    }
+
+    crate::fs::write_atomically(
+        &test_suite_path,
+        test_suite.to_yaml_pretty_with(line_ending),
+        false,
+    )?;
+
+    write!(shell.stderr, "Converted ")?;
+    shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(shell.stderr, "{}", test_suite_path.display())?;
+    shell.stderr.reset()?;
+    writeln!(shell.stderr, " into {:?}", to)?;
+    shell.stderr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::bail;
+use snowchains_core::{color_spec, testsuite::TestSuite, web::PlatformKind};
+use std::{io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptFormatTestsuite {
+    /// Writes each test case whose input and output are single short tokens in one line
+    #[structopt(long)]
+    pub compact: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptFormatTestsuite,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptFormatTestsuite {
+        compact,
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (
+        crate::config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        _,
+    ) = crate::config::target(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+    )?;
+
+    let test_suite_path = crate::config::test_suites(&cwd, config.as_deref())?.path(
+        service,
+        contest.as_deref(),
+        &problem,
+    )?;
+
+    let line_ending = crate::config::test_suite_line_ending(&cwd, config.as_deref())?;
+
+    let test_suite = crate::judge::read_test_suite(&test_suite_path)?;
+    let formatted = if compact {
+        test_suite.to_yaml_compact_with(line_ending)
+    } else {
+        test_suite.to_yaml_pretty_with(line_ending)
+    };
+
+    // `to_yaml_pretty_with` falls back to the plain serializer for what it cannot render, but
+    // check it here too since the original file is about to be overwritten.
+    if serde_yaml::from_str::<TestSuite>(&formatted).ok().as_ref() != Some(&test_suite) {
+        bail!(
+            "Could not format {} without changing the content",
+            test_suite_path.display(),
+        );
+    }
+
+    if crate::fs::read_to_string(&test_suite_path)? == formatted {
+        write!(shell.stderr, "Already formatted: ")?;
+        shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(shell.stderr, "{}", test_suite_path.display())?;
+        shell.stderr.reset()?;
+        writeln!(shell.stderr)?;
+        shell.stderr.flush()?;
+        return Ok(());
+    }
+
+    crate::fs::write_atomically(&test_suite_path, formatted, false)?;
+
+    write!(shell.stderr, "Formatted ")?;
+    shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(shell.stderr, "{}", test_suite_path.display())?;
+    shell.stderr.reset()?;
+    writeln!(shell.stderr)?;
+    shell.stderr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::history::HistoryEntry;
+use snowchains_core::color_spec;
+use std::{io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptHistory {
+    /// Number of the recent entries to print
+    #[structopt(short = "n", long, value_name("N"), default_value("10"))]
+    pub count: usize,
+
+    /// Prints JSON lines
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+}
+
+pub(crate) fn run(
+    opt: OptHistory,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptHistory {
+        count,
+        json,
+        config,
+        color: _,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let base_dir = crate::config::base_dir(&cwd, config.as_deref())?;
+    let entries = crate::history::read(&crate::history::path(&base_dir))?;
+
+    for entry in &entries[entries.len().saturating_sub(count)..] {
+        if json {
+            writeln!(shell.stdout, "{}", serde_json::to_string(entry)?)?;
+            continue;
+        }
+
+        let HistoryEntry {
+            timestamp,
+            contest,
+            problem,
+            language,
+            total,
+            passed,
+            worst,
+            failed: _,
+        } = entry;
+
+        write!(shell.stdout, "{} ", timestamp)?;
+        if let Some(contest) = contest {
+            write!(shell.stdout, "{}/", contest)?;
+        }
+        write!(
+            shell.stdout,
+            "{} ({}) {}/{} ",
+            problem, language, passed, total
+        )?;
+        let color = if worst == "AC" {
+            Color::Green
+        } else {
+            Color::Yellow
+        };
+        shell.stdout.set_color(color_spec!(Bold, Fg(color)))?;
+        write!(shell.stdout, "{}", worst)?;
+        shell.stdout.reset()?;
+        writeln!(shell.stdout)?;
+    }
+
+    shell.stdout.flush()?;
+    Ok(())
+}
@@ -1,5 +1,5 @@
-use anyhow::{bail, Context as _};
-use std::{fs, path::PathBuf};
+use anyhow::bail;
+use std::path::PathBuf;
 use structopt::StructOpt;
 use strum::VariantNames as _;
 use termcolor::WriteColor;
@@ -49,11 +49,11 @@ pub(crate) fn run(
         );
     }
 
-    fs::write(
+    crate::fs::write_atomically(
         &path,
         include_str!("../../resources/config/default-config.dhall"),
-    )
-    .with_context(|| format!("Could not write `{}`", path.display()))?;
+        false,
+    )?;
 
     writeln!(stderr, "Wrote `{}`", path.display())?;
     stderr.flush()?;
@@ -1,7 +1,11 @@
 use crate::config;
 use human_size::Size;
-use snowchains_core::web::PlatformKind;
-use std::path::PathBuf;
+use snowchains_core::{
+    judge::{DiffAlgorithm, VerdictKind},
+    testsuite::{BlankLines, Match},
+    web::PlatformKind,
+};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 use structopt::StructOpt;
 use strum::VariantNames as _;
 use termcolor::WriteColor;
@@ -20,6 +24,88 @@ pub struct OptJudge {
     #[structopt(long, value_name("SIZE"), default_value("4KiB"))]
     pub display_limit: Size,
 
+    /// Prints the details of accepted test cases as well
+    #[structopt(short, long)]
+    pub verbose: bool,
+
+    /// Omits accepted test cases and prints only failures and the summary
+    #[structopt(short, long, conflicts_with("verbose"))]
+    pub quiet: bool,
+
+    /// Number of unchanged lines shown around each difference in the diff of wrong answers
+    #[structopt(long, value_name("N"), default_value("3"))]
+    pub diff_context: usize,
+
+    /// Renders trailing spaces as `·`
+    #[structopt(long)]
+    pub show_whitespace: bool,
+
+    /// Granularity of the diff of wrong answers. `word` highlights the differing words in each line
+    #[structopt(
+        long,
+        value_name("ALGORITHM"),
+        possible_values(DiffAlgorithm::VARIANTS),
+        default_value("line")
+    )]
+    pub color_diff_algorithm: DiffAlgorithm,
+
+    /// Truncates lines of the details wider than this with `…`. Defaults to the width of the terminal, or 80. `0` disables it
+    #[structopt(long, value_name("COLUMNS"))]
+    pub width: Option<usize>,
+
+    /// Prints only the test cases with the verdicts
+    #[structopt(
+        long,
+        value_name("VERDICT"),
+        possible_values(VerdictKind::VARIANTS),
+        use_delimiter(true)
+    )]
+    pub only: Option<Vec<VerdictKind>>,
+
+    /// Path to the source file, instead of `src` in `snowchains.dhall`
+    #[structopt(long, value_name("PATH"))]
+    pub source: Option<PathBuf>,
+
+    /// Multiplies the timelimits by the factor, for machines slower than the judge. `0` disables them
+    #[structopt(long, value_name("FACTOR"), parse(try_from_str = parse_time_scale))]
+    pub time_scale: Option<f64>,
+
+    /// Compares the outputs with this instead of `match` in the test suite: `exact`, `lines`, `tokens`, `float[:ERROR]`, or `any`
+    #[structopt(long, value_name("MATCH"))]
+    pub r#match: Option<MatchOverride>,
+
+    /// Converts CRLF into LF in the inputs, the expected outputs, and the output of the program
+    #[structopt(long)]
+    pub trim_cr: bool,
+
+    /// Stops at the first failing test case, skipping the rest
+    #[structopt(long)]
+    pub fail_fast: bool,
+
+    /// Tests for only the test cases which failed in the last run for the problem. They are found by their contents, so editing the other test cases does not affect it
+    #[structopt(long, conflicts_with("testcases"))]
+    pub failed: bool,
+
+    /// Runs the tester against `example` of the interactive test suite before judging, and fails if it does not accept it
+    #[structopt(long)]
+    pub check_tester: bool,
+
+    /// Judges the test cases one by one in order and omits the progress bars and the elapsed times, so that the output is the same in every run. The verdicts are not affected
+    #[structopt(long("concurrency-1"))]
+    pub concurrency_1: bool,
+
+    /// Uses `problems/<problem>/` for both the source file and the test suite
+    #[structopt(long)]
+    pub problem_dir: bool,
+
+    /// Path to a prebuilt executable to judge, instead of compiling and running the source file
+    #[structopt(long, value_name("PATH"), conflicts_with("source"))]
+    pub bin: Option<PathBuf>,
+
+    /// Prints the commands to build and run the program as shell commands, instead of running them
+    #[structopt(long)]
+    pub print_command: bool,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -51,6 +137,10 @@ pub struct OptJudge {
 
     /// Problem index (e.g. "a", "b", "c")
     pub problem: Option<String>,
+
+    /// Arguments passed to the program, after `run` in `snowchains.dhall`
+    #[structopt(last(true), value_name("ARGS"))]
+    pub args: Vec<String>,
 }
 
 pub(crate) fn run(
@@ -61,12 +151,31 @@ pub(crate) fn run(
         release,
         testcases,
         display_limit,
+        verbose,
+        quiet,
+        diff_context,
+        show_whitespace,
+        color_diff_algorithm,
+        width,
+        only,
+        time_scale,
+        r#match,
+        trim_cr,
+        fail_fast,
+        failed,
+        check_tester,
+        concurrency_1,
+        source,
+        problem_dir,
+        bin,
+        print_command,
         config,
         color: _,
         service,
         contest,
         language,
         problem,
+        args: run_args,
     } = opt;
 
     let crate::Context { cwd, shell } = ctx;
@@ -89,13 +198,8 @@ pub(crate) fn run(
             problem,
             ..
         },
-        config::Language {
-            src,
-            transpile,
-            compile,
-            run,
-            languageId: _,
-        },
+        language_name,
+        lang,
         base_dir,
     ) = config::target_and_language(
         &cwd,
@@ -104,6 +208,7 @@ pub(crate) fn run(
         contest.as_deref(),
         problem.as_deref(),
         language.as_deref(),
+        source.as_deref(),
         if release {
             config::Mode::Release
         } else {
@@ -111,8 +216,58 @@ pub(crate) fn run(
         },
     )?;
 
+    let lang = if let Some(source) = source {
+        lang.with_src(config::source_path(&cwd, &source)?)?
+    } else if let Some(bin) = bin {
+        lang.with_prebuilt_binary(config::binary_path(&cwd, &bin)?)
+    } else if problem_dir {
+        lang.with_problem_dir(&problem)?
+    } else {
+        lang
+    };
+
+    lang.validate(&base_dir)?;
+
+    let test_suites = if problem_dir {
+        config::TestSuites::problem_dir(&cwd, config.as_deref())?
+    } else {
+        config::test_suites(&cwd, config.as_deref())?
+    };
+    let test_suite_path = test_suites.path(service, contest.as_deref(), &problem)?;
+
+    let config::Language {
+        src,
+        transpile,
+        compile,
+        run,
+        languageId: _,
+    } = lang;
+
+    let run_working_dir = config::run_working_directory(&cwd, config.as_deref(), &src)?;
+
+    if print_command {
+        return crate::judge::print_commands(
+            stdout,
+            &base_dir,
+            transpile.as_ref(),
+            compile.as_ref(),
+            &run_working_dir,
+            &run,
+            &run_args,
+        );
+    }
+
     let test_case_names = testcases.map(|ss| ss.into_iter().collect());
 
+    let width = match width {
+        Some(0) => None,
+        Some(width) => Some(width),
+        None => Some(crate::shell::stdout_width()),
+    };
+
+    let hook = config::judge_hook(&cwd, config.as_deref())?;
+    let default_tester = config::default_tester(&cwd, config.as_deref())?;
+
     crate::judge::judge(crate::judge::Args {
         stdout,
         stderr,
@@ -121,14 +276,91 @@ pub(crate) fn run(
         stderr_process_redirection,
         progress_draw_target,
         base_dir,
-        service,
-        contest,
-        problem,
+        run_working_dir,
+        test_suite_path,
         src,
         transpile,
         compile,
         run,
+        run_args,
         test_case_names,
         display_limit,
+        verbose,
+        quiet,
+        diff_context,
+        show_whitespace,
+        color_diff_algorithm,
+        width,
+        only,
+        time_scale,
+        r#match: r#match.map(|MatchOverride(matching)| matching),
+        trim_cr,
+        fail_fast,
+        failed_only: failed,
+        check_tester,
+        deterministic: concurrency_1,
+        hook,
+        default_tester,
+        contest,
+        problem,
+        language: language_name,
     })
 }
+
+fn parse_time_scale(s: &str) -> anyhow::Result<f64> {
+    let scale = s.parse::<f64>()?;
+    anyhow::ensure!(
+        Duration::try_from_secs_f64(scale).is_ok(),
+        "must be non-negative and not larger than {}",
+        Duration::MAX.as_secs(),
+    );
+    Ok(scale)
+}
+
+/// `Match` given with `--match`. `None` accepts any output.
+#[derive(Debug, Clone)]
+pub struct MatchOverride(Option<Match>);
+
+impl FromStr for MatchOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let matching = match *s.splitn(2, ':').collect::<Vec<_>>() {
+            ["exact"] => Some(Match::Exact {
+                ignore_trailing_whitespace: false,
+                blank_lines: BlankLines::Keep,
+            }),
+            ["lines"] => Some(Match::Lines),
+            ["tokens"] => Some(Match::SplitWhitespace),
+            ["float"] => Some(Match::Float {
+                relative_error: None,
+                absolute_error: None,
+            }),
+            ["float", error] => {
+                let error = error.parse()?;
+                Some(Match::Float {
+                    relative_error: Some(error),
+                    absolute_error: Some(error),
+                })
+            }
+            ["any"] => None,
+            _ => anyhow::bail!(
+                "expected `exact`, `lines`, `tokens`, `float`, `float:<ERROR>`, or `any`"
+            ),
+        };
+        Ok(Self(matching))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_time_scale() {
+        assert_eq!(2.0, super::parse_time_scale("2").unwrap());
+        assert_eq!(0.0, super::parse_time_scale("0").unwrap());
+        assert!(super::parse_time_scale("-1").is_err());
+        assert!(super::parse_time_scale("inf").is_err());
+        assert!(super::parse_time_scale("NaN").is_err());
+        assert!(super::parse_time_scale("1e30").is_err());
+    }
+}
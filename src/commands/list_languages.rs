@@ -0,0 +1,76 @@
+use snowchains_core::{color_spec, web::PlatformKind};
+use std::{io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptListLanguages {
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    #[structopt(short, long, value_name("STRING"))]
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptListLanguages,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptListLanguages {
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let crate::config::LanguageNames { names, default } = crate::config::language_names(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+    )?;
+
+    for name in names {
+        if default.as_ref() == Some(&name) {
+            shell
+                .stdout
+                .set_color(color_spec!(Bold, Fg(Color::Green)))?;
+            write!(shell.stdout, "{}", name)?;
+            shell.stdout.reset()?;
+            writeln!(shell.stdout, " (default)")?;
+        } else {
+            writeln!(shell.stdout, "{}", name)?;
+        }
+    }
+
+    shell.stdout.flush()?;
+    Ok(())
+}
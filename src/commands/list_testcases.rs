@@ -0,0 +1,117 @@
+use snowchains_core::{
+    testsuite::{TestSuite, TesterArgs},
+    web::PlatformKind,
+};
+use std::{collections::HashSet, io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::WriteColor;
+
+#[derive(StructOpt, Debug)]
+pub struct OptListTestcases {
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptListTestcases,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptListTestcases {
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (
+        crate::config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        _,
+    ) = crate::config::target(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+    )?;
+
+    let test_suite_path = crate::config::test_suites(&cwd, config.as_deref())?.path(
+        service,
+        contest.as_deref(),
+        &problem,
+    )?;
+
+    match crate::judge::read_test_suite(&test_suite_path)? {
+        TestSuite::Batch(test_suite) => {
+            let test_cases = test_suite.load_test_cases(
+                test_suite_path.parent().unwrap_or(&test_suite_path),
+                None::<HashSet<String>>,
+                |_| Ok(vec![]),
+            )?;
+
+            for test_case in test_cases {
+                writeln!(shell.stdout, "{}", test_case.name.as_deref().unwrap_or(""))?;
+            }
+        }
+        TestSuite::Interactive(test_suite) => {
+            for (i, args) in test_suite.each_args.iter().enumerate() {
+                match args {
+                    TesterArgs::Positional(args) => {
+                        writeln!(shell.stdout, "{}: {}", i + 1, args.join(" "))
+                    }
+                    TesterArgs::Named(args) => writeln!(
+                        shell.stdout,
+                        "{}: {}",
+                        i + 1,
+                        args.iter()
+                            .map(|(k, v)| format!("{}={}", k, v))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                }?;
+            }
+
+            if !test_suite.each_args.is_empty() && !test_suite.example.is_empty() {
+                writeln!(shell.stdout)?;
+            }
+            crate::judge::print_example(&mut shell.stdout, &test_suite.example)?;
+        }
+        TestSuite::Unsubmittable => writeln!(shell.stdout, "(unsubmittable)")?,
+    }
+
+    shell.stdout.flush()?;
+    Ok(())
+}
@@ -25,8 +25,13 @@ pub struct OptLogin {
     )]
     pub color: crate::ColorChoice,
 
+    /// Imports the cookies from `cookies.sqlite` of Firefox instead of logging in
+    #[cfg(feature = "browser-cookies")]
+    #[structopt(long, value_name("PATH"))]
+    pub cookies_from_firefox: Option<std::path::PathBuf>,
+
     /// Target platform
-    #[structopt(possible_values(&["atcoder", "codeforces"]))]
+    #[structopt(possible_values(PlatformKind::KEBAB_CASE_VARIANTS))]
     pub service: PlatformKind,
 }
 
@@ -48,14 +53,24 @@ pub(crate) fn run(
     let OptLogin {
         json,
         color: _,
+        #[cfg(feature = "browser-cookies")]
+        cookies_from_firefox,
         service,
     } = opt;
 
-    let crate::Context { cwd: _, mut shell } = ctx;
+    #[cfg(feature = "browser-cookies")]
+    if let Some(db) = cookies_from_firefox {
+        let crate::Context { cwd, mut shell } = ctx;
+        return import_firefox_cookies(service, &cwd.join(db), json, &mut shell.stdout);
+    }
+
+    let crate::Context { cwd, mut shell } = ctx;
 
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let headers = crate::config::http_headers_or_default(&cwd, service)?;
+    let tls = crate::config::tls_or_default(&cwd)?;
 
     let outcome = match service {
         PlatformKind::Atcoder => {
@@ -71,6 +86,8 @@ pub(crate) fn run(
                 credentials,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
@@ -86,10 +103,14 @@ pub(crate) fn run(
                 credentials,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
-        PlatformKind::Yukicoder => unreachable!("should be filtered by `possible_values`"),
+        PlatformKind::Yukicoder => {
+            crate::web::credentials::yukicoder_login(&mut shell).map(|(_, outcome)| outcome)
+        }
     }?;
 
     let message = if json {
@@ -105,3 +126,44 @@ pub(crate) fn run(
     writeln!(shell.stdout, "{}", message)?;
     shell.stdout.flush().map_err(Into::into)
 }
+
+#[cfg(feature = "browser-cookies")]
+fn import_firefox_cookies(
+    service: PlatformKind,
+    db: &std::path::Path,
+    json: bool,
+    mut stdout: impl Write,
+) -> anyhow::Result<()> {
+    let mut cookie_storage =
+        CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+
+    let url = match service {
+        PlatformKind::Atcoder => "https://atcoder.jp/",
+        PlatformKind::Codeforces => "https://codeforces.com/",
+        PlatformKind::Yukicoder => {
+            anyhow::bail!("yukicoder uses an API key instead of cookies")
+        }
+    }
+    .parse()?;
+
+    let num_imported = crate::web::browser_cookies::import_firefox_cookies(
+        &mut cookie_storage.cookie_store,
+        db,
+        &url,
+    )?;
+    anyhow::ensure!(num_imported > 0, "No cookies for {} found", url);
+
+    (cookie_storage.on_update)(&cookie_storage.cookie_store)?;
+
+    let message = if json {
+        Outcome {
+            kind: snowchains_core::web::LoginOutcome::Success,
+        }
+        .to_json()
+    } else {
+        format!("Imported {} cookie(s).", num_imported)
+    };
+
+    writeln!(stdout, "{}", message)?;
+    stdout.flush().map_err(Into::into)
+}
@@ -1,10 +1,18 @@
+pub(crate) mod append_testcases;
+pub(crate) mod convert_testsuite;
+pub(crate) mod format_testsuite;
+pub(crate) mod history;
 pub(crate) mod init;
 pub(crate) mod judge;
+pub(crate) mod list_languages;
+pub(crate) mod list_testcases;
 pub(crate) mod login;
 pub(crate) mod participate;
+pub(crate) mod prepare;
 pub(crate) mod retrieve_languages;
 pub(crate) mod retrieve_submission_summaries;
 pub(crate) mod retrieve_testcases;
+pub(crate) mod stress;
 pub(crate) mod submit;
 pub(crate) mod watch_submissions;
 pub(crate) mod xtask;
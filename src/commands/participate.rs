@@ -55,14 +55,16 @@ pub(crate) fn run(
     let OptParticipate {
         json,
         color: _,
-        service: _,
+        service,
         contest,
     } = opt;
 
-    let crate::Context { cwd: _, mut shell } = ctx;
+    let crate::Context { cwd, mut shell } = ctx;
 
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let headers = crate::config::http_headers_or_default(&cwd, service)?;
+    let tls = crate::config::tls_or_default(&cwd)?;
 
     let kind = {
         let shell = RefCell::new(&mut shell);
@@ -80,6 +82,8 @@ pub(crate) fn run(
             credentials,
             cookie_storage,
             timeout,
+            headers,
+            tls,
             shell: &shell,
         })?
     };
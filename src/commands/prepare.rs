@@ -0,0 +1,405 @@
+use crate::{config, web::CaseConversions, OptRetrieveTestcases};
+use anyhow::bail;
+use itertools::Itertools as _;
+use snowchains_core::{color_spec, web::PlatformKind};
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Buffer, Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptPrepare {
+    /// Build in `Release` mode
+    #[structopt(long)]
+    pub release: bool,
+
+    /// Number of the problems to compile at once. Defaults to all of them
+    #[structopt(short, long, value_name("N"))]
+    pub jobs: Option<usize>,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Language name
+    #[structopt(short, long, value_name("STRING"))]
+    pub language: Option<String>,
+
+    /// Problem indexes (e.g. "a", "b", "c"). Defaults to all of the problems in the contest
+    #[structopt(short, long, value_name("STRING"))]
+    pub problems: Option<Vec<String>>,
+}
+
+enum Status {
+    Ready(String),
+    NoSource(String),
+    Failed(anyhow::Error),
+}
+
+/// Lists the problems and downloads the test suites for `prepare`.
+pub(crate) trait Download {
+    fn problem_indexes(
+        &mut self,
+        config: Option<&Path>,
+        service: Option<PlatformKind>,
+        contest: Option<&str>,
+        ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+    ) -> anyhow::Result<Vec<String>>;
+
+    fn retrieve(
+        &mut self,
+        opt: OptRetrieveTestcases,
+        ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+    ) -> anyhow::Result<()>;
+}
+
+/// Communicates with the services.
+struct Online;
+
+impl Download for Online {
+    fn problem_indexes(
+        &mut self,
+        config: Option<&Path>,
+        service: Option<PlatformKind>,
+        contest: Option<&str>,
+        ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+    ) -> anyhow::Result<Vec<String>> {
+        crate::commands::retrieve_testcases::problem_indexes(config, service, contest, ctx)
+    }
+
+    fn retrieve(
+        &mut self,
+        opt: OptRetrieveTestcases,
+        ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+    ) -> anyhow::Result<()> {
+        crate::commands::retrieve_testcases::retrieve(opt, ctx).map(drop)
+    }
+}
+
+/// `offline` is `--offline`, for which the test suites already downloaded are used.
+pub(crate) fn run(
+    opt: OptPrepare,
+    offline: bool,
+    ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+) -> anyhow::Result<()> {
+    run_with(opt, offline, ctx, Online)
+}
+
+/// `run` with the services replaced with `download`.
+pub(crate) fn run_with(
+    opt: OptPrepare,
+    offline: bool,
+    ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+    mut download: impl Download,
+) -> anyhow::Result<()> {
+    let OptPrepare {
+        release,
+        jobs,
+        config,
+        color,
+        service,
+        contest,
+        language,
+        problems,
+    } = opt;
+
+    let crate::Context { cwd, shell } = ctx;
+
+    let crate::shell::Shell {
+        mut stdin,
+        mut stdout,
+        mut stderr,
+        stderr_tty,
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+    } = shell;
+
+    if jobs == Some(0) {
+        bail!("`--jobs` must be at least 1");
+    }
+
+    // The problems are listed first, and only the test suites that do not exist yet are
+    // downloaded, so that `prepare` can be run again after an interruption.
+    let problems = match problems {
+        Some(problems) => problems,
+        None => {
+            if offline {
+                bail!(
+                    "Listing the problems communicates with the service, which `--offline` \
+                     forbids. Specify them with `--problems`",
+                );
+            }
+
+            download.problem_indexes(
+                config.as_deref(),
+                service,
+                contest.as_deref(),
+                crate::Context {
+                    cwd: cwd.clone(),
+                    shell: crate::shell::Shell {
+                        stdin: stdin.as_mut(),
+                        stdout: &mut stdout,
+                        stderr: &mut stderr,
+                        stderr_tty,
+                        stdin_process_redirection,
+                        stdout_process_redirection,
+                        stderr_process_redirection,
+                    },
+                },
+            )?
+        }
+    };
+
+    let missing = missing_test_suites(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        &problems,
+    )?;
+
+    if !missing.is_empty() {
+        if offline {
+            bail!(
+                "The test suites for [{}] have not been downloaded, which `--offline` forbids",
+                missing.iter().format(", "),
+            );
+        }
+
+        download.retrieve(
+            OptRetrieveTestcases {
+                full: false,
+                max_download_size: "1GiB".parse()?,
+                json: false,
+                tester_stub: false,
+                quiet: false,
+                compact: false,
+                output_dir: None,
+                problem_dir: false,
+                config: config.clone(),
+                color,
+                service,
+                contest: contest.clone(),
+                problems: Some(missing),
+                problems_from: None,
+                urls: vec![],
+                min_cases: None,
+                retry_login: false,
+                strict_scrape: false,
+                statement_language: None,
+                statement: false,
+                editorial: false,
+                dry_run: false,
+            },
+            crate::Context {
+                cwd: cwd.clone(),
+                shell: crate::shell::Shell {
+                    stdin: stdin.as_mut(),
+                    stdout: &mut stdout,
+                    stderr: &mut stderr,
+                    stderr_tty,
+                    stdin_process_redirection,
+                    stdout_process_redirection,
+                    stderr_process_redirection,
+                },
+            },
+        )?;
+    }
+
+    let mode = if release {
+        config::Mode::Release
+    } else {
+        config::Mode::Debug
+    };
+
+    // `snowchains.dhall` is evaluated here, in the thread with the large stack.
+    let languages = problems
+        .iter()
+        .map(|problem| {
+            let language = (|| -> anyhow::Result<_> {
+                let (_, language_name, lang, base_dir) = config::target_and_language(
+                    &cwd,
+                    config.as_deref(),
+                    service,
+                    contest.as_deref(),
+                    Some(problem),
+                    language.as_deref(),
+                    None,
+                    mode,
+                )?;
+                lang.validate(&base_dir)?;
+                Ok((language_name, lang, base_dir))
+            })();
+            (problem, language)
+        })
+        .collect::<Vec<_>>();
+
+    let supports_color = stderr.supports_color();
+    let redirections = (
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+    );
+
+    let mut statuses = vec![];
+
+    for chunk in languages.chunks(jobs.unwrap_or(languages.len()).max(1)) {
+        let outputs = crossbeam_utils::thread::scope(|scope| {
+            chunk
+                .iter()
+                .map(|(problem, language)| {
+                    scope.spawn(move |_| {
+                        let mut output = if supports_color {
+                            Buffer::ansi()
+                        } else {
+                            Buffer::no_color()
+                        };
+
+                        let status = match language {
+                            Err(err) => Status::Failed(anyhow::anyhow!("{:#}", err)),
+                            Ok((_, lang, base_dir)) if !is_file(base_dir, &lang.src) => {
+                                Status::NoSource(lang.src.clone())
+                            }
+                            Ok((language_name, lang, base_dir)) => {
+                                match crate::judge::compile(
+                                    &mut output,
+                                    base_dir,
+                                    &lang.src,
+                                    lang.transpile.as_ref(),
+                                    lang.compile.as_ref(),
+                                    redirections,
+                                ) {
+                                    Ok(()) => Status::Ready(language_name.clone()),
+                                    Err(err) => Status::Failed(err),
+                                }
+                            }
+                        };
+
+                        (*problem, output, status)
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        for (problem, output, status) in outputs {
+            if !output.is_empty() {
+                stderr.set_color(color_spec!(Bold))?;
+                write!(stderr, "{}:", problem)?;
+                stderr.reset()?;
+                writeln!(stderr)?;
+                stderr.write_all(output.as_slice())?;
+            }
+            statuses.push((problem, status));
+        }
+        stderr.flush()?;
+    }
+
+    writeln!(stderr)?;
+
+    let mut failed = vec![];
+
+    for (problem, status) in &statuses {
+        stderr.set_color(color_spec!(Bold))?;
+        write!(stderr, "{}:", problem)?;
+        stderr.reset()?;
+        write!(stderr, " ")?;
+
+        match status {
+            Status::Ready(language_name) => {
+                stderr.set_color(color_spec!(Bold, Fg(Color::Green)))?;
+                write!(stderr, "Ready")?;
+                stderr.reset()?;
+                writeln!(stderr, " ({})", language_name)?;
+            }
+            Status::NoSource(src) => {
+                stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                write!(stderr, "No source file")?;
+                stderr.reset()?;
+                writeln!(stderr, " ({})", src)?;
+            }
+            Status::Failed(err) => {
+                stderr.set_color(color_spec!(Bold, Fg(Color::Red)))?;
+                write!(stderr, "Failed")?;
+                stderr.reset()?;
+                writeln!(stderr, ": {}", err)?;
+                failed.push(problem);
+            }
+        }
+    }
+    stderr.flush()?;
+
+    if !failed.is_empty() {
+        bail!("Could not prepare [{}]", failed.iter().format(", "));
+    }
+    Ok(())
+}
+
+/// Returns the problems whose test suites do not exist.
+fn missing_test_suites(
+    cwd: &Path,
+    config: Option<&Path>,
+    service: Option<PlatformKind>,
+    contest: Option<&str>,
+    problems: &[String],
+) -> anyhow::Result<Vec<String>> {
+    let test_suites = config::test_suites(cwd, config)?;
+
+    let mut missing = vec![];
+
+    for problem in problems {
+        let (
+            config::Target {
+                service, contest, ..
+            },
+            _,
+        ) = config::target(cwd, config, service, contest, Some(problem))?;
+
+        let path = test_suites.path(
+            service,
+            contest.as_deref(),
+            &CaseConversions::new(problem).kebab,
+        )?;
+
+        if !path.exists() {
+            missing.push(problem.clone());
+        }
+    }
+
+    Ok(missing)
+}
+
+fn is_file(base_dir: &Path, src: &str) -> bool {
+    let src = Path::new(src);
+    base_dir
+        .join(src.strip_prefix(".").unwrap_or(src))
+        .is_file()
+}
@@ -66,70 +66,76 @@ pub(crate) fn run(
 
     let (detected_target, _) = crate::config::detect_target(&cwd, config.as_deref())?;
 
-    let service = service
-        .map(Ok)
-        .or_else(|| detected_target.parse_service().transpose())
-        .with_context(|| {
-            "`service` was not detected. To specify it, add `--service` to the arguments"
-        })??;
-
-    let contest = contest.or(detected_target.contest);
+    let service = detected_target.service(service, contest.as_deref())?;
+
+    let contest = contest
+        .or(detected_target.contest)
+        .map(|contest| crate::config::normalize_contest(service, &contest))
+        .transpose()?;
     let problem = problem.or(detected_target.problem);
 
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
-
-    let outcome =
-        match service {
-            PlatformKind::Atcoder => {
-                let shell = RefCell::new(&mut shell);
-
-                let target = AtcoderRetrieveLanguagesTarget {
-                    contest_and_problem: contest.and_then(|c| problem.map(|p| (c, p))),
-                };
-
-                let credentials = AtcoderRetrieveLanguagesCredentials {
-                    username_and_password:
-                        &mut crate::web::credentials::atcoder_username_and_password(&shell),
-                };
-
-                Atcoder::exec(RetrieveLanguages {
-                    target,
-                    credentials,
-                    cookie_storage,
-                    timeout,
-                    shell: &shell,
-                })
-            }
-            PlatformKind::Codeforces => {
-                let shell = RefCell::new(&mut shell);
-
-                let target = CodeforcesRetrieveLanguagesTarget {
-                    contest: contest.with_context(|| "`contest` is required for Codeforces")?,
-                };
-
-                let credentials = CodeforcesRetrieveLanguagesCredentials {
-                    username_and_password:
-                        &mut crate::web::credentials::atcoder_username_and_password(&shell),
-                };
-
-                Codeforces::exec(RetrieveLanguages {
-                    target,
-                    credentials,
-                    cookie_storage,
-                    timeout,
-                    shell: &shell,
-                })
-            }
-            PlatformKind::Yukicoder => Yukicoder::exec(RetrieveLanguages {
-                target: (),
-                credentials: (),
-                cookie_storage: (),
+    let headers = crate::config::http_headers(&cwd, config.as_deref(), service)?;
+    let tls = crate::config::tls(&cwd, config.as_deref())?;
+
+    let outcome = match service {
+        PlatformKind::Atcoder => {
+            let shell = RefCell::new(&mut shell);
+
+            let target = AtcoderRetrieveLanguagesTarget {
+                contest_and_problem: contest.and_then(|c| problem.map(|p| (c, p))),
+            };
+
+            let credentials = AtcoderRetrieveLanguagesCredentials {
+                username_and_password: &mut crate::web::credentials::atcoder_username_and_password(
+                    &shell,
+                ),
+            };
+
+            Atcoder::exec(RetrieveLanguages {
+                target,
+                credentials,
+                cookie_storage,
                 timeout,
-                shell: &mut shell,
-            }),
-        }?;
+                headers,
+                tls,
+                shell: &shell,
+            })
+        }
+        PlatformKind::Codeforces => {
+            let shell = RefCell::new(&mut shell);
+
+            let target = CodeforcesRetrieveLanguagesTarget {
+                contest: contest.with_context(|| "`contest` is required for Codeforces")?,
+            };
+
+            let credentials = CodeforcesRetrieveLanguagesCredentials {
+                username_and_password:
+                    &mut crate::web::credentials::codeforces_username_and_password(&shell),
+            };
+
+            Codeforces::exec(RetrieveLanguages {
+                target,
+                credentials,
+                cookie_storage,
+                timeout,
+                headers,
+                tls,
+                shell: &shell,
+            })
+        }
+        PlatformKind::Yukicoder => Yukicoder::exec(RetrieveLanguages {
+            target: (),
+            credentials: (),
+            cookie_storage: (),
+            timeout,
+            headers,
+            tls,
+            shell: &mut shell,
+        }),
+    }?;
 
     if json {
         writeln!(shell.stdout, "{}", outcome.to_json())
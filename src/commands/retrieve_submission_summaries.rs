@@ -51,17 +51,17 @@ pub(crate) fn run(
 
     let (detected_target, _) = crate::config::detect_target(&cwd, config.as_deref())?;
 
-    let service = service
-        .map(Ok)
-        .or_else(|| detected_target.parse_service().transpose())
-        .with_context(|| {
-            "`service` was not detected. To specify it, add `--service` to the arguments"
-        })??;
+    let service = detected_target.service(service, contest.as_deref())?;
 
-    let contest = contest.or(detected_target.contest);
+    let contest = contest
+        .or(detected_target.contest)
+        .map(|contest| crate::config::normalize_contest(service, &contest))
+        .transpose()?;
 
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let headers = crate::config::http_headers(&cwd, config.as_deref(), service)?;
+    let tls = crate::config::tls(&cwd, config.as_deref())?;
 
     match service {
         PlatformKind::Atcoder => {
@@ -82,6 +82,8 @@ pub(crate) fn run(
                     credentials,
                     cookie_storage,
                     timeout,
+                    headers,
+                    tls,
                     shell: &shell,
                 })?
             };
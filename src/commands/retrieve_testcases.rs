@@ -1,22 +1,28 @@
 use crate::web::CaseConversions;
-use anyhow::Context as _;
+use anyhow::{bail, Context as _};
+use az::SaturatingAs as _;
+use human_size::{Byte, Size};
+use itertools::Itertools as _;
 use maplit::btreeset;
 use serde::Serialize;
 use snowchains_core::{
     color_spec,
-    testsuite::{Additional, BatchTestSuite, TestSuite},
+    testsuite::{
+        Additional, BatchTestSuite, CheckerShell, InteractiveTestSuite, TestSuite, Tester,
+        TesterArgs,
+    },
     web::{
         Atcoder, AtcoderRetrieveFullTestCasesCredentials,
-        AtcoderRetrieveSampleTestCasesCredentials, Codeforces,
+        AtcoderRetrieveSampleTestCasesCredentials, AtcoderStatementLanguage, Codeforces,
         CodeforcesRetrieveSampleTestCasesCredentials, CookieStorage, PlatformKind,
-        ProblemsInContest, RetrieveFullTestCases, RetrieveTestCases, Yukicoder,
-        YukicoderRetrieveFullTestCasesCredentials, YukicoderRetrieveTestCasesTargets,
+        ProblemsInContest, RetrieveFullTestCases, RetrieveProblemIndexes, RetrieveTestCases,
+        Yukicoder, YukicoderRetrieveFullTestCasesCredentials, YukicoderRetrieveTestCasesTargets,
     },
 };
 use std::{
     cell::RefCell,
     io::{BufRead, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use structopt::StructOpt;
 use strum::VariantNames as _;
@@ -29,10 +35,34 @@ pub struct OptRetrieveTestcases {
     #[structopt(long)]
     pub full: bool,
 
+    /// Fails if any of the files of the full test cases is larger than this
+    #[structopt(long, value_name("SIZE"), default_value("1GiB"))]
+    pub max_download_size: Size,
+
     /// Prints JSON data
     #[structopt(long)]
     pub json: bool,
 
+    /// Writes stubs of testers for interactive problems
+    #[structopt(long)]
+    pub tester_stub: bool,
+
+    /// Prints only the number of the saved test suites instead of each of them
+    #[structopt(short, long)]
+    pub quiet: bool,
+
+    /// Writes each test case whose input and output are single short tokens in one line
+    #[structopt(long)]
+    pub compact: bool,
+
+    /// Directory to save the test suites in, instead of `testSuites` in `snowchains.dhall`
+    #[structopt(long, value_name("DIR"))]
+    pub output_dir: Option<PathBuf>,
+
+    /// Saves each test suite in `problems/<problem>/` next to `snowchains.dhall`
+    #[structopt(long, conflicts_with("output-dir"))]
+    pub problem_dir: bool,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -61,8 +91,74 @@ pub struct OptRetrieveTestcases {
     /// Problem indexes (e.g. "a", "b", "c")
     #[structopt(short, long, value_name("STRING"))]
     pub problems: Option<Vec<String>>,
+
+    /// Reads problem indexes from a file, one per line. Each line can be prefixed with the contest ID (e.g. "abc100 a")
+    #[structopt(long, value_name("PATH"), conflicts_with("problems"))]
+    pub problems_from: Option<PathBuf>,
+
+    /// URLs of the problems (e.g. "https://yukicoder.me/problems/no/3"). yukicoder only
+    #[structopt(
+        long = "url",
+        value_name("URL"),
+        number_of_values(1),
+        conflicts_with_all(&["contest", "problems", "problems-from", "dry-run"])
+    )]
+    pub urls: Vec<Url>,
+
+    /// Fails if any of the batch test suites has fewer test cases than this, after saving them
+    #[structopt(long, value_name("N"))]
+    pub min_cases: Option<usize>,
+
+    /// Logs in again and retries once if the session expires on the way (AtCoder only)
+    #[structopt(long)]
+    pub retry_login: bool,
+
+    /// Fails instead of warning when the problem pages are only partially scraped (AtCoder only)
+    #[structopt(long)]
+    pub strict_scrape: bool,
+
+    /// Language of the statements to look for the samples in first (AtCoder only). Defaults to `statementLanguage` in `snowchains.dhall`
+    #[structopt(
+        long,
+        value_name("LANG"),
+        possible_values(AtcoderStatementLanguage::VARIANTS)
+    )]
+    pub statement_language: Option<AtcoderStatementLanguage>,
+
+    /// Also saves the problem statements in Markdown next to the test suites (e.g. `a.md`)
+    #[structopt(long)]
+    pub statement: bool,
+
+    /// Also records the links to the editorials in the test suites as `editorial`. The editorials themselves are not downloaded
+    #[structopt(long)]
+    pub editorial: bool,
+
+    /// Prints where the test suites would be saved without downloading them. Requires the problems
+    #[structopt(
+        long,
+        conflicts_with_all(&["full", "json", "tester-stub", "min-cases", "statement", "editorial"])
+    )]
+    pub dry_run: bool,
 }
 
+static TESTER_STUB: &str = r#"#!/usr/bin/env python3
+# Tester for "{display_name}".
+#
+# The stdout of this script is connected to the stdin of your solution, and vice versa.
+# Exit with a non-zero code when the solution is wrong.
+# Each element of `each_args` in the test suite is given as the command line arguments.
+
+import sys
+
+
+def main() -> None:
+    sys.exit('the tester is not implemented yet')
+
+
+if __name__ == '__main__':
+    main()
+"#;
+
 #[derive(Debug, Serialize)]
 struct Outcome {
     contest: Option<OutcomeContest>,
@@ -98,42 +194,246 @@ struct OutcomeProblemTestSuite {
     content: TestSuite,
 }
 
+#[derive(Debug, PartialEq)]
+struct ProblemList {
+    contest: Option<String>,
+    problems: Vec<String>,
+}
+
+impl ProblemList {
+    /// Parses lines of `PROBLEM` or `CONTEST PROBLEM`. Blank lines and `#` comments are ignored.
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut contest = None::<String>;
+        let mut problems = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+
+            match *line.split_whitespace().collect::<Vec<_>>() {
+                [] => {}
+                [problem] => problems.push(problem.to_owned()),
+                [contest_in_line, problem] => {
+                    match &contest {
+                        Some(contest) if contest != contest_in_line => bail!(
+                            "line {}: found {:?} but expected {:?}. Only one contest can be \
+                             retrieved at once",
+                            i + 1,
+                            contest_in_line,
+                            contest,
+                        ),
+                        Some(_) => {}
+                        None => contest = Some(contest_in_line.to_owned()),
+                    }
+                    problems.push(problem.to_owned());
+                }
+                _ => bail!("line {}: expected `PROBLEM` or `CONTEST PROBLEM`", i + 1),
+            }
+        }
+
+        Ok(Self { contest, problems })
+    }
+}
+
 pub(crate) fn run(
     opt: OptRetrieveTestcases,
     ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
 ) -> anyhow::Result<()> {
+    retrieve(opt, ctx).map(drop)
+}
+
+/// Saves the test suites and returns the indexes of the problems in kebab-case.
+/// Lists the indexes of the problems in the contest, in kebab case.
+pub(crate) fn problem_indexes(
+    config: Option<&Path>,
+    service: Option<PlatformKind>,
+    contest: Option<&str>,
+    ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+) -> anyhow::Result<Vec<String>> {
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (detected_target, _) = crate::config::detect_target(&cwd, config)?;
+
+    let service = detected_target.service(service, contest)?;
+
+    let contest = contest
+        .map(ToOwned::to_owned)
+        .or(detected_target.contest)
+        .map(|contest| crate::config::normalize_contest(service, &contest))
+        .transpose()?
+        .with_context(|| "`contest` is required to list the problems")?;
+
+    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let headers = crate::config::http_headers(&cwd, config, service)?;
+    let tls = crate::config::tls(&cwd, config)?;
+
+    let indexes = match service {
+        PlatformKind::Atcoder => {
+            let shell = RefCell::new(&mut shell);
+
+            let credentials = AtcoderRetrieveSampleTestCasesCredentials {
+                username_and_password: &mut crate::web::credentials::atcoder_username_and_password(
+                    &shell,
+                ),
+                retry_login: false,
+            };
+
+            Atcoder::exec(RetrieveProblemIndexes {
+                contest,
+                credentials,
+                cookie_storage,
+                timeout,
+                headers,
+                tls,
+                shell: &shell,
+            })
+        }
+        PlatformKind::Codeforces => {
+            let shell = RefCell::new(&mut shell);
+
+            let credentials = CodeforcesRetrieveSampleTestCasesCredentials {
+                username_and_password:
+                    &mut crate::web::credentials::codeforces_username_and_password(&shell),
+            };
+
+            Codeforces::exec(RetrieveProblemIndexes {
+                contest,
+                credentials,
+                cookie_storage,
+                timeout,
+                headers,
+                tls,
+                shell: &shell,
+            })
+        }
+        PlatformKind::Yukicoder => Yukicoder::exec(RetrieveProblemIndexes {
+            contest,
+            credentials: (),
+            cookie_storage: (),
+            timeout,
+            headers,
+            tls,
+            shell: RefCell::new(&mut shell),
+        }),
+    }?;
+
+    Ok(indexes
+        .into_iter()
+        .map(|index| CaseConversions::new(index).kebab)
+        .collect())
+}
+
+pub(crate) fn retrieve(
+    opt: OptRetrieveTestcases,
+    ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+) -> anyhow::Result<Vec<String>> {
     let OptRetrieveTestcases {
         full,
+        max_download_size,
         json,
+        tester_stub,
+        quiet,
+        compact,
+        output_dir,
+        problem_dir,
         config,
         color: _,
         service,
         contest,
         problems,
+        problems_from,
+        urls,
+        min_cases,
+        retry_login,
+        strict_scrape,
+        statement_language,
+        statement,
+        editorial,
+        dry_run,
     } = opt;
 
     let crate::Context { cwd, mut shell } = ctx;
 
+    let (contest, problems) = if let Some(path) = problems_from {
+        let path = cwd.join(path);
+        let list = ProblemList::parse(&crate::fs::read_to_string(&path)?)
+            .with_context(|| format!("Could not parse {}", path.display()))?;
+        match (contest, list.contest) {
+            (Some(contest), Some(contest_in_list)) if contest != contest_in_list => {
+                bail!(
+                    "`--contest` is {:?}, but {} is for {:?}",
+                    contest,
+                    path.display(),
+                    contest_in_list,
+                );
+            }
+            (contest, contest_in_list) => (contest.or(contest_in_list), Some(list.problems)),
+        }
+    } else {
+        (contest, problems)
+    };
+
     let (detected_target, workspace) = crate::config::detect_target(&cwd, config.as_deref())?;
+    let test_suites = if let Some(output_dir) = output_dir {
+        crate::config::TestSuites::output_dir(&cwd, &output_dir)?
+    } else if problem_dir {
+        crate::config::TestSuites::problem_dir(&cwd, config.as_deref())?
+    } else {
+        crate::config::test_suites(&cwd, config.as_deref())?
+    };
+    let line_ending = crate::config::test_suite_line_ending(&cwd, config.as_deref())?;
+
+    let service = service.or_else(|| {
+        let yukicoder =
+            !urls.is_empty() && urls.iter().all(|url| url.domain() == Some("yukicoder.me"));
+        yukicoder.then_some(PlatformKind::Yukicoder)
+    });
+    let service = detected_target.service(service, contest.as_deref())?;
 
-    let service = service
-        .map(Ok)
-        .or_else(|| detected_target.parse_service().transpose())
-        .with_context(|| {
-            "`service` was not detected. To specify it, add `--service` to the arguments"
-        })??;
+    if !urls.is_empty() && service != PlatformKind::Yukicoder {
+        bail!("`--url` is only supported for yukicoder");
+    }
+
+    if strict_scrape && service != PlatformKind::Atcoder {
+        bail!("`--strict-scrape` is only supported for AtCoder");
+    }
 
-    let contest = contest.or(detected_target.contest);
+    let contest = contest
+        .or(detected_target.contest)
+        .filter(|_| urls.is_empty())
+        .map(|contest| crate::config::normalize_contest(service, &contest))
+        .transpose()?;
 
     let problems = match (problems.as_deref().unwrap_or(&[]), &detected_target.problem) {
+        _ if !urls.is_empty() => None,
         ([], None) => None,
         ([], Some(problem)) => Some(btreeset!(problem.clone())),
         (problems, _) => Some(problems.iter().cloned().collect()),
     };
 
+    if dry_run {
+        // Listing the problems of a contest would need the network, so they must be given.
+        let problems = problems.with_context(|| {
+            "`--dry-run` requires the problems. Specify them with `--problems` or `--problems-from`"
+        })?;
+
+        let mut indexes = vec![];
+        for problem in problems {
+            let index = CaseConversions::new(problem);
+            let path = test_suites.path(service, contest.as_deref(), &index.kebab)?;
+            writeln!(shell.stdout, "{}: {}", index.original, path.display())?;
+            indexes.push(index.kebab);
+        }
+        shell.stdout.flush()?;
+        return Ok(indexes);
+    }
+
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let max_download_size = Some(max_download_size.into::<Byte>().value().saturating_as());
+    let headers = crate::config::http_headers(&cwd, config.as_deref(), service)?;
+    let tls = crate::config::tls(&cwd, config.as_deref())?;
 
     let outcome = match service {
         PlatformKind::Atcoder => {
@@ -150,6 +450,7 @@ pub(crate) fn run(
                 username_and_password: &mut crate::web::credentials::atcoder_username_and_password(
                     &shell,
                 ),
+                retry_login,
             };
 
             let full = if full {
@@ -157,6 +458,7 @@ pub(crate) fn run(
                     credentials: AtcoderRetrieveFullTestCasesCredentials {
                         dropbox_access_token: crate::web::credentials::dropbox_access_token()?,
                     },
+                    max_download_size,
                 })
             } else {
                 None
@@ -166,8 +468,17 @@ pub(crate) fn run(
                 targets,
                 credentials,
                 full,
+                strict: strict_scrape,
+                statement,
+                statement_language: match statement_language {
+                    Some(statement_language) => statement_language,
+                    None => crate::config::statement_language(&cwd, config.as_deref())?,
+                },
+                editorial,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
@@ -190,13 +501,21 @@ pub(crate) fn run(
                 targets,
                 credentials,
                 full: None,
+                strict: false,
+                statement,
+                statement_language: Default::default(),
+                editorial,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
         PlatformKind::Yukicoder => {
-            let targets = if let Some(contest) = &contest {
+            let targets = if !urls.is_empty() {
+                YukicoderRetrieveTestCasesTargets::Urls(urls.into_iter().collect())
+            } else if let Some(contest) = &contest {
                 YukicoderRetrieveTestCasesTargets::Contest(contest.clone(), problems)
             } else {
                 let nos = problems
@@ -213,6 +532,7 @@ pub(crate) fn run(
                     credentials: YukicoderRetrieveFullTestCasesCredentials {
                         api_key: crate::web::credentials::yukicoder_api_key(&mut shell)?,
                     },
+                    max_download_size,
                 })
             } else {
                 None
@@ -224,8 +544,14 @@ pub(crate) fn run(
                 targets,
                 credentials: (),
                 full,
+                strict: false,
+                statement,
+                statement_language: Default::default(),
+                editorial,
                 cookie_storage: (),
                 timeout,
+                headers,
+                tls,
                 shell,
             })
         }
@@ -257,6 +583,8 @@ pub(crate) fn run(
         problems: vec![],
     };
 
+    let mut nums_batch_cases = vec![];
+
     for snowchains_core::web::RetrieveTestCasesOutcomeProblem {
         index,
         url,
@@ -264,18 +592,13 @@ pub(crate) fn run(
         display_name,
         mut test_suite,
         text_files,
+        statement,
         ..
     } in outcome.problems
     {
         let index = CaseConversions::new(index);
 
-        let path = workspace
-            .join(".snowchains")
-            .join("tests")
-            .join(service.to_kebab_case_str())
-            .join(contest.as_deref().unwrap_or(""))
-            .join(&index.kebab)
-            .with_extension("yml");
+        let path = test_suites.path(service, contest.as_deref(), &index.kebab)?;
 
         let txt_path = |dir_file_name: &str, txt_file_name: &str| -> _ {
             path.with_file_name(&index.kebab)
@@ -287,9 +610,9 @@ pub(crate) fn run(
         for (name, snowchains_core::web::RetrieveTestCasesOutcomeProblemTextFiles { r#in, out }) in
             &text_files
         {
-            crate::fs::write(txt_path("in", name), &r#in, true)?;
+            crate::fs::write_atomically(txt_path("in", name), &r#in, true)?;
             if let Some(out) = out {
-                crate::fs::write(txt_path("out", name), out, true)?;
+                crate::fs::write_atomically(txt_path("out", name), out, true)?;
             }
         }
 
@@ -307,48 +630,94 @@ pub(crate) fn run(
             }
         }
 
-        crate::fs::write(&path, test_suite.to_yaml_pretty(), true)?;
+        let tester_stub_path = if tester_stub {
+            write_tester_stub(
+                &mut test_suite,
+                &path.with_file_name(&index.kebab),
+                &workspace,
+                &display_name,
+            )?
+        } else {
+            None
+        };
 
-        shell.stderr.set_color(color_spec!(Bold))?;
-        write!(shell.stderr, "{}:", index.original)?;
-        shell.stderr.reset()?;
+        let yaml = if compact {
+            test_suite.to_yaml_compact_with(line_ending)
+        } else {
+            test_suite.to_yaml_pretty_with(line_ending)
+        };
+        crate::fs::write_atomically(&path, yaml, true)?;
 
-        write!(shell.stderr, " Saved to ")?;
+        if let Some(statement) = &statement {
+            crate::fs::write_atomically(path.with_extension("md"), statement, true)?;
+        }
 
-        shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
-        if text_files.is_empty() {
-            write!(shell.stderr, "{}", path.display())
-        } else {
-            write!(
-                shell.stderr,
-                "{}",
-                path.with_file_name(format!("{{{index}.yml, {index}/}}", index = index.kebab))
-                    .display(),
-            )
-        }?;
-        shell.stderr.reset()?;
+        if !quiet {
+            shell.stderr.set_color(color_spec!(Bold))?;
+            write!(shell.stderr, "{}:", index.original)?;
+            shell.stderr.reset()?;
 
-        write!(shell.stderr, " (")?;
+            write!(shell.stderr, " Saved to ")?;
 
-        let (msg, color) = match &test_suite {
-            TestSuite::Batch(BatchTestSuite { cases, .. }) => {
-                match cases.len() + text_files.len() {
-                    0 => ("no test cases".to_owned(), Color::Yellow),
-                    1 => ("1 test case".to_owned(), Color::Green),
-                    n => (format!("{} test cases", n), Color::Green),
+            shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+            if text_files.is_empty() {
+                write!(shell.stderr, "{}", path.display())
+            } else {
+                write!(
+                    shell.stderr,
+                    "{}",
+                    path.with_file_name(format!("{{{index}.yml, {index}/}}", index = index.kebab))
+                        .display(),
+                )
+            }?;
+            shell.stderr.reset()?;
+
+            write!(shell.stderr, " (")?;
+
+            let (msg, color) = match &test_suite {
+                TestSuite::Batch(BatchTestSuite { cases, .. }) => {
+                    match cases.len() + text_files.len() {
+                        0 => ("no test cases".to_owned(), Color::Yellow),
+                        1 => ("1 test case".to_owned(), Color::Green),
+                        n => (format!("{} test cases", n), Color::Green),
+                    }
                 }
+                TestSuite::Interactive(InteractiveTestSuite { tester: None, .. }) => {
+                    ("interactive problem, no tester".to_owned(), Color::Yellow)
+                }
+                TestSuite::Interactive(_) => ("interactive problem".to_owned(), Color::Yellow),
+                TestSuite::Unsubmittable => ("unsubmittable problem".to_owned(), Color::Yellow),
+            };
+
+            shell.stderr.set_color(color_spec!(Fg(color)))?;
+            write!(shell.stderr, "{}", msg)?;
+            shell.stderr.reset()?;
+
+            writeln!(shell.stderr, ")")?;
+
+            if let TestSuite::Interactive(InteractiveTestSuite { example, .. }) = &test_suite {
+                crate::judge::print_example(&mut shell.stderr, example)?;
             }
-            TestSuite::Interactive(_) => ("interactive problem".to_owned(), Color::Yellow),
-            TestSuite::Unsubmittable => ("unsubmittable problem".to_owned(), Color::Yellow),
-        };
+        }
 
-        shell.stderr.set_color(color_spec!(Fg(color)))?;
-        write!(shell.stderr, "{}", msg)?;
-        shell.stderr.reset()?;
+        if let Some(tester_stub_path) = &tester_stub_path {
+            write!(
+                shell.stderr,
+                "{}: Wrote a stub of the tester to ",
+                index.original
+            )?;
+            shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+            write!(shell.stderr, "{}", tester_stub_path.display())?;
+            shell.stderr.reset()?;
+            writeln!(shell.stderr, ". Implement it before judging")?;
+        }
 
-        writeln!(shell.stderr, ")")?;
         shell.stderr.flush()?;
 
+        if let TestSuite::Batch(BatchTestSuite { cases, .. }) = &test_suite {
+            nums_batch_cases.push((index.original.clone(), cases.len() + text_files.len()));
+        }
+
         acc.problems.push(OutcomeProblem {
             index,
             url,
@@ -364,10 +733,201 @@ pub(crate) fn run(
         });
     }
 
+    if quiet {
+        writeln!(shell.stderr, "Saved {} test suite(s).", acc.problems.len())?;
+        shell.stderr.flush()?;
+    }
+
     if json {
         writeln!(shell.stdout, "{}", acc.to_json())?;
         shell.stdout.flush()?;
     }
 
+    if let Some(min_cases) = min_cases {
+        check_min_cases(min_cases, &nums_batch_cases)?;
+    }
+
+    Ok(acc
+        .problems
+        .into_iter()
+        .map(|OutcomeProblem { index, .. }| index.kebab)
+        .collect())
+}
+
+/// Writes `tester.py` into `dir` and points the tester of `test_suite` to it, if `test_suite` is
+/// an interactive one without a tester. Returns the path of the stub if so.
+///
+/// An existing `tester.py` is kept as it is.
+fn write_tester_stub(
+    test_suite: &mut TestSuite,
+    dir: &Path,
+    workspace: &Path,
+    display_name: &str,
+) -> anyhow::Result<Option<PathBuf>> {
+    let (tester, each_args) = match test_suite {
+        TestSuite::Interactive(InteractiveTestSuite {
+            tester: tester @ None,
+            each_args,
+            ..
+        }) => (tester, each_args),
+        _ => return Ok(None),
+    };
+
+    let tester_stub_path = dir.join("tester.py");
+
+    if !tester_stub_path.exists() {
+        crate::fs::write_atomically(
+            &tester_stub_path,
+            TESTER_STUB.replace("{display_name}", display_name),
+            true,
+        )?;
+    }
+
+    let rel_path = tester_stub_path
+        .strip_prefix(workspace)
+        .unwrap_or(&tester_stub_path)
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR, "/");
+
+    *tester = Some(Tester {
+        cmd: format!(
+            "python3 {} \"$@\"",
+            shell_escape::unix::escape(format!("./{}", rel_path).into()),
+        ),
+        shell: CheckerShell::Bash,
+    });
+
+    if each_args.is_empty() {
+        each_args.push(TesterArgs::Positional(vec![]));
+    }
+
+    Ok(Some(tester_stub_path))
+}
+
+/// Fails if any of `nums_cases` is less than `min_cases`.
+fn check_min_cases(min_cases: usize, nums_cases: &[(String, usize)]) -> anyhow::Result<()> {
+    let too_few = nums_cases
+        .iter()
+        .filter(|&&(_, num_cases)| num_cases < min_cases)
+        .collect::<Vec<_>>();
+
+    if !too_few.is_empty() {
+        bail!(
+            "Expected at least {} test case(s), but got fewer for [{}]",
+            min_cases,
+            too_few
+                .iter()
+                .format_with(", ", |(index, n), f| f(&format_args!(
+                    "{:?} ({})",
+                    index, n
+                ))),
+        );
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ProblemList;
+    use snowchains_core::testsuite::{
+        CheckerShell, InteractiveTestSuite, TestSuite, Tester, TesterArgs,
+    };
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn check_min_cases() {
+        assert_eq!(
+            r#"Expected at least 1 test case(s), but got fewer for ["B" (0)]"#,
+            super::check_min_cases(1, &[("A".to_owned(), 3), ("B".to_owned(), 0)])
+                .unwrap_err()
+                .to_string(),
+        );
+        assert!(super::check_min_cases(1, &[("A".to_owned(), 3)]).is_ok());
+        assert!(super::check_min_cases(3, &[("A".to_owned(), 3)]).is_ok());
+        assert!(super::check_min_cases(1, &[]).is_ok());
+    }
+
+    #[test]
+    fn write_tester_stub() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let dir = tempdir
+            .path()
+            .join("tests")
+            .join("yukicoder")
+            .join("no-100");
+
+        let mut test_suite = TestSuite::Interactive(InteractiveTestSuite {
+            description: None,
+            editorial: None,
+            timelimit: Some(Duration::from_secs(2)),
+            tester: None,
+            each_args: vec![],
+            example: vec![],
+        });
+
+        let stub_path =
+            super::write_tester_stub(&mut test_suite, &dir, tempdir.path(), "Reactive A")?;
+
+        assert_eq!(Some(dir.join("tester.py")), stub_path);
+        assert!(fs::read_to_string(dir.join("tester.py"))?.contains(r#"Tester for "Reactive A""#));
+        assert!(matches!(
+            &test_suite,
+            TestSuite::Interactive(InteractiveTestSuite {
+                tester: Some(Tester { cmd, shell: CheckerShell::Bash }),
+                each_args,
+                ..
+            }) if cmd == r#"python3 ./tests/yukicoder/no-100/tester.py "$@""#
+                && each_args == &[TesterArgs::Positional(vec![])]
+        ));
+
+        // Once the tester is set, the stub is not written again.
+        fs::write(dir.join("tester.py"), "# implemented\n")?;
+        assert_eq!(
+            None,
+            super::write_tester_stub(&mut test_suite, &dir, tempdir.path(), "Reactive A")?,
+        );
+        assert_eq!(
+            "# implemented\n",
+            fs::read_to_string(dir.join("tester.py"))?
+        );
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn parse_problem_list() {
+        let list = ProblemList::parse(
+            r#"# ABC100
+a
+  b  # later
+
+c
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ProblemList {
+                contest: None,
+                problems: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+            },
+            list,
+        );
+
+        let list = ProblemList::parse("abc100 a\n\n# skip\nabc100 d\n").unwrap();
+        assert_eq!(
+            ProblemList {
+                contest: Some("abc100".to_owned()),
+                problems: vec!["a".to_owned(), "d".to_owned()],
+            },
+            list,
+        );
+
+        assert!(ProblemList::parse("abc100 a\nabc101 a\n").is_err());
+        assert!(ProblemList::parse("abc100 a b\n").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::config;
+use human_size::Size;
+use snowchains_core::web::PlatformKind;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::WriteColor;
+
+#[derive(StructOpt, Debug)]
+pub struct OptStress {
+    /// Build in `Release` mode
+    #[structopt(long)]
+    pub release: bool,
+
+    /// Number of random inputs to try
+    #[structopt(long, value_name("N"), default_value("100"))]
+    pub iterations: u64,
+
+    /// First seed given to `generator`
+    #[structopt(long, value_name("N"), default_value("0"))]
+    pub seed: u64,
+
+    /// Display limit
+    #[structopt(long, value_name("SIZE"), default_value("4KiB"))]
+    pub display_limit: Size,
+
+    /// Path to the source file, instead of `src` in `snowchains.dhall`
+    #[structopt(long, value_name("PATH"))]
+    pub source: Option<PathBuf>,
+
+    /// Bash command of another solution to compare with, instead of `reference`. Can be repeated
+    #[structopt(long, value_name("COMMAND"), number_of_values(1))]
+    pub solver: Vec<String>,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Language name
+    #[structopt(short, long, value_name("STRING"))]
+    pub language: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptStress,
+    ctx: crate::Context<impl Sized, impl WriteColor, impl WriteColor>,
+) -> anyhow::Result<()> {
+    let OptStress {
+        release,
+        iterations,
+        seed,
+        display_limit,
+        source,
+        solver,
+        config,
+        color: _,
+        service,
+        contest,
+        language,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, shell } = ctx;
+
+    let progress_draw_target = shell.progress_draw_target();
+
+    let crate::shell::Shell {
+        stdout,
+        stderr,
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+        ..
+    } = shell;
+
+    let (
+        config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        _,
+        lang,
+        base_dir,
+    ) = config::target_and_language(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+        language.as_deref(),
+        source.as_deref(),
+        if release {
+            config::Mode::Release
+        } else {
+            config::Mode::Debug
+        },
+    )?;
+
+    let lang = if let Some(source) = source {
+        lang.with_src(config::source_path(&cwd, &source)?)?
+    } else {
+        lang
+    };
+
+    lang.validate(&base_dir)?;
+
+    let test_suite_path = config::test_suites(&cwd, config.as_deref())?.path(
+        service,
+        contest.as_deref(),
+        &problem,
+    )?;
+    let line_ending = config::test_suite_line_ending(&cwd, config.as_deref())?;
+
+    let config::Language {
+        src,
+        transpile,
+        compile,
+        run,
+        languageId: _,
+    } = lang;
+
+    let run_working_dir = config::run_working_directory(&cwd, config.as_deref(), &src)?;
+
+    crate::judge::stress(crate::judge::StressArgs {
+        stdout,
+        stderr,
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+        progress_draw_target,
+        base_dir,
+        run_working_dir,
+        test_suite_path,
+        line_ending,
+        src,
+        transpile,
+        compile,
+        run,
+        seeds: seed..seed.saturating_add(iterations),
+        display_limit,
+        solvers: solver,
+    })
+}
@@ -6,7 +6,7 @@ use snowchains_core::web::{
     PlatformKind, ProblemInContest, Submit, Yukicoder, YukicoderSubmitCredentials,
     YukicoderSubmitTarget,
 };
-use std::{cell::RefCell, env, io::BufRead, iter, path::PathBuf};
+use std::{cell::RefCell, env, io::BufRead, iter, path::PathBuf, time::Duration};
 use structopt::StructOpt;
 use strum::VariantNames as _;
 use termcolor::WriteColor;
@@ -17,6 +17,19 @@ pub struct OptSubmit {
     #[structopt(long)]
     pub no_watch: bool,
 
+    /// Waits until the submission is judged, and fails unless it is accepted
+    #[structopt(long, conflicts_with("no-watch"))]
+    pub wait: bool,
+
+    /// Maximum duration to wait for the verdict
+    #[structopt(
+        long,
+        value_name("DURATION"),
+        default_value("10min"),
+        parse(try_from_str = humantime::parse_duration)
+    )]
+    pub max_wait: Duration,
+
     /// Do not `compile` the code
     #[structopt(long)]
     pub no_judge: bool,
@@ -29,6 +42,10 @@ pub struct OptSubmit {
     #[structopt(long)]
     pub json: bool,
 
+    /// Logs in again and retries once if the session expires before submitting (AtCoder only)
+    #[structopt(long)]
+    pub retry_login: bool,
+
     /// Test for only the test cases
     #[structopt(long, value_name("NAME"))]
     pub testcases: Option<Vec<String>>,
@@ -37,6 +54,18 @@ pub struct OptSubmit {
     #[structopt(long, value_name("SIZE"), default_value("4KiB"))]
     pub display_limit: Size,
 
+    /// Path to the source file, instead of `src` in `snowchains.dhall`
+    #[structopt(long, value_name("PATH"))]
+    pub source: Option<PathBuf>,
+
+    /// Language ID to submit with, instead of `languageId` in `snowchains.dhall`
+    #[structopt(long, value_name("ID"))]
+    pub lang_id: Option<String>,
+
+    /// Prints what would be submitted without testing or submitting it
+    #[structopt(long, conflicts_with_all(&["wait", "json"]))]
+    pub dry_run: bool,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -76,11 +105,17 @@ pub(crate) fn run(
 ) -> anyhow::Result<()> {
     let OptSubmit {
         no_watch,
+        wait,
+        max_wait,
         no_judge,
         debug,
         json,
+        retry_login,
         testcases,
         display_limit,
+        source,
+        lang_id,
+        dry_run,
         config,
         color,
         service,
@@ -98,13 +133,8 @@ pub(crate) fn run(
             problem,
             mode: _,
         },
-        config::Language {
-            src,
-            transpile,
-            compile: _,
-            run: _,
-            languageId: language_id,
-        },
+        language_name,
+        lang,
         base_dir,
     ) = config::target_and_language(
         &cwd,
@@ -113,6 +143,7 @@ pub(crate) fn run(
         contest.as_deref(),
         problem.as_deref(),
         language.as_deref(),
+        source.as_deref(),
         if debug {
             config::Mode::Debug
         } else {
@@ -120,8 +151,41 @@ pub(crate) fn run(
         },
     )?;
 
+    let lang = if let Some(source) = &source {
+        lang.with_src(config::source_path(&cwd, source)?)?
+    } else {
+        lang
+    };
+
+    let config::Language {
+        src,
+        transpile,
+        compile: _,
+        run: _,
+        languageId: language_id,
+    } = lang;
+
+    if wait && service != PlatformKind::Atcoder {
+        bail!(
+            "`--wait` is not yet supported for {}",
+            service.to_kebab_case_str()
+        );
+    }
+
     let code = crate::fs::read_to_string(base_dir.join(&src))?;
-    let language_id = language_id.with_context(|| "Missing `languageId`")?;
+    let language_id = resolve_language_id(lang_id, language_id)?;
+
+    if dry_run {
+        writeln!(shell.stdout, "service: {}", service.to_kebab_case_str())?;
+        if let Some(contest) = &contest {
+            writeln!(shell.stdout, "contest: {}", contest)?;
+        }
+        writeln!(shell.stdout, "problem: {}", problem)?;
+        writeln!(shell.stdout, "language ID: {}", language_id)?;
+        writeln!(shell.stdout, "source: {}", base_dir.join(&src).display())?;
+        shell.stdout.flush()?;
+        return Ok(());
+    }
 
     if no_judge {
         if let Some(transpile) = &transpile {
@@ -145,6 +209,11 @@ pub(crate) fn run(
                 vec![]
             })
             .args(&["--display-limit", &display_limit.to_string()])
+            .args(if source.is_some() {
+                vec!["--source".to_owned(), src.clone()]
+            } else {
+                vec![]
+            })
             .arg("--config")
             .arg(base_dir.join("snowchains.dhall"))
             .args(&["--color", &color.to_string()])
@@ -168,10 +237,13 @@ pub(crate) fn run(
     }
 
     let watch_submission = !no_watch;
+    let watch_timeout = if wait { Some(max_wait) } else { None };
 
     let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let headers = crate::config::http_headers(&cwd, config.as_deref(), service)?;
+    let tls = crate::config::tls(&cwd, config.as_deref())?;
 
     let outcome = match service {
         PlatformKind::Atcoder => {
@@ -186,16 +258,21 @@ pub(crate) fn run(
                 username_and_password: &mut crate::web::credentials::atcoder_username_and_password(
                     &shell,
                 ),
+                retry_login,
             };
 
             Atcoder::exec(Submit {
                 target,
                 credentials,
                 language_id,
+                language_name: Some(language_name),
                 code,
                 watch_submission,
+                watch_timeout,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
@@ -221,10 +298,14 @@ pub(crate) fn run(
                 target,
                 credentials,
                 language_id,
+                language_name: Some(language_name),
                 code,
                 watch_submission,
+                watch_timeout,
                 cookie_storage,
                 timeout,
+                headers,
+                tls,
                 shell: &shell,
             })
         }
@@ -245,10 +326,14 @@ pub(crate) fn run(
                 target,
                 credentials,
                 language_id,
+                language_name: Some(language_name),
                 code,
                 watch_submission,
+                watch_timeout,
                 cookie_storage: (),
                 timeout,
+                headers,
+                tls,
                 shell,
             })
         }
@@ -259,5 +344,48 @@ pub(crate) fn run(
         shell.stdout.flush()?;
     }
 
+    if wait && !outcome.is_accepted() {
+        bail!(
+            "The submission was not accepted: {}",
+            outcome.verdict.as_deref().unwrap_or("unknown verdict"),
+        );
+    }
+
     Ok(())
 }
+
+/// Prefers `--lang-id` to `languageId`. For AtCoder, a warning is shown if the ID is not in the
+/// submission form, but it is submitted anyway.
+fn resolve_language_id(
+    cli_opt_lang_id: Option<String>,
+    language_id: Option<String>,
+) -> anyhow::Result<String> {
+    cli_opt_lang_id
+        .or(language_id)
+        .with_context(|| "Missing `languageId`. Specify with `--lang-id`")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn resolve_language_id() {
+        assert_eq!(
+            "5001",
+            super::resolve_language_id(Some("5001".to_owned()), Some("4050".to_owned())).unwrap(),
+        );
+        assert_eq!(
+            "5001",
+            super::resolve_language_id(Some("5001".to_owned()), None).unwrap(),
+        );
+        assert_eq!(
+            "4050",
+            super::resolve_language_id(None, Some("4050".to_owned())).unwrap(),
+        );
+        assert_eq!(
+            "Missing `languageId`. Specify with `--lang-id`",
+            super::resolve_language_id(None, None)
+                .unwrap_err()
+                .to_string(),
+        );
+    }
+}
@@ -2,34 +2,56 @@
 #![allow(redundant_semicolons)]
 
 use anyhow::{anyhow, bail, ensure, Context as _};
-use dhall::syntax::InterpolatedText;
+use dhall::syntax::{
+    Expr as DhallExpr, ExprKind, FilePrefix, Import, ImportMode, ImportTarget, InterpolatedText,
+    Scheme,
+};
 use heck::{CamelCase as _, KebabCase as _, MixedCase as _, SnakeCase as _};
 use indexmap::IndexMap;
 use itertools::Itertools as _;
 use maplit::hashmap;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::Deserialize;
 use serde_dhall::{SimpleType, StaticType};
-use snowchains_core::web::PlatformKind;
+use snowchains_core::{
+    testsuite::{CheckerShell, LineEnding, Tester},
+    web::{AtcoderStatementLanguage, PlatformKind, TlsConfig},
+};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     convert::Infallible,
-    fmt,
-    path::{Path, PathBuf},
+    env, fmt,
+    path::{self, Component, Path, PathBuf},
 };
 
+/// An error in `snowchains.dhall` or in finding it, other than ones from `serde_dhall`.
+#[derive(Debug)]
+pub(crate) struct ConfigError(String);
+
+impl From<&'_ str> for ConfigError {
+    fn from(msg: &'_ str) -> Self {
+        Self(msg.to_owned())
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
 pub(crate) fn detect_target(
     cwd: &Path,
     rel_path: Option<&Path>,
 ) -> anyhow::Result<(Detected, PathBuf)> {
     let path = find_snowchains_dhall(cwd, rel_path)?;
     let detected = Detected::load_and_eval(cwd, &path)?;
-    let dir = Path::new(&path)
-        .parent()
-        .unwrap_or_else(|| path.as_ref())
-        .to_owned();
-    Ok((detected, dir))
+    Ok((detected, path.dir()))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn target_and_language(
     cwd: &Path,
     rel_path: Option<&Path>,
@@ -37,282 +59,1324 @@ pub(crate) fn target_and_language(
     cli_opt_contest: Option<&str>,
     cli_opt_problem: Option<&str>,
     cli_opt_language: Option<&str>,
+    cli_opt_source: Option<&Path>,
     cli_opt_mode: Mode,
-) -> anyhow::Result<(Target, Language, PathBuf)> {
+) -> anyhow::Result<(Target, String, Language, PathBuf)> {
     let path = find_snowchains_dhall(cwd, rel_path)?;
 
-    let (target, language_name) = Detected::load_and_eval(cwd, &path)?.merge_with_cli_options(
+    let (mut target, language_name) = Detected::load_and_eval(cwd, &path)?.merge_with_cli_options(
         cli_opt_service,
         cli_opt_contest,
         cli_opt_problem,
         cli_opt_language,
         cli_opt_mode,
     )?;
+    target.resolve_problem_alias(&problem_aliases(&path)?);
 
-    let mut languages = serde_dhall::from_str(&format!(
-        "let target = {} let config = {} in config.languages target",
-        target.to_dhall_expr(),
-        path,
-    ))
-    .parse::<BTreeMap<String, Language>>()
-    .with_context(|| format!("Could not evaluate `{}`", path))?;
+    let mut languages = languages(&path, &target)?;
+
+    let language_name = match language_name {
+        Some(language_name) => language_name,
+        None => infer_language(&languages, &path.dir(), cli_opt_source)?,
+    };
 
     let expected_names = languages.keys().join(", ");
 
-    let language = languages.remove(&language_name).with_context(|| {
-        format!(
-            "The language `{}` not found. Expected one of [{}]",
-            language_name, expected_names,
-        )
-    })?;
+    let language = languages
+        .remove(&language_name)
+        .with_context(|| {
+            ConfigError(format!(
+                "The language `{}` not found. Expected one of [{}]",
+                language_name, expected_names,
+            ))
+        })?
+        .with_problem(&target.problem);
 
-    let dir = Path::new(&path)
-        .parent()
-        .unwrap_or_else(|| path.as_ref())
-        .to_owned();
+    Ok((target, language_name, language, path.dir()))
+}
+
+/// Infers the language from the extension of `source`, or if `source` is not given, from the `src`
+/// files that exist.
+fn infer_language(
+    languages: &BTreeMap<String, Language>,
+    base_dir: &Path,
+    source: Option<&Path>,
+) -> anyhow::Result<String> {
+    let candidates = languages
+        .iter()
+        .filter(|(_, Language { src, .. })| match source {
+            Some(source) => {
+                source.extension().is_some() && Path::new(src).extension() == source.extension()
+            }
+            None => base_dir.join(src).is_file(),
+        })
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
 
-    Ok((target, language, dir))
+    match *candidates {
+        [name] => Ok(name.clone()),
+        [] => {
+            Err(ConfigError::from("`language` was not detected. Specify with `--language`").into())
+        }
+        _ => Err(ConfigError(format!(
+            "`language` is ambiguous among [{}]. Specify with `--language`",
+            candidates.iter().format(", "),
+        ))
+        .into()),
+    }
 }
 
-pub(crate) fn xtask(cwd: &Path, rel_path: Option<&Path>, name: &str) -> anyhow::Result<Script> {
-    let path = find_snowchains_dhall(cwd, rel_path)?;
+/// Normalizes a contest ID for `service`, rejecting ones that cannot exist.
+///
+/// - AtCoder: lowercased. `chokudais*` is corrected to `chokudai_s*`.
+/// - Codeforces and yukicoder: must be an unsigned integer. For yukicoder, `problems` is also
+///   accepted as is, since the default `snowchains.dhall` puts the problems in no contest there.
+pub(crate) fn normalize_contest(service: PlatformKind, contest: &str) -> anyhow::Result<String> {
+    let contest = contest.trim();
 
-    let xtask = serde_dhall::from_str(&format!("let config = {} in config.xtask", path))
-        .type_annotation(&map_annot(SimpleType::Text, Script::static_type()))
-        .parse::<IndexMap<String, _>>()
-        .with_context(|| format!("Could not evalute `{}`", path))?;
+    let normalized = match service {
+        PlatformKind::Atcoder => {
+            let contest = contest.to_lowercase();
+            let contest = match contest.strip_prefix("chokudais") {
+                Some(rest) => format!("chokudai_s{}", rest),
+                None => contest,
+            };
+            Some(contest).filter(|contest| {
+                !contest.is_empty()
+                    && contest
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            })
+        }
+        PlatformKind::Yukicoder if contest == "problems" => Some(contest.to_owned()),
+        PlatformKind::Codeforces | PlatformKind::Yukicoder => {
+            contest.parse::<u64>().ok().map(|id| id.to_string())
+        }
+    };
 
-    xtask.get(name).cloned().with_context(|| {
-        format!(
-            "No such xtask subcommand: `{}` (found [{}])",
-            name,
-            xtask
-                .keys()
-                .format_with(", ", |s, f| f(&format_args!("`{}`", s)))
-        )
+    normalized.with_context(|| {
+        ConfigError(format!(
+            "Invalid contest ID for {}: {:?}",
+            service.to_kebab_case_str(),
+            contest,
+        ))
     })
 }
 
-fn find_snowchains_dhall(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<String> {
-    let path = if let Some(rel_path) = rel_path {
-        let rel_path = rel_path.strip_prefix(".").unwrap_or(rel_path);
-        let path = cwd.join(rel_path);
-        ensure!(path.exists(), "`{}` does not exist", path.display());
-        path
-    } else {
-        cwd.ancestors()
-            .map(|p| p.join("snowchains.dhall"))
-            .find(|p| p.exists())
-            .with_context(|| {
-                format!(
-                    "Could not find `snowchains.dhall` in `{}` or any parent directory",
-                    cwd.display(),
-                )
-            })?
-    };
-
-    let path = path
-        .into_os_string()
-        .into_string()
-        .map_err(|path| anyhow!("The config path must be valid UTF-8: {:?}", path))?;
+/// Infers `service` from `contest`, for when neither `--service` nor `snowchains.dhall` tells it.
+///
+/// - AtCoder: `abc*`, `arc*`, `agc*`, and `ahc*`.
+///
+/// Fails for an unsigned integer, which can be a contest of both Codeforces and yukicoder.
+fn infer_service(contest: &str) -> anyhow::Result<Option<PlatformKind>> {
+    let contest = contest.trim().to_lowercase();
 
-    if path.chars().any(|c| c.is_whitespace() || c.is_control()) {
-        bail!(
-            "The config path must not contain whitespace and control characters: {:?}",
-            path,
-        );
+    if ["abc", "arc", "agc", "ahc"]
+        .iter()
+        .any(|prefix| contest.starts_with(prefix))
+    {
+        Ok(Some(PlatformKind::Atcoder))
+    } else if contest.parse::<u64>().is_ok() {
+        Err(ConfigError(format!(
+            "`service` cannot be inferred from {:?}, which can be a contest of both Codeforces \
+             and yukicoder. Specify with `--service`",
+            contest,
+        ))
+        .into())
+    } else {
+        Ok(None)
     }
-
-    Ok(path)
 }
 
-fn quote(s: impl AsRef<str>) -> impl fmt::Display {
-    InterpolatedText::<Infallible>::from(s.as_ref().to_owned())
-}
+pub(crate) fn target(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+    cli_opt_service: Option<PlatformKind>,
+    cli_opt_contest: Option<&str>,
+    cli_opt_problem: Option<&str>,
+) -> anyhow::Result<(Target, PathBuf)> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
 
-fn map_annot(key: SimpleType, value: SimpleType) -> SimpleType {
-    SimpleType::List(Box::new(SimpleType::Record(
-        hashmap!("mapKey".to_owned() => key, "mapValue".to_owned() => value),
-    )))
-}
+    let mut target = Detected::load_and_eval(cwd, &path)?.merge_target_with_cli_options(
+        cli_opt_service,
+        cli_opt_contest,
+        cli_opt_problem,
+        Mode::Debug,
+    )?;
+    target.resolve_problem_alias(&problem_aliases(&path)?);
 
-#[derive(Debug, Deserialize, StaticType)]
-pub(crate) struct Detected {
-    pub(crate) service: Option<String>,
-    pub(crate) contest: Option<String>,
-    pub(crate) problem: Option<String>,
-    pub(crate) language: Option<String>,
+    Ok((target, path.dir()))
 }
 
-impl Detected {
-    fn load_and_eval(cwd: &Path, path: &str) -> anyhow::Result<Self> {
-        let rel_path_components = cwd
-            .strip_prefix({
-                let path = Path::new(path);
-                path.parent().unwrap_or(path)
-            })
-            .map(|rel_path| {
-                rel_path
-                    .iter()
-                    .map(|comp| {
-                        quote(
-                            comp.to_str()
-                                .expect("components of a UTF-8 path should also be UTF-8"),
-                        )
-                    })
-                    .join(", ")
-            })
-            .unwrap_or_default();
-
-        let rel_path_components = if rel_path_components.is_empty() {
-            "[] : List Text".to_owned()
-        } else {
-            format!("[{}]", rel_path_components)
-        };
-
-        serde_dhall::from_str(&format!(
-            r"let relativePathSegments = {}
-
-let config = {}
+pub(crate) fn language_names(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+    cli_opt_service: Option<PlatformKind>,
+    cli_opt_contest: Option<&str>,
+    cli_opt_problem: Option<&str>,
+) -> anyhow::Result<LanguageNames> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+    let detected = Detected::load_and_eval(cwd, &path)?;
+    LanguageNames::new(
+        &path,
+        &detected,
+        cli_opt_service,
+        cli_opt_contest,
+        cli_opt_problem,
+    )
+}
 
-in  {{ service = config.detectServiceFromRelativePathSegments relativePathSegments
-    , contest = config.detectContestFromRelativePathSegments relativePathSegments
-    , problem = config.detectProblemFromRelativePathSegments relativePathSegments
-    , language = config.detectLanguageFromRelativePathSegments relativePathSegments
-    }}
-",
-            rel_path_components, path,
-        ))
-        .static_type_annotation()
-        .parse()
-        .with_context(|| format!("Could not evalute `{}`", path))
-    }
+#[derive(Debug)]
+pub(crate) struct LanguageNames {
+    pub(crate) names: Vec<String>,
+    /// The language detected by `detectLanguageFromRelativePathSegments`.
+    pub(crate) default: Option<String>,
+}
 
-    fn merge_with_cli_options(
-        &self,
+impl LanguageNames {
+    fn new(
+        path: &ConfigPath,
+        detected: &Detected,
         service: Option<PlatformKind>,
         contest: Option<&str>,
         problem: Option<&str>,
-        language: Option<&str>,
-        mode: Mode,
-    ) -> anyhow::Result<(Target, String)> {
-        let service = service.map(Ok).unwrap_or_else(|| {
-            self.service
-                .as_deref()
-                .with_context(|| "`service` was not detected. Specify with `--service`")?
-                .parse()
-                .with_context(|| {
-                    "`detectServiceFromRelativePathSegments` returned unrecognized `service`. \
-                     Specify the correct `service` with `--service`"
-                })
-        })?;
+    ) -> anyhow::Result<Self> {
+        let service = detected.service(service, contest)?;
 
         let contest = contest
-            .or_else(|| self.contest.as_deref())
+            .or(detected.contest.as_deref())
             .map(ToOwned::to_owned);
 
+        // The names of the languages should not depend on `problem`.
         let problem = problem
-            .map(Ok)
-            .unwrap_or_else(|| {
-                self.problem
-                    .as_deref()
-                    .with_context(|| "`problem` was not detected. Specify with `--problem`")
-            })?
-            .to_owned();
-
-        let language = language
-            .map(Ok)
-            .unwrap_or_else(|| {
-                self.language
-                    .as_deref()
-                    .with_context(|| "`language` was not detected. Specify with `--language`")
-            })?
+            .or(detected.problem.as_deref())
+            .unwrap_or_default()
             .to_owned();
 
         let target = Target {
             service,
             contest,
             problem,
-            mode,
+            mode: Mode::Debug,
         };
 
-        Ok((target, language))
-    }
+        let names = languages(path, &target)?.into_keys().collect::<Vec<_>>();
 
-    pub(crate) fn parse_service(&self) -> anyhow::Result<Option<PlatformKind>> {
-        self.service
-            .as_deref()
-            .map(str::parse)
-            .transpose()
-            .with_context(|| {
-                "Specified invalid `service` by `detectServiceFromRelativePathSegments`"
-            })
+        let default = detected
+            .language
+            .clone()
+            .filter(|language| names.contains(language));
+
+        Ok(Self { names, default })
     }
 }
 
-#[derive(Debug, Deserialize, StaticType, Clone)]
-pub(crate) enum Command {
-    Args(Vec<String>),
-    Script(Script),
+fn languages(path: &ConfigPath, target: &Target) -> anyhow::Result<BTreeMap<String, Language>> {
+    serde_dhall::from_str(&format!(
+        "let target = {} let config = {} in config.languages target",
+        target.to_dhall_expr(),
+        path.to_dhall_expr(),
+    ))
+    .parse()
+    .with_context(|| format!("Could not evaluate `{}`", path))
 }
 
-#[derive(Debug, Deserialize, StaticType, Clone)]
-pub(crate) struct Script {
-    pub(crate) program: String,
-    pub(crate) extension: String,
-    pub(crate) content: String,
+pub(crate) fn xtask(cwd: &Path, rel_path: Option<&Path>, name: &str) -> anyhow::Result<Script> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    let xtask = serde_dhall::from_str(&format!(
+        "let config = {} in config.xtask",
+        path.to_dhall_expr(),
+    ))
+    .type_annotation(&map_annot(SimpleType::Text, Script::static_type()))
+    .parse::<IndexMap<String, _>>()
+    .with_context(|| format!("Could not evalute `{}`", path))?;
+
+    xtask.get(name).cloned().with_context(|| {
+        format!(
+            "No such xtask subcommand: `{}` (found [{}])",
+            name,
+            xtask
+                .keys()
+                .format_with(", ", |s, f| f(&format_args!("`{}`", s)))
+        )
+    })
 }
 
-#[allow(non_snake_case)] // for `StaticType`
-#[derive(Debug, Deserialize, StaticType)]
-pub(crate) struct Language {
-    pub(crate) src: String,
-    pub(crate) transpile: Option<Compile>,
-    pub(crate) compile: Option<Compile>,
-    pub(crate) run: Command,
-    pub(crate) languageId: Option<String>,
+/// `judgeHook` in `snowchains.dhall`, a command run after `judge`.
+pub(crate) fn judge_hook(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<Option<Command>> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    serde_dhall::from_str(&format!(
+        "let config = {} in ({{ judgeHook = None {} }} // config).judgeHook",
+        path.to_dhall_expr(),
+        COMMAND_TYPE,
+    ))
+    .static_type_annotation()
+    .parse()
+    .with_context(|| format!("Could not evalute `{}`", path))
 }
 
-#[derive(Debug, Deserialize, StaticType)]
-pub(crate) struct Compile {
-    pub(crate) command: Command,
-    pub(crate) output: String,
+/// `defaultTester` in `snowchains.dhall`, a Bash command used as `tester` of interactive test
+/// suites without one.
+pub(crate) fn default_tester(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+) -> anyhow::Result<Option<Tester>> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    let cmd = serde_dhall::from_str(&format!(
+        "let config = {} in ({{ defaultTester = None Text }} // config).defaultTester",
+        path.to_dhall_expr(),
+    ))
+    .static_type_annotation()
+    .parse::<Option<String>>()
+    .with_context(|| format!("Could not evalute `{}`", path))?;
+
+    Ok(cmd.map(|cmd| Tester {
+        cmd,
+        shell: CheckerShell::Bash,
+    }))
 }
 
-#[derive(Debug)]
-pub(crate) struct Target {
-    pub(crate) service: PlatformKind,
-    pub(crate) contest: Option<String>,
-    pub(crate) problem: String,
-    pub(crate) mode: Mode,
+/// `httpHeaders` in `snowchains.dhall`, extra headers sent to each service.
+///
+/// ```dhall
+/// , httpHeaders = toMap { atcoder = toMap { `User-Agent` = "snowchains (my-name)" } }
+/// ```
+pub(crate) fn http_headers(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+    service: PlatformKind,
+) -> anyhow::Result<HeaderMap> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    let headers = serde_dhall::from_str(&format!(
+        "let config = {} in ({{ httpHeaders = [] : List {{ mapKey : Text, mapValue : List {{ mapKey : Text, mapValue : Text }} }} }} // config).httpHeaders",
+        path.to_dhall_expr(),
+    ))
+    .type_annotation(&map_annot(
+        SimpleType::Text,
+        map_annot(SimpleType::Text, SimpleType::Text),
+    ))
+    .parse::<IndexMap<String, IndexMap<String, String>>>()
+    .with_context(|| format!("Could not evalute `{}`", path))?;
+
+    headers
+        .get(service.to_kebab_case_str())
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => Ok((name, value)),
+                _ => Err(ConfigError(format!(
+                    "Invalid header in `httpHeaders`: {:?}: {:?}",
+                    name, value,
+                ))
+                .into()),
+            }
+        })
+        .collect()
 }
 
-impl Target {
-    fn to_dhall_expr(&self) -> String {
-        format!(
-            r"let Service = < Atcoder | Codeforces | Yukicoder >
+/// `http_headers` if `snowchains.dhall` exists. Otherwise, no headers.
+pub(crate) fn http_headers_or_default(
+    cwd: &Path,
+    service: PlatformKind,
+) -> anyhow::Result<HeaderMap> {
+    if find_project_snowchains_dhall(cwd, None).is_ok() {
+        http_headers(cwd, None, service)
+    } else {
+        Ok(HeaderMap::new())
+    }
+}
 
-let CaseConvertedText =
-      {{ lowercase : Text
-      , uppercase : Text
-      , snakeCase : Text
-      , kebabCase : Text
-      , mixedCase : Text
-      , pascalCase : Text
-      }}
+/// `problemAliases` in `snowchains.dhall`, the names of the problems for each contest.
+///
+/// ```dhall
+/// , problemAliases = toMap { abc300 = toMap { a = "abc300_a" } }
+/// ```
+fn problem_aliases(
+    path: &ConfigPath,
+) -> anyhow::Result<IndexMap<String, IndexMap<String, String>>> {
+    serde_dhall::from_str(&format!(
+        "let config = {} in ({{ problemAliases = [] : List {{ mapKey : Text, mapValue : List {{ mapKey : Text, mapValue : Text }} }} }} // config).problemAliases",
+        path.to_dhall_expr(),
+    ))
+    .type_annotation(&map_annot(
+        SimpleType::Text,
+        map_annot(SimpleType::Text, SimpleType::Text),
+    ))
+    .parse()
+    .with_context(|| format!("Could not evalute `{}`", path))
+}
 
-let Mode = < Debug | Release >
+/// `testSuiteLineEnding` in `snowchains.dhall`, the line ending of saved test suites.
+///
+/// ```dhall
+/// , testSuiteLineEnding = < Lf | Crlf | Native >.Crlf
+/// ```
+pub(crate) fn test_suite_line_ending(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+) -> anyhow::Result<LineEnding> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
 
-in  {{ service = Service.{}
-    , contest = {}
-    , problem =
-        {{ lowercase = {}
-        , uppercase = {}
-        , snakeCase = {}
-        , kebabCase = {}
-        , mixedCase = {}
-        , pascalCase = {}
-        }}
+    serde_dhall::from_str(&format!(
+        "let config = {} in ({{ testSuiteLineEnding = {}.Lf }} // config).testSuiteLineEnding",
+        path.to_dhall_expr(),
+        LINE_ENDING_TYPE,
+    ))
+    .type_annotation(&SimpleType::Union(hashmap!(
+        "Lf".to_owned() => None,
+        "Crlf".to_owned() => None,
+        "Native".to_owned() => None,
+    )))
+    .parse()
+    .with_context(|| format!("Could not evalute `{}`", path))
+}
+
+/// `statementLanguage` in `snowchains.dhall`, the language of the statements on AtCoder in which
+/// the samples are looked for first.
+///
+/// ```dhall
+/// , statementLanguage = < Ja | En >.En
+/// ```
+pub(crate) fn statement_language(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+) -> anyhow::Result<AtcoderStatementLanguage> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    serde_dhall::from_str(&format!(
+        "let config = {} in ({{ statementLanguage = {}.Ja }} // config).statementLanguage",
+        path.to_dhall_expr(),
+        STATEMENT_LANGUAGE_TYPE,
+    ))
+    .type_annotation(&SimpleType::Union(hashmap!(
+        "Ja".to_owned() => None,
+        "En".to_owned() => None,
+    )))
+    .parse()
+    .with_context(|| format!("Could not evalute `{}`", path))
+}
+
+/// `runWorkingDirectory` in `snowchains.dhall`, the working directory of `run` in `judge` and
+/// `stress`.
+///
+/// `$srcdir` is replaced with the directory of `src`. A relative path is resolved against the
+/// directory of `snowchains.dhall`, which is also the default. Only the working directory changes;
+/// the inputs are given through the stdin either way, so a program that opens data files with
+/// relative paths should set this to where the files are.
+///
+/// ```dhall
+/// , runWorkingDirectory = Some "$srcdir"
+/// ```
+pub(crate) fn run_working_directory(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+    src: &str,
+) -> anyhow::Result<PathBuf> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+    let base_dir = path.dir();
+
+    let dir = serde_dhall::from_str(&format!(
+        "let config = {} in ({{ runWorkingDirectory = None Text }} // config).runWorkingDirectory",
+        path.to_dhall_expr(),
+    ))
+    .static_type_annotation()
+    .parse::<Option<String>>()
+    .with_context(|| format!("Could not evalute `{}`", path))?;
+
+    let dir = match dir {
+        None => return Ok(base_dir),
+        Some(dir) => dir,
+    };
+
+    let src_dir = join_under(&base_dir, Path::new(src))
+        .parent()
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| base_dir.clone());
+
+    let dir = join_under(
+        &base_dir,
+        Path::new(&dir.replace("$srcdir", &src_dir.to_string_lossy())),
+    );
+
+    if !dir.is_dir() {
+        bail!(ConfigError(format!(
+            "`runWorkingDirectory` ({}) is not a directory",
+            dir.display(),
+        )));
+    }
+    Ok(dir)
+}
+
+/// `tlsRootCertificates` and `tlsDangerAcceptInvalidCerts` in `snowchains.dhall`.
+///
+/// The paths to the PEM files are relative to the directory of `snowchains.dhall`.
+///
+/// ```dhall
+/// , tlsRootCertificates = [ "./corporate-ca.pem" ]
+/// , tlsDangerAcceptInvalidCerts = False
+/// ```
+pub(crate) fn tls(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<TlsConfig> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+
+    let (root_certificates, danger_accept_invalid_certs) = serde_dhall::from_str(&format!(
+        "let config = {{ tlsRootCertificates = [] : List Text, tlsDangerAcceptInvalidCerts = False }} // {} \
+         in {{ _1 = config.tlsRootCertificates, _2 = config.tlsDangerAcceptInvalidCerts }}",
+        path.to_dhall_expr(),
+    ))
+    .type_annotation(&SimpleType::Record(hashmap!(
+        "_1".to_owned() => SimpleType::List(Box::new(SimpleType::Text)),
+        "_2".to_owned() => SimpleType::Bool,
+    )))
+    .parse::<(Vec<String>, bool)>()
+    .with_context(|| format!("Could not evalute `{}`", path))?;
+
+    let root_certificates = root_certificates
+        .iter()
+        .map(|pem| std::fs::read(path.dir().join(pem)))
+        .collect::<Result<_, _>>()
+        .with_context(|| "Could not read `tlsRootCertificates`")?;
+
+    Ok(TlsConfig {
+        root_certificates,
+        danger_accept_invalid_certs,
+    })
+}
+
+/// [`tls`], or the default if `snowchains.dhall` is not found.
+pub(crate) fn tls_or_default(cwd: &Path) -> anyhow::Result<TlsConfig> {
+    if find_project_snowchains_dhall(cwd, None).is_ok() {
+        tls(cwd, None)
+    } else {
+        Ok(TlsConfig::default())
+    }
+}
+
+/// `Command` in the schema.
+const COMMAND_TYPE: &str =
+    "< Args : List Text | Script : { program : Text, extension : Text, content : Text } >";
+
+/// `LineEnding` in the schema.
+const LINE_ENDING_TYPE: &str = "< Lf | Crlf | Native >";
+
+/// `StatementLanguage` in the schema.
+const STATEMENT_LANGUAGE_TYPE: &str = "< Ja | En >";
+
+/// Directory of each problem for `--problem-dir`, which has both the source file and the test
+/// suite.
+const PROBLEM_DIR: &str = "./problems/{problem}";
+
+/// Default of `testSuites`.
+const DEFAULT_TEST_SUITES: &str = ".snowchains/tests/$service/$contest";
+
+/// The directory of `snowchains.dhall`.
+pub(crate) fn base_dir(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<PathBuf> {
+    find_project_snowchains_dhall(cwd, rel_path).map(|project| ConfigPath::dir_of(&project))
+}
+
+pub(crate) fn test_suites(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<TestSuites> {
+    let path = find_snowchains_dhall(cwd, rel_path)?;
+    TestSuites::new(&path)
+}
+
+/// `testSuites` in `snowchains.dhall`, the directory of the test suites.
+///
+/// `$service`, `$contest`, and `{problem}` in it are replaced. A relative one is resolved from
+/// the directory of `snowchains.dhall`, and one starting with `~` from the home directory.
+#[derive(Debug)]
+pub(crate) struct TestSuites {
+    template: String,
+    base_dir: PathBuf,
+}
+
+impl TestSuites {
+    fn new(path: &ConfigPath) -> anyhow::Result<Self> {
+        let template = serde_dhall::from_str(&format!(
+            "let config = {} in ({{ testSuites = {} }} // config).testSuites",
+            path.to_dhall_expr(),
+            quote(DEFAULT_TEST_SUITES),
+        ))
+        .static_type_annotation()
+        .parse::<String>()
+        .with_context(|| format!("Could not evalute `{}`", path))?;
+
+        Ok(Self {
+            template,
+            base_dir: path.dir(),
+        })
+    }
+
+    /// Uses `problems/{problem}` next to `snowchains.dhall` instead, for `--problem-dir`.
+    pub(crate) fn problem_dir(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<Self> {
+        let path = find_snowchains_dhall(cwd, rel_path)?;
+
+        Ok(Self {
+            template: PROBLEM_DIR.to_owned(),
+            base_dir: path.dir(),
+        })
+    }
+
+    /// Uses `dir` given on the command line instead. It is resolved from `cwd`.
+    pub(crate) fn output_dir(cwd: &Path, dir: &Path) -> anyhow::Result<Self> {
+        let template = dir
+            .to_str()
+            .with_context(|| format!("The output directory must be valid UTF-8: {:?}", dir))?
+            .to_owned();
+
+        Ok(Self {
+            template,
+            base_dir: cwd.to_owned(),
+        })
+    }
+
+    pub(crate) fn path(
+        &self,
+        service: PlatformKind,
+        contest: Option<&str>,
+        problem: &str,
+    ) -> anyhow::Result<PathBuf> {
+        self.path_with_home(dirs_next::home_dir().as_deref(), service, contest, problem)
+    }
+
+    fn path_with_home(
+        &self,
+        home: Option<&Path>,
+        service: PlatformKind,
+        contest: Option<&str>,
+        problem: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let dir = self
+            .template
+            .replace("$service", service.to_kebab_case_str())
+            .replace("$contest", contest.unwrap_or(""))
+            .replace("{problem}", problem);
+
+        let dir = match dir.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => home
+                .with_context(|| "Could not find the home directory")?
+                .join(rest.trim_start_matches('/')),
+            _ => join_under(&self.base_dir, Path::new(&dir)),
+        };
+
+        Ok(dir.join(problem).with_extension("yml"))
+    }
+}
+
+/// Path to `snowchains.dhall` and the global one.
+///
+/// The global one is `$XDG_CONFIG_HOME/snowchains/snowchains.dhall` (or the equivalent on each
+/// platform). If it exists, it is merged under the project one with `//`, so the fields of the
+/// project one take precedence.
+#[derive(Debug)]
+struct ConfigPath {
+    project: String,
+    global: Option<String>,
+}
+
+impl ConfigPath {
+    fn dir(&self) -> PathBuf {
+        Self::dir_of(&self.project)
+    }
+
+    fn dir_of(project: &str) -> PathBuf {
+        let path = Path::new(project);
+        path.parent().unwrap_or(path).to_owned()
+    }
+
+    fn to_dhall_expr(&self) -> String {
+        if let Some(global) = &self.global {
+            format!("({} // {})", global, self.project)
+        } else {
+            self.project.clone()
+        }
+    }
+}
+
+impl fmt::Display for ConfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(global) = &self.global {
+            write!(f, "{} // {}", global, self.project)
+        } else {
+            write!(f, "{}", self.project)
+        }
+    }
+}
+
+/// Resolves `path` under `base_dir` unless it is absolute.
+///
+/// On Windows, a UNC path (e.g. `\\server\share\foo`) is absolute. A drive-relative path (e.g.
+/// `C:foo`) is resolved under `base_dir` if it is on the same drive, and is left to the OS
+/// otherwise, instead of being joined as a whole.
+fn join_under(base_dir: &Path, path: &Path) -> PathBuf {
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Prefix(_)) if path.has_root() => path.to_owned(),
+        Some(Component::Prefix(prefix)) => {
+            if base_dir.components().next() == Some(Component::Prefix(prefix)) {
+                base_dir.join(components.as_path())
+            } else {
+                path.to_owned()
+            }
+        }
+        _ => base_dir.join(path.strip_prefix(".").unwrap_or(path)),
+    }
+}
+
+/// Resolves a path given with `--source`.
+pub(crate) fn source_path(cwd: &Path, path: &Path) -> anyhow::Result<String> {
+    let path = join_under(cwd, path);
+    ensure!(path.is_file(), "`{}` is not a file", path.display());
+    path.into_os_string()
+        .into_string()
+        .map_err(|path| anyhow!("The source path must be valid UTF-8: {:?}", path))
+}
+
+pub(crate) fn binary_path(cwd: &Path, path: &Path) -> anyhow::Result<String> {
+    let path = join_under(cwd, path);
+    ensure!(path.is_file(), "`{}` is not a file", path.display());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        ensure!(mode & 0o111 != 0, "`{}` is not executable", path.display());
+    }
+
+    path.into_os_string()
+        .into_string()
+        .map_err(|path| anyhow!("The binary path must be valid UTF-8: {:?}", path))
+}
+
+fn find_snowchains_dhall(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<ConfigPath> {
+    let project = find_project_snowchains_dhall(cwd, rel_path)?;
+
+    let global = dirs_next::config_dir()
+        .map(|dir| dir.join("snowchains").join("snowchains.dhall"))
+        .filter(|path| path.exists())
+        .map(validate_config_path)
+        .transpose()?;
+
+    Ok(ConfigPath { project, global })
+}
+
+fn find_project_snowchains_dhall(cwd: &Path, rel_path: Option<&Path>) -> anyhow::Result<String> {
+    let path = if let Some(rel_path) = rel_path {
+        let path = join_under(cwd, rel_path);
+        ensure!(
+            path.exists(),
+            ConfigError(format!("`{}` does not exist", path.display())),
+        );
+        path
+    } else {
+        cwd.ancestors()
+            .map(|p| p.join("snowchains.dhall"))
+            .find(|p| p.exists())
+            .with_context(|| {
+                ConfigError(format!(
+                    "Could not find `snowchains.dhall` in `{}` or any parent directory",
+                    cwd.display(),
+                ))
+            })?
+    };
+
+    validate_config_path(path)
+}
+
+fn validate_config_path(path: PathBuf) -> anyhow::Result<String> {
+    let path = path
+        .into_os_string()
+        .into_string()
+        .map_err(|path| ConfigError(format!("The config path must be valid UTF-8: {:?}", path)))?;
+
+    if path.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        bail!(ConfigError(format!(
+            "The config path must not contain whitespace and control characters: {:?}",
+            path,
+        )));
+    }
+
+    Ok(path)
+}
+
+/// For `--offline`, ensures that evaluating `snowchains.dhall` fetches nothing.
+///
+/// Every remote import reachable through the local imports must have a hash and be in the cache of
+/// Dhall, which is filled when `snowchains.dhall` is evaluated online.
+pub(crate) fn ensure_remote_imports_cached(
+    cwd: &Path,
+    rel_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    // If `snowchains.dhall` is not found, the command itself reports it.
+    let path = match find_snowchains_dhall(cwd, rel_path) {
+        Ok(path) => path,
+        Err(_) => return Ok(()),
+    };
+
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os(if cfg!(windows) {
+                "LOCALAPPDATA"
+            } else {
+                "HOME"
+            })
+            .map(|dir| Path::new(&dir).join(".cache"))
+        })
+        .map(|dir| dir.join("dhall"));
+
+    let mut visited = HashSet::new();
+    for path in std::iter::once(&path.project).chain(&path.global) {
+        check_remote_imports_cached(Path::new(path), cache_dir.as_deref(), &mut visited)?;
+    }
+    Ok(())
+}
+
+fn check_remote_imports_cached(
+    path: &Path,
+    cache_dir: Option<&Path>,
+    visited: &mut HashSet<PathBuf>,
+) -> anyhow::Result<()> {
+    if !visited.insert(path.to_owned()) {
+        return Ok(());
+    }
+
+    // Missing files and syntax errors are reported when the file is evaluated.
+    let expr = match std::fs::read_to_string(path)
+        .ok()
+        .and_then(|code| dhall::syntax::parse_expr(&code).ok())
+    {
+        Some(expr) => expr,
+        None => return Ok(()),
+    };
+
+    let mut imports = vec![];
+    collect_imports(&expr, &mut imports);
+
+    for import in imports {
+        let cached = match &import.hash {
+            Some(dhall::syntax::Hash::SHA256(sha256)) => cache_dir.is_some_and(|cache_dir| {
+                let hex = sha256.iter().map(|b| format!("{:02x}", b)).join("");
+                cache_dir.join(format!("1220{}", hex)).exists()
+            }),
+            None => false,
+        };
+        if cached || import.mode == ImportMode::Location {
+            continue;
+        }
+
+        match &import.location {
+            ImportTarget::Remote(url) => {
+                let url = format!(
+                    "{}://{}/{}{}",
+                    match url.scheme {
+                        Scheme::HTTP => "http",
+                        Scheme::HTTPS => "https",
+                    },
+                    url.authority,
+                    url.path.file_path.join("/"),
+                    url.query
+                        .as_ref()
+                        .map(|q| format!("?{}", q))
+                        .unwrap_or_default(),
+                );
+                bail!(ConfigError(if import.hash.is_some() {
+                    format!(
+                        "`{}` imports `{}`, which is not cached yet. Run a command without \
+                         `--offline` once to cache it",
+                        path.display(),
+                        url,
+                    )
+                } else {
+                    format!(
+                        "`{}` imports `{}` without a hash, which `--offline` forbids since it \
+                         cannot be cached",
+                        path.display(),
+                        url,
+                    )
+                }));
+            }
+            ImportTarget::Local(prefix, file_path) => {
+                let base = match prefix {
+                    FilePrefix::Absolute => Some(PathBuf::from("/")),
+                    FilePrefix::Here => Some(ConfigPath::dir_of(&path.to_string_lossy())),
+                    FilePrefix::Parent => {
+                        Some(ConfigPath::dir_of(&path.to_string_lossy()).join(".."))
+                    }
+                    FilePrefix::Home => dirs_next::home_dir(),
+                };
+                if let Some(base) = base {
+                    let local = file_path
+                        .file_path
+                        .iter()
+                        .fold(base, |path, segment| path.join(segment));
+                    check_remote_imports_cached(&local, cache_dir, visited)?;
+                }
+            }
+            ImportTarget::Env(_) | ImportTarget::Missing => {}
+        }
+    }
+    Ok(())
+}
+
+fn collect_imports(expr: &DhallExpr, imports: &mut Vec<Import<DhallExpr>>) {
+    if let ExprKind::Import(import) = expr.kind() {
+        imports.push(import.clone());
+    }
+    let _ = expr.kind().traverse_ref(|expr| {
+        collect_imports(expr, imports);
+        Ok::<_, Infallible>(())
+    });
+}
+
+/// Replaces the occurrences of `from` in `text` which are not part of longer words. Returns `None`
+/// if there are none.
+fn replace_words(text: &str, from: &str, to: &str) -> Option<String> {
+    let is_boundary =
+        |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || "\"'`;&|()<>=".contains(c));
+
+    let mut acc = "".to_owned();
+    let mut end = 0;
+    let mut replaced = false;
+
+    for (start, _) in text.match_indices(from) {
+        if is_boundary(text[..start].chars().next_back())
+            && is_boundary(text[start + from.len()..].chars().next())
+        {
+            acc += &text[end..start];
+            acc += to;
+            end = start + from.len();
+            replaced = true;
+        }
+    }
+
+    if !replaced {
+        return None;
+    }
+    acc += &text[end..];
+    Some(acc)
+}
+
+fn quote(s: impl AsRef<str>) -> impl fmt::Display {
+    InterpolatedText::<Infallible>::from(s.as_ref().to_owned())
+}
+
+fn map_annot(key: SimpleType, value: SimpleType) -> SimpleType {
+    SimpleType::List(Box::new(SimpleType::Record(
+        hashmap!("mapKey".to_owned() => key, "mapValue".to_owned() => value),
+    )))
+}
+
+#[derive(Debug, Deserialize, StaticType)]
+pub(crate) struct Detected {
+    pub(crate) service: Option<String>,
+    pub(crate) contest: Option<String>,
+    pub(crate) problem: Option<String>,
+    pub(crate) language: Option<String>,
+}
+
+impl Detected {
+    fn load_and_eval(cwd: &Path, path: &ConfigPath) -> anyhow::Result<Self> {
+        let rel_path_components = cwd
+            .strip_prefix(path.dir())
+            .map(|rel_path| {
+                rel_path
+                    .iter()
+                    .map(|comp| {
+                        quote(
+                            comp.to_str()
+                                .expect("components of a UTF-8 path should also be UTF-8"),
+                        )
+                    })
+                    .join(", ")
+            })
+            .unwrap_or_default();
+
+        let rel_path_components = if rel_path_components.is_empty() {
+            "[] : List Text".to_owned()
+        } else {
+            format!("[{}]", rel_path_components)
+        };
+
+        serde_dhall::from_str(&format!(
+            r"let relativePathSegments = {}
+
+let config = {}
+
+in  {{ service = config.detectServiceFromRelativePathSegments relativePathSegments
+    , contest = config.detectContestFromRelativePathSegments relativePathSegments
+    , problem = config.detectProblemFromRelativePathSegments relativePathSegments
+    , language = config.detectLanguageFromRelativePathSegments relativePathSegments
+    }}
+",
+            rel_path_components,
+            path.to_dhall_expr(),
+        ))
+        .static_type_annotation()
+        .parse()
+        .with_context(|| format!("Could not evalute `{}`", path))
+    }
+
+    fn merge_with_cli_options(
+        &self,
+        service: Option<PlatformKind>,
+        contest: Option<&str>,
+        problem: Option<&str>,
+        language: Option<&str>,
+        mode: Mode,
+    ) -> anyhow::Result<(Target, Option<String>)> {
+        let target = self.merge_target_with_cli_options(service, contest, problem, mode)?;
+        let language = language.or(self.language.as_deref()).map(ToOwned::to_owned);
+        Ok((target, language))
+    }
+
+    fn merge_target_with_cli_options(
+        &self,
+        service: Option<PlatformKind>,
+        contest: Option<&str>,
+        problem: Option<&str>,
+        mode: Mode,
+    ) -> anyhow::Result<Target> {
+        let service = self.service(service, contest)?;
+
+        let contest = contest
+            .or_else(|| self.contest.as_deref())
+            .map(|contest| normalize_contest(service, contest))
+            .transpose()?;
+
+        let problem = problem
+            .map(Ok)
+            .unwrap_or_else(|| {
+                self.problem.as_deref().with_context(|| {
+                    ConfigError::from("`problem` was not detected. Specify with `--problem`")
+                })
+            })?
+            .to_owned();
+
+        Ok(Target {
+            service,
+            contest,
+            problem,
+            mode,
+        })
+    }
+
+    /// Decides `service` from `--service`, `detectServiceFromRelativePathSegments`, or
+    /// `infer_service` on the contest, in this order.
+    pub(crate) fn service(
+        &self,
+        cli_opt_service: Option<PlatformKind>,
+        cli_opt_contest: Option<&str>,
+    ) -> anyhow::Result<PlatformKind> {
+        if let Some(service) = cli_opt_service {
+            return Ok(service);
+        }
+        if let Some(service) = self.parse_service()? {
+            return Ok(service);
+        }
+        cli_opt_contest
+            .or(self.contest.as_deref())
+            .map(infer_service)
+            .transpose()?
+            .flatten()
+            .with_context(|| {
+                ConfigError::from("`service` was not detected. Specify with `--service`")
+            })
+    }
+
+    fn parse_service(&self) -> anyhow::Result<Option<PlatformKind>> {
+        self.service
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .with_context(|| {
+                ConfigError::from(
+                    "Specified invalid `service` by `detectServiceFromRelativePathSegments`",
+                )
+            })
+    }
+}
+
+#[derive(Debug, Deserialize, StaticType, Clone)]
+pub(crate) enum Command {
+    Args(Vec<String>),
+    Script(Script),
+}
+
+impl Command {
+    /// Replaces the arguments equal to `from`, or the words of the script equal to `from`, with
+    /// `to`. Returns `None` if there are none.
+    fn replace_path(&self, from: &str, to: &str) -> Option<Self> {
+        match self {
+            Self::Args(args) => {
+                if !args.iter().any(|arg| arg == from) {
+                    return None;
+                }
+                let args = args
+                    .iter()
+                    .map(|arg| if arg == from { to } else { arg })
+                    .map(ToOwned::to_owned)
+                    .collect();
+                Some(Self::Args(args))
+            }
+            Self::Script(Script {
+                program,
+                extension,
+                content,
+            }) => Some(Self::Script(Script {
+                program: program.clone(),
+                extension: extension.clone(),
+                content: replace_words(content, from, to)?,
+            })),
+        }
+    }
+
+    fn replace(&self, from: &str, to: &str) -> Self {
+        match self {
+            Self::Args(args) => Self::Args(args.iter().map(|s| s.replace(from, to)).collect()),
+            Self::Script(Script {
+                program,
+                extension,
+                content,
+            }) => Self::Script(Script {
+                program: program.clone(),
+                extension: extension.clone(),
+                content: content.replace(from, to),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, StaticType, Clone)]
+pub(crate) struct Script {
+    pub(crate) program: String,
+    pub(crate) extension: String,
+    pub(crate) content: String,
+}
+
+#[allow(non_snake_case)] // for `StaticType`
+#[derive(Debug, Deserialize, StaticType)]
+pub(crate) struct Language {
+    pub(crate) src: String,
+    pub(crate) transpile: Option<Compile>,
+    pub(crate) compile: Option<Compile>,
+    pub(crate) run: Command,
+    pub(crate) languageId: Option<String>,
+}
+
+impl Language {
+    /// Replaces `src` with `new_src`, including the arguments and the words of the scripts equal to
+    /// it. Fails if there are none, since it would build and run the original file.
+    pub(crate) fn with_src(self, new_src: String) -> anyhow::Result<Self> {
+        let src = self.src.clone();
+
+        let mut mentions_src = false;
+
+        let language = self.map_commands(|command| {
+            if let Some(command) = command.replace_path(&src, &new_src) {
+                mentions_src = true;
+                command
+            } else {
+                command.clone()
+            }
+        });
+
+        if !mentions_src {
+            bail!(ConfigError::from(&*format!(
+                "Could not replace `src` ({:?}) with {:?}: none of the commands have it as a whole \
+                 argument",
+                src, new_src,
+            )));
+        }
+
+        Ok(Self {
+            src: new_src,
+            ..language
+        })
+    }
+
+    /// Replaces `$problem` and `{problem}` in the commands and the outputs with `problem`.
+    ///
+    /// In the content of `Script`, only `{problem}` is replaced, since `$problem` there may be a
+    /// variable of the script itself.
+    pub(crate) fn with_problem(self, problem: &str) -> Self {
+        let mut language = self.map_commands(|command| match command {
+            Command::Args(args) => Command::Args(
+                args.iter()
+                    .map(|arg| {
+                        arg.replace("$problem", problem)
+                            .replace("{problem}", problem)
+                    })
+                    .collect(),
+            ),
+            Command::Script(_) => command.replace("{problem}", problem),
+        });
+
+        for Compile { output, .. } in language.transpile.iter_mut().chain(&mut language.compile) {
+            *output = output
+                .replace("$problem", problem)
+                .replace("{problem}", problem);
+        }
+
+        language
+    }
+
+    fn map_commands(self, mut f: impl FnMut(&Command) -> Command) -> Self {
+        let Self {
+            src,
+            transpile,
+            compile,
+            run,
+            languageId,
+        } = self;
+
+        let mut map = |compile: Option<Compile>| {
+            compile.map(|Compile { command, output }| Compile {
+                command: f(&command),
+                output,
+            })
+        };
+
+        Self {
+            src,
+            transpile: map(transpile),
+            compile: map(compile),
+            run: f(&run),
+            languageId,
+        }
+    }
+
+    /// Moves `src` into `problems/{problem}`, keeping the file name, for `--problem-dir`.
+    pub(crate) fn with_problem_dir(self, problem: &str) -> anyhow::Result<Self> {
+        let file_name = Path::new(&self.src)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .with_context(|| format!("Invalid `src`: {:?}", self.src))?;
+
+        let new_src = format!(
+            "{}/{}",
+            PROBLEM_DIR.replace("{problem}", problem),
+            file_name
+        );
+        self.with_src(new_src)
+    }
+
+    /// Fails if `output` of `transpile` or `compile` is `src` itself or `base_dir`, which the build
+    /// would overwrite.
+    pub(crate) fn validate(&self, base_dir: &Path) -> anyhow::Result<()> {
+        let resolve = |path: &str| {
+            base_dir
+                .join(path)
+                .components()
+                .fold(PathBuf::new(), |mut ret, component| {
+                    match component {
+                        path::Component::CurDir => {}
+                        path::Component::ParentDir => {
+                            ret.pop();
+                        }
+                        component => ret.push(component),
+                    }
+                    ret
+                })
+        };
+
+        let src = resolve(&self.src);
+        let base_dir = resolve(".");
+
+        for (name, compile) in &[("transpile", &self.transpile), ("compile", &self.compile)] {
+            if let Some(Compile { output, .. }) = compile {
+                let output_path = resolve(output);
+                if output_path == src {
+                    bail!(ConfigError::from(&*format!(
+                        "`output` of `{}` ({:?}) is the same file as `src` ({:?}), which would be \
+                         overwritten",
+                        name, output, self.src,
+                    )));
+                }
+                if output_path == base_dir {
+                    bail!(ConfigError::from(&*format!(
+                        "`output` of `{}` ({:?}) is the working directory",
+                        name, output,
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips `transpile` and `compile`, and runs `bin` instead of `run`.
+    pub(crate) fn with_prebuilt_binary(self, bin: String) -> Self {
+        Self {
+            transpile: None,
+            compile: None,
+            run: Command::Args(vec![bin]),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, StaticType)]
+pub(crate) struct Compile {
+    pub(crate) command: Command,
+    pub(crate) output: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct Target {
+    pub(crate) service: PlatformKind,
+    pub(crate) contest: Option<String>,
+    pub(crate) problem: String,
+    pub(crate) mode: Mode,
+}
+
+impl Target {
+    /// Replaces `problem` with the ID it is aliased to in `problemAliases` of `contest`, so that
+    /// the paths, the URLs, and the submission all use the ID.
+    ///
+    /// The ID is still passed to `snowchains_core` as an index. For AtCoder, it is matched
+    /// against the screen names (`abc300_a`) of the tasks as well as the indexes (`A`).
+    fn resolve_problem_alias(&mut self, aliases: &IndexMap<String, IndexMap<String, String>>) {
+        if let Some(id) = self
+            .contest
+            .as_ref()
+            .and_then(|contest| aliases.get(contest)?.get(&self.problem))
+        {
+            self.problem = id.clone();
+        }
+    }
+
+    fn to_dhall_expr(&self) -> String {
+        format!(
+            r"let Service = < Atcoder | Codeforces | Yukicoder >
+
+let CaseConvertedText =
+      {{ lowercase : Text
+      , uppercase : Text
+      , snakeCase : Text
+      , kebabCase : Text
+      , mixedCase : Text
+      , pascalCase : Text
+      }}
+
+let Mode = < Debug | Release >
+
+in  {{ service = Service.{}
+    , contest = {}
+    , problem =
+        {{ lowercase = {}
+        , uppercase = {}
+        , snakeCase = {}
+        , kebabCase = {}
+        , mixedCase = {}
+        , pascalCase = {}
+        }}
     , mode = Mode.{}
     }}
 ",
@@ -349,3 +1413,1026 @@ pub(crate) enum Mode {
     Debug,
     Release,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{
+        Command, Compile, ConfigError, ConfigPath, Detected, Language, LanguageNames, Mode, Script,
+        Target, TestSuites,
+    };
+    use maplit::btreemap;
+    use snowchains_core::{
+        testsuite::{CheckerShell, LineEnding, Tester},
+        web::{AtcoderStatementLanguage, PlatformKind},
+    };
+    use std::{collections::HashSet, fs, path::Path, thread};
+
+    static TYPES: &str = r#"
+let CaseConvertedText =
+      { lowercase : Text
+      , uppercase : Text
+      , snakeCase : Text
+      , kebabCase : Text
+      , mixedCase : Text
+      , pascalCase : Text
+      }
+
+let Target =
+      { service : < Atcoder | Codeforces | Yukicoder >
+      , contest : Optional CaseConvertedText
+      , problem : CaseConvertedText
+      , mode : < Debug | Release >
+      }
+
+let Script = { program : Text, extension : Text, content : Text }
+
+let Command = < Args : List Text | Script : Script >
+
+let Compile = { command : Command, output : Text }
+
+let language =
+      λ(name : Text) →
+      λ(target : Target) →
+        { src = "${name}/${target.problem.kebabCase}.py"
+        , transpile = None Compile
+        , compile = None Compile
+        , run = Command.Args [ "python3" ]
+        , languageId = Some "4006"
+        }
+"#;
+
+    #[test]
+    fn global_config_is_merged_under_project_config() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_global_config_is_merged_under_project_config)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_global_config_is_merged_under_project_config() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let global = tempdir.path().join("global.dhall");
+        fs::write(
+            &global,
+            format!(
+                r"{}
+in  {{ languages = λ(target : Target) → toMap {{ py = language {:?} target }}
+    , xtask = [] : List {{ mapKey : Text, mapValue : Script }}
+    }}
+",
+                TYPES, "global",
+            ),
+        )?;
+
+        let project_without_languages = tempdir.path().join("a.dhall");
+        fs::write(
+            &project_without_languages,
+            r#"{ detectServiceFromRelativePathSegments = λ(_ : List Text) → Some "atcoder"
+, detectContestFromRelativePathSegments = λ(_ : List Text) → Some "practice"
+, detectProblemFromRelativePathSegments = λ(_ : List Text) → None Text
+, detectLanguageFromRelativePathSegments = λ(_ : List Text) → Some "py"
+}
+"#,
+        )?;
+
+        let project_with_languages = tempdir.path().join("b.dhall");
+        fs::write(
+            &project_with_languages,
+            format!(
+                r"{}
+in  {{ languages = λ(target : Target) → toMap {{ py = language {:?} target }} }}
+",
+                TYPES, "project",
+            ),
+        )?;
+
+        let target = Target {
+            service: PlatformKind::Atcoder,
+            contest: Some("practice".to_owned()),
+            problem: "a".to_owned(),
+            mode: Mode::Debug,
+        };
+
+        let path = |project: &std::path::Path| ConfigPath {
+            project: project.to_str().unwrap().to_owned(),
+            global: Some(global.to_str().unwrap().to_owned()),
+        };
+
+        let languages = super::languages(&path(&project_without_languages), &target)?;
+        assert_eq!("global/a.py", languages["py"].src);
+
+        let languages = super::languages(&path(&project_with_languages), &target)?;
+        assert_eq!("project/a.py", languages["py"].src);
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn default_config_remote_imports_cached() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_default_config_remote_imports_cached)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_default_config_remote_imports_cached() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+        let path = tempdir.path().join("snowchains.dhall");
+        let cache_dir = tempdir.path().join("dhall");
+
+        fs::write(
+            &path,
+            include_str!("../resources/config/default-config.dhall"),
+        )?;
+        fs::create_dir(&cache_dir)?;
+
+        let check =
+            || super::check_remote_imports_cached(&path, Some(&cache_dir), &mut HashSet::new());
+
+        let err = check().unwrap_err();
+        assert!(err.to_string().contains("is not cached yet"), "{}", err);
+        assert!(err.is::<ConfigError>());
+
+        for hash in &[
+            "e657b55ecae4d899465c3032cb1a64c6aa6dc2aa3034204f3c15ce5c96c03e63",
+            "6acc68ed8830bdabc6beb6bc39d4348886bdcf2d78a102b3f1c9338b07f84389",
+        ] {
+            fs::write(cache_dir.join(format!("1220{}", hash)), "")?;
+        }
+        check()?;
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn http_headers() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_http_headers)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_http_headers() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        assert!(super::http_headers_or_default(tempdir.path(), PlatformKind::Atcoder)?.is_empty());
+
+        fs::write(tempdir.path().join("snowchains.dhall"), "{=}")?;
+        assert!(super::http_headers_or_default(tempdir.path(), PlatformKind::Atcoder)?.is_empty());
+
+        fs::write(
+            tempdir.path().join("snowchains.dhall"),
+            r#"{ httpHeaders = toMap { atcoder = toMap { `User-Agent` = "my-agent" } } }"#,
+        )?;
+        let headers = super::http_headers_or_default(tempdir.path(), PlatformKind::Atcoder)?;
+        assert_eq!(Some("my-agent"), headers["user-agent"].to_str().ok());
+        assert!(
+            super::http_headers_or_default(tempdir.path(), PlatformKind::Codeforces)?.is_empty()
+        );
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn problem_aliases() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_problem_aliases)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_problem_aliases() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        fs::write(
+            tempdir.path().join("snowchains.dhall"),
+            format!(
+                r#"{}
+in  {{ detectServiceFromRelativePathSegments = λ(_ : List Text) → Some "atcoder"
+    , detectContestFromRelativePathSegments = λ(_ : List Text) → Some "abc300"
+    , detectProblemFromRelativePathSegments = λ(_ : List Text) → None Text
+    , detectLanguageFromRelativePathSegments = λ(_ : List Text) → Some "py"
+    , languages = λ(target : Target) → toMap {{ py = language {:?} target }}
+    , problemAliases = toMap {{ abc300 = toMap {{ a = "abc300_a" }} }}
+    }}
+"#,
+                TYPES, "py",
+            ),
+        )?;
+
+        // `judge` and `submit`
+        for mode in [Mode::Debug, Mode::Release] {
+            let (target, _, language, _) = super::target_and_language(
+                tempdir.path(),
+                None,
+                None,
+                None,
+                Some("a"),
+                None,
+                None,
+                mode,
+            )?;
+            assert_eq!("abc300_a", target.problem);
+            assert_eq!("py/abc300-a.py", language.src);
+        }
+
+        let (target, _) = super::target(tempdir.path(), None, None, None, Some("a"))?;
+        assert_eq!("abc300_a", target.problem);
+
+        let (target, _) = super::target(tempdir.path(), None, None, None, Some("b"))?;
+        assert_eq!("b", target.problem);
+
+        let (target, _) = super::target(tempdir.path(), None, None, Some("abc301"), Some("a"))?;
+        assert_eq!("a", target.problem);
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn language_names() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_language_names)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_language_names() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+        fs::write(
+            &project,
+            format!(
+                r"{}
+in  {{ languages =
+        λ(target : Target) →
+          toMap {{ py = language {:?} target, pypy = language {:?} target }}
+    }}
+",
+                TYPES, "py", "pypy",
+            ),
+        )?;
+
+        let path = ConfigPath {
+            project: project.to_str().unwrap().to_owned(),
+            global: None,
+        };
+
+        let detected = Detected {
+            service: Some("atcoder".to_owned()),
+            contest: Some("practice".to_owned()),
+            problem: None,
+            language: Some("pypy".to_owned()),
+        };
+
+        let LanguageNames { names, default } =
+            LanguageNames::new(&path, &detected, None, None, None)?;
+        assert_eq!(["py", "pypy"], *names);
+        assert_eq!(Some("pypy"), default.as_deref());
+
+        let detected = Detected {
+            language: Some("rust".to_owned()),
+            ..detected
+        };
+
+        let LanguageNames { default, .. } = LanguageNames::new(&path, &detected, None, None, None)?;
+        assert_eq!(None, default);
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn statement_language() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_statement_language)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_statement_language() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+
+        fs::write(&project, "{=}")?;
+        assert_eq!(
+            AtcoderStatementLanguage::Ja,
+            super::statement_language(tempdir.path(), None)?,
+        );
+
+        fs::write(&project, "{ statementLanguage = < Ja | En >.En }")?;
+        assert_eq!(
+            AtcoderStatementLanguage::En,
+            super::statement_language(tempdir.path(), None)?,
+        );
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn run_working_directory() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_run_working_directory)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_run_working_directory() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+        fs::create_dir_all(tempdir.path().join("src").join("data"))?;
+
+        fs::write(&project, "{=}")?;
+        assert_eq!(
+            tempdir.path(),
+            super::run_working_directory(tempdir.path(), None, "./src/a.rs")?,
+        );
+
+        fs::write(&project, r#"{ runWorkingDirectory = Some "$srcdir" }"#)?;
+        assert_eq!(
+            tempdir.path().join("src"),
+            super::run_working_directory(tempdir.path(), None, "./src/a.rs")?,
+        );
+
+        fs::write(&project, r#"{ runWorkingDirectory = Some "$srcdir/data" }"#)?;
+        assert_eq!(
+            tempdir.path().join("src").join("data"),
+            super::run_working_directory(tempdir.path(), None, "src/a.rs")?,
+        );
+
+        fs::write(
+            &project,
+            r#"{ runWorkingDirectory = Some "./nonexistent" }"#,
+        )?;
+        super::run_working_directory(tempdir.path(), None, "src/a.rs").unwrap_err();
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_suite_line_ending() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_test_suite_line_ending)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_test_suite_line_ending() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+
+        fs::write(&project, "{=}")?;
+        assert_eq!(
+            LineEnding::Lf,
+            super::test_suite_line_ending(tempdir.path(), None)?,
+        );
+
+        fs::write(
+            &project,
+            "{ testSuiteLineEnding = < Lf | Crlf | Native >.Crlf }",
+        )?;
+        assert_eq!(
+            LineEnding::Crlf,
+            super::test_suite_line_ending(tempdir.path(), None)?,
+        );
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn default_tester() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_default_tester)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_default_tester() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+
+        fs::write(&project, "{=}")?;
+        assert_eq!(None, super::default_tester(tempdir.path(), None)?);
+
+        fs::write(
+            &project,
+            r#"{ defaultTester = Some "python3 ./tester.py" }"#,
+        )?;
+        assert_eq!(
+            Some(Tester {
+                cmd: "python3 ./tester.py".to_owned(),
+                shell: CheckerShell::Bash,
+            }),
+            super::default_tester(tempdir.path(), None)?,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tls() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_tls)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_tls() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let project = tempdir.path().join("snowchains.dhall");
+
+        fs::write(&project, "{=}")?;
+        let tls = super::tls(tempdir.path(), None)?;
+        assert!(tls.root_certificates.is_empty());
+        assert!(!tls.danger_accept_invalid_certs);
+
+        fs::write(
+            tempdir.path().join("ca.pem"),
+            "-----BEGIN CERTIFICATE-----\n",
+        )?;
+        fs::write(
+            &project,
+            r#"{ tlsRootCertificates = [ "./ca.pem" ], tlsDangerAcceptInvalidCerts = True }"#,
+        )?;
+        fs::create_dir(tempdir.path().join("sub"))?;
+        let tls = super::tls(&tempdir.path().join("sub"), None)?;
+        assert_eq!(
+            vec![b"-----BEGIN CERTIFICATE-----\n".to_vec()],
+            tls.root_certificates,
+        );
+        assert!(tls.danger_accept_invalid_certs);
+
+        fs::write(
+            &project,
+            r#"{ tlsRootCertificates = [ "./nonexistent.pem" ] }"#,
+        )?;
+        super::tls(tempdir.path(), None).unwrap_err();
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn with_src() {
+        let language = Language {
+            src: "cpp/a.cpp".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec![
+                    "g++".to_owned(),
+                    "cpp/a.cpp".to_owned(),
+                    "-o".to_owned(),
+                    "cpp/target/a".to_owned(),
+                ]),
+                output: "cpp/target/a".to_owned(),
+            }),
+            run: Command::Args(vec!["cpp/target/a".to_owned()]),
+            languageId: Some("4003".to_owned()),
+        }
+        .with_src("/tmp/scratch.cpp".to_owned())
+        .unwrap();
+
+        assert_eq!("/tmp/scratch.cpp", language.src);
+        assert!(matches!(
+            &language.compile,
+            Some(Compile { command: Command::Args(args), .. })
+                if args == &["g++", "/tmp/scratch.cpp", "-o", "cpp/target/a"]
+        ));
+        assert!(matches!(&language.run, Command::Args(args) if args == &["cpp/target/a"]));
+    }
+
+    #[test]
+    fn with_src_whole_arguments() {
+        let language = Language {
+            src: "a.bash".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec![
+                    "cp".to_owned(),
+                    "a.bash".to_owned(),
+                    "a.bash.out".to_owned(),
+                ]),
+                output: "a.bash.out".to_owned(),
+            }),
+            run: Command::Script(Script {
+                program: "bash".to_owned(),
+                extension: "bash".to_owned(),
+                content: "bash a.bash.out \"$@\" # built from a.bash\nsrc=\"a.bash\"\n".to_owned(),
+            }),
+            languageId: None,
+        }
+        .with_src("/tmp/scratch.bash".to_owned())
+        .unwrap();
+
+        assert!(matches!(
+            &language.compile,
+            Some(Compile { command: Command::Args(args), .. })
+                if args == &["cp", "/tmp/scratch.bash", "a.bash.out"]
+        ));
+        assert!(matches!(
+            &language.run,
+            Command::Script(Script { content, .. })
+                if content == "bash a.bash.out \"$@\" # built from /tmp/scratch.bash\nsrc=\"/tmp/scratch.bash\"\n"
+        ));
+    }
+
+    #[test]
+    fn with_src_not_in_commands() {
+        let err = Language {
+            src: "./cpp/a.cpp".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec![
+                    "g++".to_owned(),
+                    "cpp/a.cpp".to_owned(),
+                    "-o".to_owned(),
+                    "cpp/target/a".to_owned(),
+                ]),
+                output: "cpp/target/a".to_owned(),
+            }),
+            run: Command::Args(vec!["cpp/target/a".to_owned()]),
+            languageId: None,
+        }
+        .with_src("/tmp/scratch.cpp".to_owned())
+        .unwrap_err();
+
+        assert!(err.is::<ConfigError>());
+        assert!(err.to_string().contains("none of the commands have it"));
+    }
+
+    #[test]
+    fn with_problem() {
+        let language = Language {
+            src: "cpp/a.cpp".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec![
+                    "g++".to_owned(),
+                    "-DPROBLEM=$problem".to_owned(),
+                    "cpp/a.cpp".to_owned(),
+                    "-o".to_owned(),
+                    "cpp/target/{problem}".to_owned(),
+                ]),
+                output: "cpp/target/{problem}".to_owned(),
+            }),
+            run: Command::Script(Script {
+                program: "bash".to_owned(),
+                extension: "bash".to_owned(),
+                content: "problem=x\necho {problem} $problem\n".to_owned(),
+            }),
+            languageId: None,
+        }
+        .with_problem("a");
+
+        assert_eq!("cpp/a.cpp", language.src);
+        assert!(matches!(
+            &language.compile,
+            Some(Compile { command: Command::Args(args), .. })
+                if args == &["g++", "-DPROBLEM=a", "cpp/a.cpp", "-o", "cpp/target/a"]
+        ));
+        assert_eq!(
+            Some("cpp/target/a"),
+            language.compile.as_ref().map(|c| &*c.output),
+        );
+        assert!(matches!(
+            &language.run,
+            Command::Script(Script { content, .. }) if content == "problem=x\necho a $problem\n"
+        ));
+    }
+
+    #[test]
+    fn validate() {
+        let language = |output: &str| Language {
+            src: "./cpp/a.cpp".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec![
+                    "g++".to_owned(),
+                    "cpp/a.cpp".to_owned(),
+                    "-o".to_owned(),
+                    output.to_owned(),
+                ]),
+                output: output.to_owned(),
+            }),
+            run: Command::Args(vec![output.to_owned()]),
+            languageId: None,
+        };
+
+        let base_dir = Path::new("/snowchains");
+
+        language("./cpp/target/a").validate(base_dir).unwrap();
+
+        for output in &[
+            "cpp/a.cpp",
+            "./cpp/target/../a.cpp",
+            "/snowchains/cpp/a.cpp",
+        ] {
+            let err = language(output).validate(base_dir).unwrap_err();
+            assert!(err.is::<ConfigError>());
+            assert!(
+                err.to_string().contains("is the same file as `src`"),
+                "{}",
+                err,
+            );
+        }
+
+        let err = language(".").validate(base_dir).unwrap_err();
+        assert!(
+            err.to_string().contains("is the working directory"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn infer_language() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let language = |src: &str| Language {
+            src: src.to_owned(),
+            transpile: None,
+            compile: None,
+            run: Command::Args(vec![]),
+            languageId: None,
+        };
+
+        let languages = btreemap!(
+            "python3".to_owned() => language("py/a.py"),
+            "rust".to_owned() => language("rs/src/bin/a.rs"),
+            "rust-nightly".to_owned() => language("rs-nightly/src/bin/a.rs"),
+        );
+
+        let infer = |source: Option<&str>| {
+            super::infer_language(&languages, tempdir.path(), source.map(Path::new))
+        };
+
+        assert_eq!("python3", infer(Some("/tmp/scratch.py"))?);
+        assert!(infer(Some("scratch.rs"))
+            .unwrap_err()
+            .to_string()
+            .starts_with("`language` is ambiguous among [rust, rust-nightly]."));
+        assert!(infer(Some("scratch.cpp")).is_err());
+        assert!(infer(Some("Makefile")).is_err());
+        assert!(infer(None).is_err());
+
+        fs::create_dir_all(tempdir.path().join("rs-nightly/src/bin"))?;
+        fs::write(tempdir.path().join("rs-nightly/src/bin/a.rs"), "")?;
+        assert_eq!("rust-nightly", infer(None)?);
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn infer_service() {
+        let infer_service = |contest| super::infer_service(contest).unwrap();
+
+        assert_eq!(Some(PlatformKind::Atcoder), infer_service("abc300"));
+        assert_eq!(Some(PlatformKind::Atcoder), infer_service("ARC100"));
+        assert_eq!(Some(PlatformKind::Atcoder), infer_service("agc001"));
+        assert_eq!(None, infer_service("practice"));
+        assert_eq!(None, infer_service(""));
+
+        let err = super::infer_service("9000").unwrap_err();
+        assert!(err.is::<ConfigError>());
+        assert!(
+            err.to_string().contains("Codeforces and yukicoder"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn normalize_contest() {
+        let normalize = |service, contest| {
+            super::normalize_contest(service, contest).map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            Ok("abc100".to_owned()),
+            normalize(PlatformKind::Atcoder, " ABC100\n")
+        );
+        assert_eq!(
+            Ok("chokudai_s001".to_owned()),
+            normalize(PlatformKind::Atcoder, "chokudaiS001"),
+        );
+        assert_eq!(
+            Ok("chokudai_s001".to_owned()),
+            normalize(PlatformKind::Atcoder, "chokudai_s001"),
+        );
+        assert_eq!(
+            Ok("tokiomarine2020".to_owned()),
+            normalize(PlatformKind::Atcoder, "tokiomarine2020"),
+        );
+        assert_eq!(
+            Err(r#"Invalid contest ID for atcoder: "abc 100""#.to_owned()),
+            normalize(PlatformKind::Atcoder, "abc 100"),
+        );
+        assert!(normalize(PlatformKind::Atcoder, "").is_err());
+
+        assert_eq!(
+            Ok("1000".to_owned()),
+            normalize(PlatformKind::Codeforces, "1000")
+        );
+        assert!(normalize(PlatformKind::Codeforces, "abc100").is_err());
+
+        assert_eq!(
+            Ok("276".to_owned()),
+            normalize(PlatformKind::Yukicoder, " 0276 ")
+        );
+        assert_eq!(
+            Err(r#"Invalid contest ID for yukicoder: "no""#.to_owned()),
+            normalize(PlatformKind::Yukicoder, "no"),
+        );
+        assert_eq!(
+            Ok("problems".to_owned()),
+            normalize(PlatformKind::Yukicoder, "problems"),
+        );
+        assert!(normalize(PlatformKind::Codeforces, "problems").is_err());
+    }
+
+    #[test]
+    fn yukicoder_problems_dir() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_yukicoder_problems_dir)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_yukicoder_problems_dir() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        // What the default `snowchains.dhall` detects in `yukicoder/problems/`.
+        fs::write(
+            tempdir.path().join("snowchains.dhall"),
+            format!(
+                r#"{}
+in  {{ detectServiceFromRelativePathSegments = λ(_ : List Text) → Some "yukicoder"
+    , detectContestFromRelativePathSegments = λ(_ : List Text) → Some "problems"
+    , detectProblemFromRelativePathSegments = λ(_ : List Text) → None Text
+    , detectLanguageFromRelativePathSegments = λ(_ : List Text) → Some "py"
+    , languages = λ(target : Target) → toMap {{ py = language {:?} target }}
+    }}
+"#,
+                TYPES, "py",
+            ),
+        )?;
+
+        let cwd = tempdir.path().join("yukicoder").join("problems");
+        fs::create_dir_all(&cwd)?;
+
+        let (target, _, _, _) = super::target_and_language(
+            &cwd,
+            None,
+            None,
+            None,
+            Some("1"),
+            None,
+            None,
+            Mode::Release,
+        )?;
+        assert_eq!(PlatformKind::Yukicoder, target.service);
+        assert_eq!(Some("problems"), target.contest.as_deref());
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn source_path() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        fs::write(tempdir.path().join("scratch.py"), "")?;
+
+        assert_eq!(
+            tempdir.path().join("scratch.py").to_str().unwrap(),
+            super::source_path(tempdir.path(), "./scratch.py".as_ref())?,
+        );
+        assert!(super::source_path(tempdir.path(), "nonexistent.py".as_ref()).is_err());
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn problem_dir() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        fs::write(tempdir.path().join("snowchains.dhall"), "{=}\n")?;
+        fs::create_dir(tempdir.path().join("sub"))?;
+
+        assert_eq!(
+            tempdir.path().join("problems").join("a").join("a.yml"),
+            TestSuites::problem_dir(&tempdir.path().join("sub"), None)?.path(
+                PlatformKind::Atcoder,
+                Some("practice"),
+                "a",
+            )?,
+        );
+
+        let language = Language {
+            src: "./src/bin/a.rs".to_owned(),
+            transpile: None,
+            compile: Some(Compile {
+                command: Command::Args(vec!["rustc".to_owned(), "./src/bin/a.rs".to_owned()]),
+                output: "./a".to_owned(),
+            }),
+            run: Command::Args(vec!["./a".to_owned()]),
+            languageId: None,
+        }
+        .with_problem_dir("a")?;
+
+        assert_eq!("./problems/a/a.rs", language.src);
+        assert!(matches!(
+            &language.compile,
+            Some(Compile { command: Command::Args(args), .. })
+                if args == &["rustc", "./problems/a/a.rs"]
+        ));
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_suites() {
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(run_test_suites)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run_test_suites() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let without_test_suites = tempdir.path().join("a.dhall");
+        fs::write(&without_test_suites, "{=}\n")?;
+
+        let with_test_suites = tempdir.path().join("b.dhall");
+        fs::write(
+            &with_test_suites,
+            r#"{ testSuites = "/srv/tests/$service/$contest/{problem}" }
+"#,
+        )?;
+
+        let path = |project: &Path| ConfigPath {
+            project: project.to_str().unwrap().to_owned(),
+            global: None,
+        };
+
+        let home = Some(Path::new("/home/user"));
+
+        let test_suites = TestSuites::new(&path(&without_test_suites))?;
+        assert_eq!(
+            tempdir
+                .path()
+                .join(".snowchains")
+                .join("tests")
+                .join("atcoder")
+                .join("practice")
+                .join("a.yml"),
+            test_suites.path_with_home(home, PlatformKind::Atcoder, Some("practice"), "a")?,
+        );
+
+        let test_suites = TestSuites::new(&path(&with_test_suites))?;
+        assert_eq!(
+            Path::new("/srv/tests/atcoder/practice/a/a.yml"),
+            test_suites.path_with_home(home, PlatformKind::Atcoder, Some("practice"), "a")?,
+        );
+
+        let test_suites = TestSuites::output_dir(
+            &tempdir.path().join("cwd"),
+            Path::new("./experiment/$contest"),
+        )?;
+        assert_eq!(
+            tempdir
+                .path()
+                .join("cwd")
+                .join("experiment")
+                .join("practice")
+                .join("a.yml"),
+            test_suites.path_with_home(home, PlatformKind::Atcoder, Some("practice"), "a")?,
+        );
+
+        let test_suites = TestSuites {
+            template: "~/tests/$service/$contest".to_owned(),
+            base_dir: tempdir.path().to_owned(),
+        };
+        assert_eq!(
+            Path::new("/home/user/tests/yukicoder/a.yml"),
+            test_suites.path_with_home(home, PlatformKind::Yukicoder, None, "a")?,
+        );
+        assert!(test_suites
+            .path_with_home(None, PlatformKind::Yukicoder, None, "a")
+            .is_err());
+
+        tempdir.close()?;
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn join_under_unc() {
+        assert_eq!(
+            Path::new(r"\\server\share\tests\a.yml"),
+            super::join_under(
+                Path::new(r"C:\work"),
+                Path::new(r"\\server\share\tests\a.yml")
+            ),
+        );
+        assert_eq!(
+            Path::new(r"C:\work\tests\a.yml"),
+            super::join_under(Path::new(r"C:\work"), Path::new(r".\tests\a.yml")),
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn join_under_drive_relative() {
+        assert_eq!(
+            Path::new(r"C:\work\tests\a.yml"),
+            super::join_under(Path::new(r"C:\work"), Path::new(r"C:tests\a.yml")),
+        );
+        assert_eq!(
+            Path::new(r"D:tests\a.yml"),
+            super::join_under(Path::new(r"C:\work"), Path::new(r"D:tests\a.yml")),
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use anyhow::Context as _;
 use serde::{de::DeserializeOwned, Serialize};
-use std::{fs::Metadata, path::Path};
+use std::{
+    fs::{File, Metadata, Permissions},
+    io::{self, Write as _},
+    path::Path,
+};
 
 pub(crate) fn metadata(path: impl AsRef<Path>) -> anyhow::Result<Metadata> {
     let path = path.as_ref();
@@ -23,23 +27,85 @@ pub(crate) fn read_json<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> anyhow:
 pub(crate) fn read_yaml<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> anyhow::Result<T> {
     let path = path.as_ref();
     let content = read_to_string(path)?;
-    serde_yaml::from_str(&content)
-        .with_context(|| format!("Could not parse the YAML at `{}`", path.display()))
+    serde_yaml::from_str(&content).map_err(|err| {
+        let mut msg = format!("Could not parse the YAML at `{}`", path.display());
+        if let Some(location) = err.location() {
+            msg += &format!(", line {}, column {}", location.line(), location.column());
+            if let Some(line) = content.lines().nth(location.line().saturating_sub(1)) {
+                msg += &format!(":\n{} | {}", location.line(), line);
+            }
+        }
+        anyhow::Error::from(err).context(msg)
+    })
 }
 
-pub(crate) fn write(
+/// Writes `contents` to a temporary file in the same directory and renames it to `path`, so that
+/// `path` is never observed partially written.
+///
+/// The files snowchains writes as a whole are written through this, so that an interrupted run
+/// never leaves them broken. The exceptions are the cookie jar, which `snowchains_core` rewrites
+/// in place while holding a lock on it, and the history, which is only appended to.
+pub(crate) fn write_atomically(
     path: impl AsRef<Path>,
     contents: impl AsRef<[u8]>,
     create_dir_all: bool,
 ) -> anyhow::Result<()> {
+    write_atomically_with(path.as_ref(), create_dir_all, |file| {
+        file.write_all(contents.as_ref())
+    })
+}
+
+fn write_atomically_with(
+    path: &Path,
+    create_dir_all: bool,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> anyhow::Result<()> {
+    let dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
     if create_dir_all {
-        if let Some(parent) = path.as_ref().parent() {
-            self::create_dir_all(parent)?;
-        }
+        self::create_dir_all(dir)?;
+    }
+
+    // The temporary file is removed when dropped.
+    let mut tempfile = tempfile::Builder::new()
+        .prefix(".snowchains-")
+        .tempfile_in(dir)
+        .with_context(|| format!("Could not create a temporary file in `{}`", dir.display()))?;
+
+    write(tempfile.as_file_mut())
+        .with_context(|| format!("Could not write `{}`", path.display()))?;
+
+    let permissions = match std::fs::metadata(path) {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(_) => default_permissions(),
+    };
+    if let Some(permissions) = permissions {
+        tempfile
+            .as_file()
+            .set_permissions(permissions)
+            .with_context(|| format!("Could not write `{}`", path.display()))?;
     }
 
-    std::fs::write(&path, contents)
-        .with_context(|| format!("Could not write `{}`", path.as_ref().display()))
+    tempfile
+        .persist(path)
+        .with_context(|| format!("Could not write `{}`", path.display()))?;
+    Ok(())
+}
+
+/// Temporary files are created with `0o600`, which is too strict for new files.
+#[cfg(unix)]
+fn default_permissions() -> Option<Permissions> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    Some(Permissions::from_mode(0o644))
+}
+
+#[cfg(not(unix))]
+fn default_permissions() -> Option<Permissions> {
+    None
 }
 
 pub(crate) fn write_json(
@@ -47,10 +113,84 @@ pub(crate) fn write_json(
     value: impl Serialize,
     create_dir_all: bool,
 ) -> anyhow::Result<()> {
-    write(path, serde_json::to_string(&value)?, create_dir_all)
+    write_atomically(path, serde_json::to_string(&value)?, create_dir_all)
 }
 
 pub(crate) fn create_dir_all(path: impl AsRef<Path>) -> anyhow::Result<()> {
     std::fs::create_dir_all(&path)
         .with_context(|| format!("Could not create `{}`", path.as_ref().display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, io};
+
+    #[test]
+    fn read_yaml_error_points_at_line() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let path = tempdir.path().join("a.yml");
+        fs::write(&path, "type: Batch\nmatch: Lines\ncases: [\n  - in: \"\"\n")?;
+
+        let err = super::read_yaml::<BTreeMap<String, serde_yaml::Value>, _>(&path).unwrap_err();
+        let msg = err.to_string();
+        assert!(
+            msg.starts_with(&format!(
+                "Could not parse the YAML at `{}`, line ",
+                path.display()
+            )),
+            "{}",
+            msg,
+        );
+        assert!(msg.contains("4 |   - in: \"\""), "{}", msg);
+
+        tempdir.close().map_err(Into::into)
+    }
+
+    #[test]
+    fn write_atomically() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let path = tempdir.path().join("a.yml");
+
+        super::write_atomically(&path, "old\n", false)?;
+        assert_eq!("old\n", fs::read_to_string(&path)?);
+
+        // Interrupted after writing a part of the contents.
+        super::write_atomically_with(&path, false, |file| {
+            io::Write::write_all(file, b"par")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+        })
+        .unwrap_err();
+        assert_eq!("old\n", fs::read_to_string(&path)?);
+        assert_eq!(1, fs::read_dir(tempdir.path())?.count());
+
+        super::write_atomically(&path, "new\n", false)?;
+        assert_eq!("new\n", fs::read_to_string(&path)?);
+        assert_eq!(1, fs::read_dir(tempdir.path())?.count());
+
+        tempdir.close().map_err(Into::into)
+    }
+
+    #[test]
+    fn write_atomically_creates_parent_dirs() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let path = tempdir.path().join("a").join("in").join("1.txt");
+
+        super::write_atomically(&path, "1\n", false).unwrap_err();
+        assert!(!tempdir.path().join("a").exists());
+
+        super::write_atomically(&path, "1\n", true)?;
+        assert_eq!("1\n", fs::read_to_string(&path)?);
+        assert_eq!(1, fs::read_dir(path.parent().unwrap())?.count());
+
+        tempdir.close().map_err(Into::into)
+    }
+}
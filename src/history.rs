@@ -0,0 +1,63 @@
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::OpenOptions,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+/// A line of `.snowchains/history.jsonl`, which `judge` appends after each run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct HistoryEntry {
+    /// RFC 3339 in UTC.
+    pub(crate) timestamp: String,
+    pub(crate) contest: Option<String>,
+    pub(crate) problem: String,
+    pub(crate) language: String,
+    pub(crate) total: usize,
+    pub(crate) passed: usize,
+    /// `AC`, `WA`, `TLE`, or `RE`.
+    pub(crate) worst: String,
+    /// `BatchTestCase::content_hash` of the failed test cases, for `judge --failed`. Always empty
+    /// for interactive test suites.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) failed: Vec<String>,
+}
+
+/// `.snowchains/history.jsonl` next to `snowchains.dhall`.
+pub(crate) fn path(base_dir: &Path) -> PathBuf {
+    base_dir.join(".snowchains").join("history.jsonl")
+}
+
+/// Appends `entry` as one line.
+pub(crate) fn append(path: &Path, entry: &HistoryEntry) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::fs::create_dir_all(parent)?;
+    }
+
+    let line = serde_json::to_string(entry)? + "\n";
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_ref()))
+        .with_context(|| format!("Could not append to `{}`", path.display()))
+}
+
+/// Reads all of the entries. Returns an empty `Vec` if `path` does not exist.
+pub(crate) fn read(path: &Path) -> anyhow::Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    crate::fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Could not parse line {} of `{}`", i + 1, path.display()))
+        })
+        .collect()
+}
@@ -1,23 +1,36 @@
-use crate::config;
-use anyhow::bail;
+use crate::{config, history::HistoryEntry};
+use anyhow::{bail, Context as _};
 use az::SaturatingAs as _;
+use futures_util::future::{self, Either};
 use human_size::{Byte, Size};
 use indicatif::ProgressDrawTarget;
 use itertools::Itertools as _;
 use maplit::btreemap;
 use snowchains_core::{
-    color_spec, judge::CommandExpression, testsuite::TestSuite, web::PlatformKind,
+    color_spec,
+    judge::{
+        CommandExpression, DiffAlgorithm, Interrupted, JudgeOptions, PrintOptions, VerdictKind,
+    },
+    testsuite::{
+        BatchTestCase, CheckerShell, DeterministicExpectedOutput, ExpectedOutput, Interaction,
+        InteractiveTestCase, LineEnding, Match, Reference, TestCases, TestSuite, Tester,
+    },
 };
 use std::{
+    borrow::Cow,
     collections::HashSet,
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
+    fmt,
     io::Write as _,
     iter, mem,
-    ops::Deref,
+    ops::{Deref, Range},
     path::{Path, PathBuf},
     process::Stdio,
+    time::{Duration, Instant, SystemTime},
 };
+use tempfile::NamedTempFile;
 use termcolor::{Color, WriteColor};
+use url::Url;
 
 pub(crate) struct Args<W1, W2> {
     pub(crate) stdout: W1,
@@ -27,15 +40,43 @@ pub(crate) struct Args<W1, W2> {
     pub(crate) stderr_process_redirection: fn() -> Stdio,
     pub(crate) progress_draw_target: ProgressDrawTarget,
     pub(crate) base_dir: PathBuf,
-    pub(crate) service: PlatformKind,
-    pub(crate) contest: Option<String>,
-    pub(crate) problem: String,
+    /// Working directory of `run`. See `config::run_working_directory`.
+    pub(crate) run_working_dir: PathBuf,
+    pub(crate) test_suite_path: PathBuf,
     pub(crate) src: String,
     pub(crate) transpile: Option<config::Compile>,
     pub(crate) compile: Option<config::Compile>,
     pub(crate) run: config::Command,
+    /// Appended to the arguments of `run`.
+    pub(crate) run_args: Vec<String>,
     pub(crate) test_case_names: Option<HashSet<String>>,
     pub(crate) display_limit: Size,
+    pub(crate) verbose: bool,
+    pub(crate) quiet: bool,
+    pub(crate) diff_context: usize,
+    pub(crate) show_whitespace: bool,
+    pub(crate) color_diff_algorithm: DiffAlgorithm,
+    /// Passed to `PrintOptions::width`.
+    pub(crate) width: Option<usize>,
+    pub(crate) only: Option<Vec<VerdictKind>>,
+    pub(crate) time_scale: Option<f64>,
+    /// Replaces `match` of the test suite. `Some(None)` accepts any output.
+    pub(crate) r#match: Option<Option<Match>>,
+    pub(crate) trim_cr: bool,
+    pub(crate) fail_fast: bool,
+    /// Judges only the test cases in `HistoryEntry::failed` of the last run for the problem.
+    pub(crate) failed_only: bool,
+    /// Runs the tester against `example` of the interactive test suite before judging.
+    pub(crate) check_tester: bool,
+    /// Judges the test cases one by one without the progress bars, and hides the elapsed times.
+    pub(crate) deterministic: bool,
+    pub(crate) hook: Option<config::Command>,
+    /// Used for interactive test suites without `tester`.
+    pub(crate) default_tester: Option<Tester>,
+    /// Recorded in `.snowchains/history.jsonl`.
+    pub(crate) contest: Option<String>,
+    pub(crate) problem: String,
+    pub(crate) language: String,
 }
 
 pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Result<()> {
@@ -47,40 +88,484 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
         stderr_process_redirection,
         progress_draw_target,
         base_dir,
-        service,
-        contest,
-        problem,
+        run_working_dir,
+        test_suite_path,
         src,
         transpile,
         compile,
         run,
+        run_args,
         test_case_names,
         display_limit,
+        verbose,
+        quiet,
+        diff_context,
+        show_whitespace,
+        color_diff_algorithm,
+        width,
+        only,
+        time_scale,
+        r#match,
+        trim_cr,
+        fail_fast,
+        failed_only,
+        check_tester,
+        deterministic,
+        hook,
+        default_tester,
+        contest,
+        problem,
+        language,
     } = args;
 
-    let test_suite_dir = base_dir
-        .join(".snowchains")
-        .join("tests")
-        .join(service.to_kebab_case_str())
-        .join(contest.as_deref().unwrap_or(""));
-    let test_suite_path = test_suite_dir.join(problem).with_extension("yml");
+    let test_suite_dir = test_suite_path.parent().unwrap_or(&test_suite_path);
+
+    // Taken before `--match` and `--trim-cr` modify the test cases.
+    let mut content_hashes = vec![];
 
-    let test_cases = match crate::fs::read_yaml(&test_suite_path)? {
+    let mut test_cases = match read_test_suite(&test_suite_path)? {
         TestSuite::Batch(test_sutie) => {
-            test_sutie.load_test_cases(&test_suite_dir, test_case_names, |_| {
-                unimplemented!("`SystemTestCases` is not impelemented");
-            })?
+            if verbose {
+                let notes = test_sutie
+                    .cases
+                    .iter()
+                    .flat_map(|case| {
+                        Some((case.name.as_deref().unwrap_or(""), case.note.as_deref()?))
+                    })
+                    .collect::<Vec<_>>();
+                print_description(
+                    &mut stderr,
+                    test_sutie.description.as_deref(),
+                    test_sutie.editorial.as_ref(),
+                    &notes,
+                )?;
+            }
+
+            let (mut test_cases, num_excluded) = test_sutie.load_test_cases_with_num_excluded(
+                test_suite_dir,
+                test_case_names,
+                |_| unimplemented!("`SystemTestCases` is not impelemented"),
+            )?;
+
+            if num_excluded > 0 {
+                writeln!(
+                    stderr,
+                    "Excluded {} test case(s) by `exclude`.",
+                    num_excluded,
+                )?;
+                stderr.flush()?;
+            }
+
+            content_hashes = test_cases.iter().map(BatchTestCase::content_hash).collect();
+
+            if failed_only {
+                let failed = last_failed(&base_dir, contest.as_deref(), &problem)?;
+                let (cases, hashes) = test_cases
+                    .into_iter()
+                    .zip(content_hashes)
+                    .filter(|(_, hash)| failed.contains(hash))
+                    .unzip();
+                test_cases = cases;
+                content_hashes = hashes;
+
+                if test_cases.is_empty() {
+                    bail!("None of the test cases which failed in the last run are left");
+                }
+            }
+
+            TestCases::Batch(test_cases)
+        }
+        TestSuite::Interactive(test_suite) => {
+            if failed_only {
+                bail!("`--failed` is not supported for interactive test suites");
+            }
+
+            if verbose {
+                print_description(
+                    &mut stderr,
+                    test_suite.description.as_deref(),
+                    test_suite.editorial.as_ref(),
+                    &[],
+                )?;
+            }
+
+            if verbose {
+                print_example(&mut stderr, &test_suite.example)?;
+            }
+
+            let test_cases = test_suite
+                .load_test_cases(default_tester.as_ref(), test_case_names)
+                .with_context(|| format!("Could not load {}", test_suite_path.display()))?;
+
+            if check_tester {
+                check_tester_with_example(
+                    &mut stderr,
+                    &test_suite.example,
+                    &test_cases,
+                    &run_working_dir,
+                )?;
+            }
+
+            TestCases::Interactive(test_cases)
         }
-        _ => todo!("currently only `Batch` is supported"),
+        TestSuite::Unsubmittable => bail!("{} is unsubmittable", test_suite_path.display()),
+    };
+
+    if let Some(scale) = time_scale {
+        test_cases.scale_timelimit(scale);
+    }
+
+    if let Some(matching) = r#match {
+        match &mut test_cases {
+            TestCases::Batch(test_cases) => {
+                for test_case in test_cases.iter_mut() {
+                    test_case.set_match(matching.clone());
+
+                    if let ExpectedOutput::Deterministic(DeterministicExpectedOutput::Float {
+                        text,
+                        ..
+                    }) = &test_case.output
+                    {
+                        if !text.split_whitespace().any(|s| s.parse::<f64>().is_ok()) {
+                            stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                            write!(stderr, "warning:")?;
+                            stderr.reset()?;
+                            writeln!(
+                                stderr,
+                                " {:?}: `--match float` is given, but the expected output has no \
+                                 numbers",
+                                test_case.name.as_deref().unwrap_or(""),
+                            )?;
+                        }
+                    }
+                }
+            }
+            TestCases::Interactive(_) => {
+                stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                write!(stderr, "warning:")?;
+                stderr.reset()?;
+                writeln!(stderr, " `--match` is ignored for interactive test suites")?;
+            }
+        }
+        stderr.flush()?;
+    }
+
+    if trim_cr {
+        if let TestCases::Batch(test_cases) = &mut test_cases {
+            test_cases.iter_mut().for_each(BatchTestCase::set_trim_cr);
+        }
+    }
+
+    let (cmd, tempfile, build_time) = prepare(
+        &mut stderr,
+        &test_suite_path,
+        &base_dir,
+        run_working_dir,
+        &src,
+        transpile,
+        compile,
+        run,
+        &run_args,
+        (
+            stdin_process_redirection,
+            stdout_process_redirection,
+            stderr_process_redirection,
+        ),
+    )?;
+
+    let judge_started = Instant::now();
+
+    let num_test_cases = test_cases.len();
+
+    let progress_draw_target = if deterministic || quiet {
+        ProgressDrawTarget::hidden()
+    } else {
+        progress_draw_target
+    };
+
+    let opts = JudgeOptions {
+        fail_fast,
+        jobs: deterministic.then_some(1),
+    };
+
+    let outcome = match &test_cases {
+        TestCases::Batch(test_cases) => snowchains_core::judge::judge(
+            progress_draw_target,
+            tokio::signal::ctrl_c,
+            &cmd,
+            test_cases,
+            opts,
+        )?,
+        TestCases::Interactive(test_cases) => snowchains_core::judge::judge_interactive(
+            progress_draw_target,
+            tokio::signal::ctrl_c,
+            &cmd,
+            test_cases,
+            opts,
+        )?,
+    };
+
+    let timings = Timings {
+        build: build_time,
+        judge: judge_started.elapsed(),
+        num_test_cases: outcome.verdicts.len(),
+    };
+
+    if let Some(tempfile) = tempfile {
+        tempfile.close()?;
+    }
+
+    let shown = match &only {
+        Some(kinds) => Cow::Owned(outcome.filter_by_kind(kinds)),
+        None => Cow::Borrowed(&outcome),
+    };
+
+    writeln!(stderr)?;
+    if outcome.verdicts.len() < num_test_cases {
+        writeln!(
+            stderr,
+            "Skipped {} test case(s) after the first failure.",
+            num_test_cases - outcome.verdicts.len(),
+        )?;
+    }
+    if only.is_some() {
+        writeln!(
+            stderr,
+            "Showing {}/{} test case(s).",
+            shown.verdicts.len(),
+            outcome.verdicts.len(),
+        )?;
+    }
+    stderr.flush()?;
+    shown.print_pretty(
+        stdout,
+        PrintOptions {
+            display_limit: Some(display_limit.into::<Byte>().value().saturating_as()),
+            verbose,
+            diff_context: Some(diff_context),
+            visible_whitespace: show_whitespace,
+            quiet,
+            diff_algorithm: color_diff_algorithm,
+            width,
+            hide_elapsed: deterministic,
+        },
+    )?;
+
+    if !deterministic {
+        writeln!(stderr)?;
+        writeln!(stderr, "{}", timings)?;
+    }
+    stderr.flush()?;
+
+    let summary = JudgeSummary::new(outcome.verdicts.iter().map(|v| v.kind()));
+
+    let entry = HistoryEntry {
+        timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        contest,
+        problem,
+        language,
+        total: summary.total,
+        passed: summary.passed,
+        worst: summary.worst.to_string(),
+        // The skipped test cases of `--fail-fast` are always at the end.
+        failed: content_hashes
+            .iter()
+            .zip(&outcome.verdicts)
+            .filter(|(_, verdict)| verdict.kind() != VerdictKind::Accepted)
+            .map(|(hash, _)| hash.clone())
+            .collect(),
     };
+    if let Err(err) = crate::history::append(&crate::history::path(&base_dir), &entry) {
+        stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+        write!(stderr, "warning:")?;
+        stderr.reset()?;
+        writeln!(stderr, " Could not record the history: {:#}", err)?;
+        stderr.flush()?;
+    }
+
+    if let Some(hook) = &hook {
+        if let Err(err) = run_hook(
+            hook,
+            &base_dir,
+            &test_suite_path,
+            summary,
+            (
+                stdin_process_redirection,
+                stdout_process_redirection,
+                stderr_process_redirection,
+            ),
+        ) {
+            stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+            write!(stderr, "warning:")?;
+            stderr.reset()?;
+            writeln!(stderr, " `judgeHook` failed: {:#}", err)?;
+            stderr.flush()?;
+        }
+    }
+
+    outcome.error_on_fail()
+}
+
+pub(crate) struct StressArgs<W1, W2> {
+    pub(crate) stdout: W1,
+    pub(crate) stderr: W2,
+    pub(crate) stdin_process_redirection: fn() -> Stdio,
+    pub(crate) stdout_process_redirection: fn() -> Stdio,
+    pub(crate) stderr_process_redirection: fn() -> Stdio,
+    pub(crate) progress_draw_target: ProgressDrawTarget,
+    pub(crate) base_dir: PathBuf,
+    /// Working directory of `run`. See `config::run_working_directory`.
+    pub(crate) run_working_dir: PathBuf,
+    pub(crate) test_suite_path: PathBuf,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) src: String,
+    pub(crate) transpile: Option<config::Compile>,
+    pub(crate) compile: Option<config::Compile>,
+    pub(crate) run: config::Command,
+    pub(crate) seeds: Range<u64>,
+    pub(crate) display_limit: Size,
+    pub(crate) solvers: Vec<String>,
+}
 
-    let redirections = (
+pub(crate) fn stress(args: StressArgs<impl WriteColor, impl WriteColor>) -> anyhow::Result<()> {
+    let StressArgs {
+        stdout,
+        mut stderr,
         stdin_process_redirection,
         stdout_process_redirection,
         stderr_process_redirection,
-    );
+        progress_draw_target,
+        base_dir,
+        run_working_dir,
+        test_suite_path,
+        line_ending,
+        src,
+        transpile,
+        compile,
+        run,
+        seeds,
+        display_limit,
+        solvers,
+    } = args;
+
+    let mut test_suite = match read_test_suite(&test_suite_path)? {
+        TestSuite::Batch(test_suite) => test_suite,
+        _ => bail!("{} is not a batch test suite", test_suite_path.display()),
+    };
+
+    let (cmd, tempfile, _) = prepare(
+        &mut stderr,
+        &test_suite_path,
+        &base_dir,
+        run_working_dir,
+        &src,
+        transpile,
+        compile,
+        run,
+        &[],
+        (
+            stdin_process_redirection,
+            stdout_process_redirection,
+            stderr_process_redirection,
+        ),
+    )?;
 
+    let num_seeds = seeds.end.saturating_sub(seeds.start);
+
+    let voting = !solvers.is_empty();
+
+    let failure = if !voting {
+        snowchains_core::judge::stress(
+            progress_draw_target,
+            tokio::signal::ctrl_c,
+            &cmd,
+            &test_suite,
+            seeds,
+        )
+    } else {
+        let solvers = solvers
+            .into_iter()
+            .map(|cmd| Reference {
+                cmd,
+                shell: CheckerShell::Bash,
+            })
+            .collect::<Vec<_>>();
+
+        snowchains_core::judge::vote(
+            progress_draw_target,
+            tokio::signal::ctrl_c,
+            &cmd,
+            &solvers,
+            &test_suite,
+            seeds,
+        )
+    }
+    .with_context(|| format!("Could not stress-test with {}", test_suite_path.display()))?;
+
+    if let Some(tempfile) = tempfile {
+        tempfile.close()?;
+    }
+
+    writeln!(stderr)?;
+
+    let failure = if let Some(failure) = failure {
+        failure
+    } else {
+        writeln!(stderr, "No failures with {} seed(s).", num_seeds)?;
+        stderr.flush()?;
+        return Ok(());
+    };
+
+    stderr.flush()?;
+    failure.outcome.print_pretty(
+        stdout,
+        PrintOptions {
+            display_limit: Some(display_limit.into::<Byte>().value().saturating_as()),
+            diff_context: Some(3),
+            width: Some(crate::shell::stdout_width()),
+            ..PrintOptions::default()
+        },
+    )?;
+
+    let test_case = failure.to_test_case();
+    let name = test_case.name.clone().unwrap_or_default();
+    test_suite.cases.push(test_case);
+    crate::fs::write_atomically(
+        &test_suite_path,
+        TestSuite::Batch(test_suite).to_yaml_pretty_with(line_ending),
+        false,
+    )?;
+
+    writeln!(stderr)?;
+    write!(stderr, "Saved the input as {:?} in ", name)?;
+    stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(stderr, "{}", test_suite_path.display())?;
+    stderr.reset()?;
+    writeln!(stderr)?;
+    stderr.flush()?;
+
+    if voting {
+        bail!("Found a disagreement with seed {}", failure.seed);
+    }
+    bail!("Found a failing input with seed {}", failure.seed);
+}
+
+/// Builds the code and prints the command to run.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn prepare(
+    mut stderr: impl WriteColor,
+    test_suite_path: &Path,
+    base_dir: &Path,
+    run_working_dir: PathBuf,
+    src: &str,
+    transpile: Option<config::Compile>,
+    compile: Option<config::Compile>,
+    run: config::Command,
+    run_args: &[String],
+    redirections: (fn() -> Stdio, fn() -> Stdio, fn() -> Stdio),
+) -> anyhow::Result<(CommandExpression, Option<NamedTempFile>, Option<Duration>)> {
     let mut newline = false;
+    let mut build_time = None;
 
     for (action, msg) in &[(transpile, "Transpiling..."), (compile, "Compiling...")] {
         if let Some(action) = action {
@@ -88,7 +573,9 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
                 writeln!(stderr)?;
             }
 
-            build(&mut stderr, &base_dir, &src, action, redirections, msg)?;
+            let started = Instant::now();
+            build(&mut stderr, base_dir, src, action, redirections, msg)?;
+            *build_time.get_or_insert_with(Duration::default) += started.elapsed();
         }
     }
 
@@ -101,12 +588,12 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
     writeln!(stderr)?;
     stderr.flush()?;
 
-    let (cmd, tempfile) = match run {
+    let (mut cmd, tempfile) = match run {
         config::Command::Args(args) => {
             let cmd = CommandExpression {
                 program: args.get(0).cloned().unwrap_or_default().into(),
                 args: args.into_iter().skip(1).map(Into::into).collect(),
-                cwd: base_dir,
+                cwd: run_working_dir,
                 env: btreemap!(),
             };
 
@@ -127,7 +614,7 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
             let cmd = CommandExpression {
                 program: program.into(),
                 args: vec![tempfile.path().into()],
-                cwd: base_dir,
+                cwd: run_working_dir,
                 env: btreemap!(),
             };
 
@@ -135,6 +622,9 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
         }
     };
 
+    // For `Script`, they are given to the script as `$1`, `$2`, ...
+    cmd.args.extend(run_args.iter().map(Into::into));
+
     stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
     write!(stderr, "Test file:")?;
     stderr.reset()?;
@@ -152,25 +642,312 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> anyhow::Res
 
     stderr.flush()?;
 
-    let outcome = snowchains_core::judge::judge(
-        progress_draw_target,
+    Ok((cmd, tempfile, build_time))
+}
+
+/// `HistoryEntry::failed` of the last run for the problem.
+fn last_failed(
+    base_dir: &Path,
+    contest: Option<&str>,
+    problem: &str,
+) -> anyhow::Result<HashSet<String>> {
+    let entry = crate::history::read(&crate::history::path(base_dir))?
+        .into_iter()
+        .rev()
+        .find(|entry| entry.contest.as_deref() == contest && entry.problem == problem)
+        .with_context(|| "`--failed` requires a previous run for the problem in the history")?;
+
+    if entry.failed.is_empty() {
+        bail!("No test cases failed in the last run ({})", entry.timestamp);
+    }
+    Ok(entry.failed.into_iter().collect())
+}
+
+/// Prints `example` of an interactive test suite, if any.
+pub(crate) fn print_example(
+    mut wtr: impl WriteColor,
+    example: &[Interaction],
+) -> anyhow::Result<()> {
+    if example.is_empty() {
+        return Ok(());
+    }
+
+    wtr.set_color(color_spec!(Bold))?;
+    write!(wtr, "Example interaction:")?;
+    wtr.reset()?;
+    writeln!(wtr)?;
+    for line in example {
+        writeln!(wtr, "{}", line)?;
+    }
+    writeln!(wtr)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Judges `example` as if it were the solver, with the tester of the first test case. This finds a
+/// broken tester before the actual solver is blamed for it.
+fn check_tester_with_example(
+    mut stderr: impl WriteColor,
+    example: &[Interaction],
+    test_cases: &[InteractiveTestCase],
+    cwd: &Path,
+) -> anyhow::Result<()> {
+    if example.is_empty() {
+        bail!("`--check-tester` requires `example` in the test suite");
+    }
+
+    let test_case = test_cases
+        .first()
+        .with_context(|| "`--check-tester` requires a test case")?;
+
+    let cmd = CommandExpression {
+        program: "bash".into(),
+        args: vec!["-c".into(), example_replayer(example).into()],
+        cwd: cwd.to_owned(),
+        env: btreemap!(),
+    };
+
+    let outcome = snowchains_core::judge::judge_interactive(
+        ProgressDrawTarget::hidden(),
         tokio::signal::ctrl_c,
         &cmd,
-        &test_cases,
+        std::slice::from_ref(test_case),
+        JudgeOptions::default(),
     )?;
 
+    stderr.set_color(color_spec!(Bold))?;
+    write!(stderr, "Checking the tester with `example`:")?;
+    stderr.reset()?;
+    writeln!(stderr)?;
+    outcome.print_pretty(&mut stderr, PrintOptions::default())?;
+    writeln!(stderr)?;
+    stderr.flush()?;
+
+    if outcome.error_on_fail().is_err() {
+        bail!("The tester did not accept `example`");
+    }
+    Ok(())
+}
+
+/// Bash script which plays the solver of `example`. It fails if the tester writes a line other
+/// than the one in `example`.
+fn example_replayer(example: &[Interaction]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+
+    example
+        .iter()
+        .map(|line| match line {
+            Interaction::In(line) => format!(
+                "IFS= read -r line && [ \"$line\" = {0} ] || \
+                 {{ printf 'expected %s, got %s\\n' {0} \"$line\" >&2; exit 1; }}\n",
+                quote(line),
+            ),
+            Interaction::Out(line) => format!("printf '%s\\n' {}\n", quote(line)),
+        })
+        .collect()
+}
+
+/// Prints `description` of the test suite and `note`s of the test cases.
+fn print_description(
+    mut stderr: impl WriteColor,
+    description: Option<&str>,
+    editorial: Option<&Url>,
+    notes: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    if let Some(description) = description {
+        stderr.set_color(color_spec!(Bold))?;
+        write!(stderr, "Description:")?;
+        stderr.reset()?;
+        writeln!(stderr)?;
+        for line in description.lines() {
+            writeln!(stderr, "{}", line)?;
+        }
+        writeln!(stderr)?;
+    }
+
+    if let Some(editorial) = editorial {
+        stderr.set_color(color_spec!(Bold))?;
+        write!(stderr, "Editorial:")?;
+        stderr.reset()?;
+        writeln!(stderr, " {}", editorial)?;
+        writeln!(stderr)?;
+    }
+
+    if !notes.is_empty() {
+        stderr.set_color(color_spec!(Bold))?;
+        write!(stderr, "Notes:")?;
+        stderr.reset()?;
+        writeln!(stderr)?;
+        for (name, note) in notes {
+            writeln!(stderr, "{:?}: {}", name, note)?;
+        }
+        writeln!(stderr)?;
+    }
+
+    stderr.flush().map_err(Into::into)
+}
+
+/// Time spent on each phase of `judge`.
+#[derive(Debug, Clone, Copy)]
+struct Timings {
+    build: Option<Duration>,
+    judge: Duration,
+    num_test_cases: usize,
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(build) = self.build {
+            write!(f, "Compiled in {:.1}s, judged", build.as_secs_f64())?;
+        } else {
+            write!(f, "Judged")?;
+        }
+        write!(
+            f,
+            " {} test case(s) in {:.1}s",
+            self.num_test_cases,
+            self.judge.as_secs_f64(),
+        )
+    }
+}
+
+/// Result of `judge` passed to `judgeHook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JudgeSummary {
+    total: usize,
+    passed: usize,
+    /// `RE` > `TLE` > `WA` > `AC`.
+    worst: VerdictKind,
+}
+
+impl JudgeSummary {
+    fn new(kinds: impl IntoIterator<Item = VerdictKind>) -> Self {
+        fn severity(kind: VerdictKind) -> u8 {
+            match kind {
+                VerdictKind::Accepted => 0,
+                VerdictKind::WrongAnswer => 1,
+                VerdictKind::TimelimitExceeded => 2,
+                VerdictKind::RuntimeError => 3,
+            }
+        }
+
+        let mut summary = Self {
+            total: 0,
+            passed: 0,
+            worst: VerdictKind::Accepted,
+        };
+
+        for kind in kinds {
+            summary.total += 1;
+            if kind == VerdictKind::Accepted {
+                summary.passed += 1;
+            }
+            if severity(kind) > severity(summary.worst) {
+                summary.worst = kind;
+            }
+        }
+
+        summary
+    }
+
+    fn env(&self, test_suite_path: &Path) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "SNOWCHAINS_JUDGE_TEST_SUITE",
+                test_suite_path.display().to_string(),
+            ),
+            ("SNOWCHAINS_JUDGE_TOTAL", self.total.to_string()),
+            ("SNOWCHAINS_JUDGE_PASSED", self.passed.to_string()),
+            (
+                "SNOWCHAINS_JUDGE_FAILED",
+                (self.total - self.passed).to_string(),
+            ),
+            ("SNOWCHAINS_JUDGE_WORST", self.worst.to_string()),
+        ]
+    }
+}
+
+/// Runs `judgeHook` with the summary in `SNOWCHAINS_JUDGE_*` environment variables.
+#[allow(clippy::type_complexity)]
+fn run_hook(
+    hook: &config::Command,
+    base_dir: &Path,
+    test_suite_path: &Path,
+    summary: JudgeSummary,
+    (stdin_process_redirection, stdout_process_redirection, stderr_process_redirection): (
+        fn() -> Stdio,
+        fn() -> Stdio,
+        fn() -> Stdio,
+    ),
+) -> anyhow::Result<()> {
+    let (program, args, tempfile) = match hook {
+        config::Command::Args(args) => {
+            let mut args = args.iter().map(OsString::from);
+            let program = args.next().unwrap_or_default();
+            (program, args.collect::<Vec<_>>(), None)
+        }
+        config::Command::Script(config::Script {
+            program,
+            extension,
+            content,
+        }) => {
+            let mut tempfile = tempfile::Builder::new()
+                .prefix("snowchains-judge-hook")
+                .suffix(&format!(".{}", extension))
+                .tempfile()?;
+
+            tempfile.write_all(content.as_ref())?;
+
+            let args = vec![tempfile.path().as_os_str().to_owned()];
+            (program.into(), args, Some(tempfile))
+        }
+    };
+
+    let status = std::process::Command::new(&program)
+        .args(&args)
+        .envs(summary.env(test_suite_path))
+        .current_dir(base_dir)
+        .stdin(stdin_process_redirection())
+        .stdout(stdout_process_redirection())
+        .stderr(stderr_process_redirection())
+        .status()
+        .with_context(|| format!("Could not execute {}", shell_escape_args(&program, &args)))?;
+
     if let Some(tempfile) = tempfile {
         tempfile.close()?;
     }
 
-    writeln!(stderr)?;
-    stderr.flush()?;
-    outcome.print_pretty(
-        stdout,
-        Some(display_limit.into::<Byte>().value().saturating_as()),
-    )?;
+    if !status.success() {
+        bail!(
+            "{} {}",
+            shell_escape_args(&program, &args),
+            if let Some(code) = status.code() {
+                format!("exited with code {}", code)
+            } else {
+                "was terminated by signal".to_owned()
+            },
+        );
+    }
 
-    outcome.error_on_fail()
+    Ok(())
+}
+
+/// Reads the test suite, telling to retrieve it first if it does not exist.
+pub(crate) fn read_test_suite(path: &Path) -> anyhow::Result<TestSuite> {
+    let dir = path.parent().unwrap_or(path);
+    if !dir.exists() {
+        bail!(
+            "The test suite directory {} does not exist. Run `snowchains retrieve testcases` first",
+            dir.display(),
+        );
+    }
+    if !path.exists() {
+        bail!(
+            "{} does not exist. Run `snowchains retrieve testcases` first",
+            path.display(),
+        );
+    }
+    crate::fs::read_yaml(path)
 }
 
 pub(crate) fn transpile(
@@ -196,6 +973,24 @@ pub(crate) fn transpile(
     )
 }
 
+/// Transpiles and compiles `src` without running it. Outputs newer than `src` are not rebuilt.
+#[allow(clippy::type_complexity)]
+pub(crate) fn compile(
+    mut stderr: impl WriteColor,
+    base_dir: &Path,
+    src: &str,
+    transpile: Option<&config::Compile>,
+    compile: Option<&config::Compile>,
+    redirections: (fn() -> Stdio, fn() -> Stdio, fn() -> Stdio),
+) -> anyhow::Result<()> {
+    for (action, msg) in &[(transpile, "Transpiling..."), (compile, "Compiling...")] {
+        if let Some(action) = action {
+            build(&mut stderr, base_dir, src, action, redirections, msg)?;
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::type_complexity)]
 fn build(
     mut stderr: impl WriteColor,
@@ -312,16 +1107,34 @@ fn run_command<S1: AsRef<OsStr>, S2: AsRef<OsStr>, I: IntoIterator<Item = S2>, W
 
     stderr.flush()?;
 
-    let status = std::process::Command::new(program)
-        .args(&args)
-        .current_dir(base_dir)
-        .stdin(stdin_process_redirection)
-        .stdout(stdout_process_redirection)
-        .stderr(stderr_process_redirection)
-        .status()?;
+    // The command stays in our process group so that Ctrl-C reaches it as well, and we wait for it
+    // instead of being killed.
+    let status = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()?
+        .block_on(async {
+            let mut child = tokio::process::Command::new(program)
+                .args(&args)
+                .current_dir(base_dir)
+                .stdin(stdin_process_redirection)
+                .stdout(stdout_process_redirection)
+                .stderr(stderr_process_redirection)
+                .kill_on_drop(true)
+                .spawn()?;
+
+            let result =
+                future::select(Box::pin(child.wait()), Box::pin(tokio::signal::ctrl_c())).await;
+            match result {
+                Either::Left((status, _)) => Ok(status?),
+                Either::Right((result, _)) => {
+                    result?;
+                    Err(anyhow::Error::from(Interrupted))
+                }
+            }
+        })?;
 
     if !status.success() {
-        bail!(
+        bail!(BuildFailure(format!(
             "{} {}",
             shell_escaped,
             if let Some(code) = status.code() {
@@ -329,18 +1142,174 @@ fn run_command<S1: AsRef<OsStr>, S2: AsRef<OsStr>, I: IntoIterator<Item = S2>, W
             } else {
                 "was terminated by signal".to_owned()
             },
-        );
+        )));
     }
 
     Ok(())
 }
 
+/// A transpile or compile command failed.
+#[derive(Debug)]
+pub(crate) struct BuildFailure(String);
+
+impl fmt::Display for BuildFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BuildFailure {}
+
 fn shell_escape_args(program: impl AsRef<OsStr>, args: &[impl AsRef<OsStr>]) -> String {
-    format!(
-        "`{}`",
-        iter::once(program.as_ref())
-            .chain(args.iter().map(AsRef::as_ref))
-            .map(|s| shell_escape::unix::escape(s.to_string_lossy()))
-            .format(" "),
-    )
+    format!("`{}`", shell_words(program, args))
+}
+
+fn shell_words(program: impl AsRef<OsStr>, args: &[impl AsRef<OsStr>]) -> String {
+    iter::once(program.as_ref())
+        .chain(args.iter().map(AsRef::as_ref))
+        .map(|s| shell_escape::unix::escape(s.to_string_lossy()))
+        .join(" ")
+}
+
+/// Prints `cd`, `transpile`, `compile`, and `run` as lines of a shell script, instead of running
+/// them.
+///
+/// The content of a `Script` is given as `<(printf %s '...')`, which needs Bash.
+pub(crate) fn print_commands(
+    mut stdout: impl WriteColor,
+    base_dir: &Path,
+    transpile: Option<&config::Compile>,
+    compile: Option<&config::Compile>,
+    run_working_dir: &Path,
+    run: &config::Command,
+    run_args: &[String],
+) -> anyhow::Result<()> {
+    let command_line = |command: &config::Command, extra_args: &[String]| -> String {
+        match command {
+            config::Command::Args(args) => shell_words(
+                args.first().map(Deref::deref).unwrap_or(""),
+                &args.iter().skip(1).chain(extra_args).collect::<Vec<_>>(),
+            ),
+            config::Command::Script(config::Script {
+                program, content, ..
+            }) => format!(
+                "{} <(printf %s {}) {}",
+                shell_escape::unix::escape(program.into()),
+                shell_escape::unix::escape(content.into()),
+                extra_args
+                    .iter()
+                    .map(|s| shell_escape::unix::escape(s.into()))
+                    .join(" "),
+            )
+            .trim_end()
+            .to_owned(),
+        }
+    };
+
+    writeln!(
+        stdout,
+        "cd {}",
+        shell_escape::unix::escape(base_dir.to_string_lossy()),
+    )?;
+    for config::Compile { command, .. } in transpile.into_iter().chain(compile) {
+        writeln!(stdout, "{}", command_line(command, &[]))?;
+    }
+    if run_working_dir != base_dir {
+        writeln!(
+            stdout,
+            "cd {}",
+            shell_escape::unix::escape(run_working_dir.to_string_lossy()),
+        )?;
+    }
+    writeln!(stdout, "{}", command_line(run, run_args))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::judge::{JudgeSummary, Timings};
+    use snowchains_core::judge::VerdictKind;
+    use std::time::Duration;
+
+    #[test]
+    fn judge_summary() {
+        assert_eq!(
+            JudgeSummary {
+                total: 0,
+                passed: 0,
+                worst: VerdictKind::Accepted,
+            },
+            JudgeSummary::new(vec![]),
+        );
+
+        assert_eq!(
+            JudgeSummary {
+                total: 4,
+                passed: 1,
+                worst: VerdictKind::TimelimitExceeded,
+            },
+            JudgeSummary::new(vec![
+                VerdictKind::WrongAnswer,
+                VerdictKind::Accepted,
+                VerdictKind::TimelimitExceeded,
+                VerdictKind::WrongAnswer,
+            ]),
+        );
+    }
+
+    #[test]
+    fn read_missing_test_suite() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let path = tempdir.path().join("atcoder").join("abc100").join("a.yml");
+
+        assert_eq!(
+            format!(
+                "The test suite directory {} does not exist. Run `snowchains retrieve testcases` \
+                 first",
+                path.parent().unwrap().display(),
+            ),
+            crate::judge::read_test_suite(&path)
+                .unwrap_err()
+                .to_string(),
+        );
+
+        std::fs::create_dir_all(path.parent().unwrap())?;
+
+        assert_eq!(
+            format!(
+                "{} does not exist. Run `snowchains retrieve testcases` first",
+                path.display(),
+            ),
+            crate::judge::read_test_suite(&path)
+                .unwrap_err()
+                .to_string(),
+        );
+
+        tempdir.close().map_err(Into::into)
+    }
+
+    #[test]
+    fn timings() {
+        let timings = Timings {
+            build: Some(Duration::from_millis(2100)),
+            judge: Duration::from_millis(400),
+            num_test_cases: 10,
+        };
+
+        assert_eq!(
+            "Compiled in 2.1s, judged 10 test case(s) in 0.4s",
+            timings.to_string(),
+        );
+
+        let timings = Timings {
+            build: None,
+            ..timings
+        };
+
+        assert_eq!("Judged 10 test case(s) in 0.4s", timings.to_string());
+    }
 }
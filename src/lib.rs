@@ -1,18 +1,36 @@
 mod commands;
 mod config;
 mod fs;
+mod history;
 mod judge;
 pub mod shell;
 mod web;
 
 pub use crate::commands::{
-    init::OptInit, judge::OptJudge, login::OptLogin, participate::OptParticipate,
+    append_testcases::OptAppendTestcases,
+    convert_testsuite::{OptConvertTestsuite, TestSuiteType},
+    format_testsuite::OptFormatTestsuite,
+    history::OptHistory,
+    init::OptInit,
+    judge::{MatchOverride, OptJudge},
+    list_languages::OptListLanguages,
+    list_testcases::OptListTestcases,
+    login::OptLogin,
+    participate::OptParticipate,
+    prepare::OptPrepare,
     retrieve_languages::OptRetrieveLanguages,
     retrieve_submission_summaries::OptRetrieveSubmissionSummaries,
-    retrieve_testcases::OptRetrieveTestcases, submit::OptSubmit,
-    watch_submissions::OptWatchSubmissions, xtask::OptXtask,
+    retrieve_testcases::OptRetrieveTestcases,
+    stress::OptStress,
+    submit::OptSubmit,
+    watch_submissions::OptWatchSubmissions,
+    xtask::OptXtask,
+};
+use std::{
+    env,
+    io::BufRead,
+    path::{Path, PathBuf},
 };
-use std::{env, io::BufRead, path::PathBuf};
 use structopt::{
     clap::{self, AppSettings},
     StructOpt,
@@ -22,9 +40,52 @@ use termcolor::WriteColor;
 
 pub const STACK_SIZE: usize = 128 * 1024 * 1024;
 
+/// Exit status of `snowchains(1)` for an error.
+///
+/// | Code | Meaning                                                  |
+/// | ---- | -------------------------------------------------------- |
+/// | 1    | Other errors                                             |
+/// | 2    | Some of the test cases failed                            |
+/// | 3    | Could not find or evaluate `snowchains.dhall`            |
+/// | 4    | Could not communicate with or scrape the service         |
+/// | 5    | The transpile or compile command failed                  |
+/// | 130  | Interrupted by Ctrl-C                                    |
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    return if has::<snowchains_core::judge::Interrupted>(err) {
+        130
+    } else if has::<snowchains_core::judge::JudgeFailure>(err) {
+        2
+    } else if has::<config::ConfigError>(err) || has::<serde_dhall::Error>(err) {
+        3
+    } else if has::<snowchains_core::web::ScrapeError>(err)
+        || has::<snowchains_core::web::ServiceError>(err)
+        || has::<reqwest::Error>(err)
+    {
+        4
+    } else if has::<judge::BuildFailure>(err) {
+        5
+    } else {
+        1
+    };
+
+    fn has<E: std::error::Error + Send + Sync + 'static>(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<E>().is_some() || err.chain().any(|e| e.is::<E>())
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, global_setting = AppSettings::DeriveDisplayOrder)]
-pub enum Opt {
+pub struct Opt {
+    /// Fails immediately instead of communicating with the services
+    #[structopt(long, global = true)]
+    pub offline: bool,
+
+    #[structopt(subcommand)]
+    pub command: OptCommand,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum OptCommand {
     /// Create a new config file
     #[structopt(author, visible_alias("i"))]
     Init(OptInit),
@@ -44,14 +105,42 @@ pub enum Opt {
     #[structopt(author, visible_alias("d"))]
     Download(OptRetrieveTestcases),
 
+    /// Downloads the test suites and compiles the code for every problem in a contest
+    #[structopt(author)]
+    Prepare(OptPrepare),
+
     /// Watches data
     #[structopt(author, visible_alias("w"))]
     Watch(OptWatch),
 
+    /// Appends data
+    #[structopt(author)]
+    Append(OptAppend),
+
+    /// Lists data in the config file
+    #[structopt(author)]
+    List(OptList),
+
+    /// Converts data
+    #[structopt(author)]
+    Convert(OptConvert),
+
+    /// Formats data
+    #[structopt(author)]
+    Format(OptFormat),
+
     /// Tests code
     #[structopt(author, visible_aliases(&["j", "test", "t"]))]
     Judge(OptJudge),
 
+    /// Prints the recent results of `judge`
+    #[structopt(author)]
+    History(OptHistory),
+
+    /// Generates random inputs and judges them against `reference`
+    #[structopt(author)]
+    Stress(OptStress),
+
     /// Submits code
     #[structopt(author, visible_alias("s"))]
     Submit(OptSubmit),
@@ -76,6 +165,38 @@ pub enum OptRetrieve {
     SubmissionSummaries(OptRetrieveSubmissionSummaries),
 }
 
+#[derive(StructOpt, Debug)]
+pub enum OptAppend {
+    /// Appends test cases from the text files in a directory
+    #[structopt(author, visible_alias("t"))]
+    Testcases(OptAppendTestcases),
+}
+
+#[derive(StructOpt, Debug)]
+pub enum OptList {
+    /// Lists the languages, marking the default one
+    #[structopt(author, visible_alias("l"))]
+    Languages(OptListLanguages),
+
+    /// Lists the test cases, and `example` of an interactive problem
+    #[structopt(author, visible_alias("t"))]
+    Testcases(OptListTestcases),
+}
+
+#[derive(StructOpt, Debug)]
+pub enum OptConvert {
+    /// Converts a test suite between `Batch` and `Interactive`
+    #[structopt(author, visible_alias("t"))]
+    Testsuite(OptConvertTestsuite),
+}
+
+#[derive(StructOpt, Debug)]
+pub enum OptFormat {
+    /// Rewrites a test suite in the canonical form without changing the content
+    #[structopt(author, visible_alias("t"))]
+    Testsuite(OptFormatTestsuite),
+}
+
 #[derive(StructOpt, Debug)]
 pub enum OptWatch {
     /// Watches your submissions
@@ -87,11 +208,18 @@ impl Opt {
     pub fn from_args_with_workaround_for_clap_issue_1538() -> Self {
         let mut args = env::args_os().collect::<Vec<_>>();
 
+        // `--offline` may come before the subcommand.
+        let i = args
+            .iter()
+            .skip(1)
+            .position(|arg| arg != "--offline")
+            .map_or(args.len(), |i| i + 1);
+
         Self::from_iter_safe(&args).unwrap_or_else(|clap::Error { kind, .. }| {
             if matches!(
-                args.get(1).and_then(|s| s.to_str()),
+                args.get(i).and_then(|s| s.to_str()),
                 Some("x") | Some("xtask")
-            ) && matches!(args.get(2).and_then(|s| s.to_str()), Some(s) if !s.starts_with('-'))
+            ) && matches!(args.get(i + 1).and_then(|s| s.to_str()), Some(s) if !s.starts_with('-'))
                 && matches!(
                     kind,
                     clap::ErrorKind::UnknownArgument
@@ -99,7 +227,7 @@ impl Opt {
                         | clap::ErrorKind::VersionDisplayed
                 )
             {
-                args.insert(3, "--".into());
+                args.insert(i + 2, "--".into());
             }
 
             Self::from_iter(args)
@@ -107,6 +235,12 @@ impl Opt {
     }
 
     pub fn color(&self) -> crate::ColorChoice {
+        self.command.color()
+    }
+}
+
+impl OptCommand {
+    fn color(&self) -> crate::ColorChoice {
         match *self {
             Self::Init(OptInit { color, .. })
             | Self::Login(OptLogin { color, .. })
@@ -118,12 +252,70 @@ impl Opt {
                 ..
             }))
             | Self::Download(OptRetrieveTestcases { color, .. })
+            | Self::Prepare(OptPrepare { color, .. })
             | Self::Watch(OptWatch::Submissions(OptWatchSubmissions { color, .. }))
+            | Self::Append(OptAppend::Testcases(OptAppendTestcases { color, .. }))
+            | Self::List(OptList::Languages(OptListLanguages { color, .. }))
+            | Self::List(OptList::Testcases(OptListTestcases { color, .. }))
+            | Self::Convert(OptConvert::Testsuite(OptConvertTestsuite { color, .. }))
+            | Self::Format(OptFormat::Testsuite(OptFormatTestsuite { color, .. }))
             | Self::Judge(OptJudge { color, .. })
+            | Self::History(OptHistory { color, .. })
+            | Self::Stress(OptStress { color, .. })
             | Self::Submit(OptSubmit { color, .. }) => color,
             Self::Xtask(_) => crate::ColorChoice::Auto,
         }
     }
+
+    /// `--config`.
+    fn config(&self) -> Option<&Path> {
+        match self {
+            Self::Retrieve(OptRetrieve::Languages(OptRetrieveLanguages { config, .. }))
+            | Self::Retrieve(OptRetrieve::Testcases(OptRetrieveTestcases { config, .. }))
+            | Self::Retrieve(OptRetrieve::SubmissionSummaries(OptRetrieveSubmissionSummaries {
+                config,
+                ..
+            }))
+            | Self::Download(OptRetrieveTestcases { config, .. })
+            | Self::Prepare(OptPrepare { config, .. })
+            | Self::Watch(OptWatch::Submissions(OptWatchSubmissions { config, .. }))
+            | Self::Append(OptAppend::Testcases(OptAppendTestcases { config, .. }))
+            | Self::List(OptList::Languages(OptListLanguages { config, .. }))
+            | Self::List(OptList::Testcases(OptListTestcases { config, .. }))
+            | Self::Convert(OptConvert::Testsuite(OptConvertTestsuite { config, .. }))
+            | Self::Format(OptFormat::Testsuite(OptFormatTestsuite { config, .. }))
+            | Self::Judge(OptJudge { config, .. })
+            | Self::History(OptHistory { config, .. })
+            | Self::Stress(OptStress { config, .. })
+            | Self::Submit(OptSubmit { config, .. }) => config.as_deref(),
+            Self::Init(_) | Self::Login(_) | Self::Participate(_) | Self::Xtask(_) => None,
+        }
+    }
+
+    /// Whether the command communicates with the services.
+    fn communicates(&self) -> bool {
+        match self {
+            Self::Login(_)
+            | Self::Participate(_)
+            | Self::Retrieve(OptRetrieve::Languages(_))
+            | Self::Retrieve(OptRetrieve::SubmissionSummaries(_))
+            | Self::Watch(_) => true,
+            Self::Retrieve(OptRetrieve::Testcases(OptRetrieveTestcases { dry_run, .. }))
+            | Self::Download(OptRetrieveTestcases { dry_run, .. })
+            | Self::Submit(OptSubmit { dry_run, .. }) => !dry_run,
+            // `prepare` does not communicate if all of the test suites have been downloaded.
+            Self::Init(_)
+            | Self::Prepare(_)
+            | Self::Append(_)
+            | Self::List(_)
+            | Self::Convert(_)
+            | Self::Format(_)
+            | Self::Judge(_)
+            | Self::History(_)
+            | Self::Stress(_)
+            | Self::Xtask(_) => false,
+        }
+    }
 }
 
 #[derive(EnumVariantNames, EnumString, strum::Display, Debug, Clone, Copy)]
@@ -143,19 +335,945 @@ pub fn run<R: BufRead, W1: WriteColor, W2: WriteColor>(
     opt: Opt,
     ctx: Context<R, W1, W2>,
 ) -> anyhow::Result<()> {
-    match opt {
-        Opt::Init(opt) => commands::init::run(opt, ctx),
-        Opt::Login(opt) => commands::login::run(opt, ctx),
-        Opt::Participate(opt) => commands::participate::run(opt, ctx),
-        Opt::Retrieve(OptRetrieve::Languages(opt)) => commands::retrieve_languages::run(opt, ctx),
-        Opt::Retrieve(OptRetrieve::Testcases(opt)) => commands::retrieve_testcases::run(opt, ctx),
-        Opt::Retrieve(OptRetrieve::SubmissionSummaries(opt)) => {
+    let Opt { offline, command } = opt;
+
+    if offline && command.communicates() {
+        anyhow::bail!("This command communicates with the service, which `--offline` forbids");
+    }
+
+    if offline {
+        config::ensure_remote_imports_cached(&ctx.cwd, command.config())?;
+    }
+
+    match command {
+        OptCommand::Init(opt) => commands::init::run(opt, ctx),
+        OptCommand::Login(opt) => commands::login::run(opt, ctx),
+        OptCommand::Participate(opt) => commands::participate::run(opt, ctx),
+        OptCommand::Retrieve(OptRetrieve::Languages(opt)) => {
+            commands::retrieve_languages::run(opt, ctx)
+        }
+        OptCommand::Retrieve(OptRetrieve::Testcases(opt)) => {
+            commands::retrieve_testcases::run(opt, ctx)
+        }
+        OptCommand::Retrieve(OptRetrieve::SubmissionSummaries(opt)) => {
             commands::retrieve_submission_summaries::run(opt, ctx)
         }
-        Opt::Download(opt) => commands::retrieve_testcases::run(opt, ctx),
-        Opt::Watch(OptWatch::Submissions(opt)) => commands::watch_submissions::run(opt, ctx),
-        Opt::Judge(opt) => commands::judge::run(opt, ctx),
-        Opt::Submit(opt) => commands::submit::run(opt, ctx),
-        Opt::Xtask(opt) => commands::xtask::run(opt, ctx),
+        OptCommand::Download(opt) => commands::retrieve_testcases::run(opt, ctx),
+        OptCommand::Prepare(opt) => commands::prepare::run(opt, offline, ctx),
+        OptCommand::Watch(OptWatch::Submissions(opt)) => commands::watch_submissions::run(opt, ctx),
+        OptCommand::Append(OptAppend::Testcases(opt)) => commands::append_testcases::run(opt, ctx),
+        OptCommand::List(OptList::Languages(opt)) => commands::list_languages::run(opt, ctx),
+        OptCommand::List(OptList::Testcases(opt)) => commands::list_testcases::run(opt, ctx),
+        OptCommand::Convert(OptConvert::Testsuite(opt)) => {
+            commands::convert_testsuite::run(opt, ctx)
+        }
+        OptCommand::Format(OptFormat::Testsuite(opt)) => commands::format_testsuite::run(opt, ctx),
+        OptCommand::Judge(opt) => commands::judge::run(opt, ctx),
+        OptCommand::History(opt) => commands::history::run(opt, ctx),
+        OptCommand::Stress(opt) => commands::stress::run(opt, ctx),
+        OptCommand::Submit(opt) => commands::submit::run(opt, ctx),
+        OptCommand::Xtask(opt) => commands::xtask::run(opt, ctx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::prepare::{Download, OptPrepare},
+        history::HistoryEntry,
+        shell::TtyOrPiped,
+        Context, Opt, OptRetrieveTestcases,
+    };
+    use anyhow::Context as _;
+    use snowchains_core::web::PlatformKind;
+    use std::{
+        fs,
+        io::{self, BufRead, Write},
+        path::Path,
+        process::Stdio,
+        thread,
+    };
+    use structopt::StructOpt as _;
+    use termcolor::{NoColor, WriteColor};
+
+    #[test]
+    fn exit_code() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo 1\n"),
+            Some("type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"2\\n\"\n"),
+            |cwd| {
+                let judge = |cwd: &Path| -> _ {
+                    let err = run(&["snowchains", "judge"], cwd)
+                        .err()
+                        .with_context(|| "expected an error")?;
+                    anyhow::Ok(crate::exit_code(&err))
+                };
+
+                let empty = tempfile::Builder::new()
+                    .prefix("snowchains-tests-")
+                    .tempdir()?;
+                assert_eq!(3, judge(empty.path())?);
+                empty.close()?;
+
+                assert_eq!(2, judge(cwd)?);
+
+                fs::write(
+                    cwd.join("snowchains.dhall"),
+                    snowchains_dhall(
+                        r#"Some { command = Command.Args [ "false" ], output = "a.out" }"#,
+                    ),
+                )?;
+
+                assert_eq!(5, judge(cwd)?);
+
+                let err = reqwest::blocking::get("http://127.0.0.1:0/")
+                    .map_err(anyhow::Error::from)
+                    .with_context(|| "Could not connect")
+                    .unwrap_err();
+
+                assert_eq!(4, crate::exit_code(&err));
+                assert_eq!(
+                    130,
+                    crate::exit_code(
+                        &anyhow::Error::from(snowchains_core::judge::Interrupted)
+                            .context("Could not judge"),
+                    ),
+                );
+                assert_eq!(1, crate::exit_code(&anyhow::anyhow!("other")));
+                Ok(())
+            },
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn judge_prebuilt_binary() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        with_practice_fixture(
+            Some(r#"Some { command = Command.Args [ "false" ], output = "a.out" }"#),
+            Some("echo 1\n"),
+            Some("type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"2\\n\"\n"),
+            |cwd| {
+                let prebuilt = cwd.join("prebuilt");
+                fs::write(&prebuilt, "#!/bin/sh\necho 2\n")?;
+
+                let err = run(&["snowchains", "judge", "--bin", "./prebuilt"], cwd).unwrap_err();
+                assert!(err.to_string().ends_with("is not executable"), "{}", err);
+
+                fs::set_permissions(&prebuilt, fs::Permissions::from_mode(0o755))?;
+                run(&["snowchains", "judge", "--bin", "./prebuilt"], cwd)
+            },
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn judge_hook() {
+        with_practice_fixture(
+            None,
+            Some("echo 1\n"),
+            Some(
+                "type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n  - in: \"\"\n    out: \"2\\n\"\n",
+            ),
+            |cwd| {
+                let write_config = |hook: &str| {
+                    fs::write(
+                        cwd.join("snowchains.dhall"),
+                        format!(
+                            "({}) // {{ judgeHook = Some ({}) }}",
+                            snowchains_dhall("None Compile"),
+                            hook,
+                        ),
+                    )
+                };
+
+                write_config(
+                    r#"< Args : List Text | Script : { program : Text, extension : Text, content : Text } >.Args [ "sh", "-c", "echo $SNOWCHAINS_JUDGE_TOTAL $SNOWCHAINS_JUDGE_PASSED $SNOWCHAINS_JUDGE_FAILED $SNOWCHAINS_JUDGE_WORST > summary.txt" ]"#,
+                )?;
+
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                assert_eq!("2 1 1 WA\n", fs::read_to_string(cwd.join("summary.txt"))?);
+
+                // A failing hook only warns.
+                write_config(
+                    r#"< Args : List Text | Script : { program : Text, extension : Text, content : Text } >.Script { program = "sh", extension = "sh", content = "exit 1" }"#,
+                )?;
+                fs::write(
+                    cwd.join(".snowchains/tests/atcoder/practice/a.yml"),
+                    "type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n",
+                )?;
+                run(&["snowchains", "judge"], cwd)
+            },
+        );
+    }
+
+    #[test]
+    fn judge_trim_cr() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("printf '1\\r\\n'\n"),
+            Some("type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n"),
+            |cwd| {
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                run(&["snowchains", "judge", "--trim-cr"], cwd)
+            },
+        );
+    }
+
+    #[test]
+    fn judge_match_override() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo '1  2'\n"),
+            Some("type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"1 2\\n\"\n"),
+            |cwd| {
+                let path = cwd.join(".snowchains/tests/atcoder/practice/a.yml");
+                let yaml = fs::read_to_string(&path)?;
+
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                run(&["snowchains", "judge", "--match", "tokens"], cwd)?;
+                run(&["snowchains", "judge", "--match", "any"], cwd)?;
+                run(&["snowchains", "judge", "--match", "float:1e-6"], cwd)?;
+                run(&["snowchains", "judge", "--match", "exact"], cwd).unwrap_err();
+                run(&["snowchains", "judge", "--match", "regex"], cwd).unwrap_err();
+
+                // The test suite is left as it is.
+                assert_eq!(yaml, fs::read_to_string(&path)?);
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn judge_run_working_directory() {
+        with_practice_fixture(
+            None,
+            None,
+            Some("type: Batch\nmatch: Lines\ncases:\n  - in: \"\"\n    out: \"42\\n\"\n"),
+            |cwd| {
+                let write_config = |run_working_directory: &str| {
+                    fs::write(
+                        cwd.join("snowchains.dhall"),
+                        format!(
+                            "({}) // {{ runWorkingDirectory = {} }}",
+                            snowchains_dhall("None Compile")
+                                .replace(r#"src = "a.bash""#, r#"src = "src/a.bash""#),
+                            run_working_directory,
+                        ),
+                    )
+                };
+
+                fs::create_dir_all(cwd.join("src"))?;
+                fs::write(cwd.join("src/a.bash"), "cat ./table.txt\n")?;
+                fs::write(cwd.join("src/table.txt"), "42\n")?;
+
+                // `bash a.bash` and `./table.txt` are only found in `src`.
+                write_config("None Text")?;
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+
+                write_config(r#"Some "$srcdir""#)?;
+                run(&["snowchains", "judge"], cwd)?;
+
+                write_config(r#"Some "./src""#)?;
+                run(&["snowchains", "judge"], cwd)
+            },
+        );
+    }
+
+    #[test]
+    fn judge_cases_from() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("read n\necho $((n * n))\n"),
+            Some("type: Batch\nmatch: Lines\ncases_from: ./a/cases.yml\n"),
+            |cwd| {
+                let dir = cwd.join(".snowchains/tests/atcoder/practice/a");
+                fs::create_dir_all(&dir)?;
+                fs::write(
+                    dir.join("cases.yml"),
+                    "- in: \"5\\n\"\n  out: \"25\\n\"\n- in: \"3\\n\"\n  out: \"9\\n\"\n",
+                )?;
+
+                let stdout = run_with_stdout(&["snowchains", "judge"], cwd)?;
+                assert!(stdout.contains("2/2"), "{}", stdout);
+
+                fs::write(dir.join("cases.yml"), "- in: \"5\\n\"\n  out: \"24\\n\"\n")?;
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn judge_concurrency_1() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("read n\nsleep \"0.0$((RANDOM % 5))\"\necho $((n * n))\n"),
+            Some(
+                r#"type: Batch
+match: Lines
+cases:
+  - name: "1"
+    in: "1\n"
+    out: "1\n"
+  - name: "2"
+    in: "2\n"
+    out: "4\n"
+  - name: "3"
+    in: "3\n"
+    out: "9\n"
+  - name: "4"
+    in: "4\n"
+    out: "16\n"
+"#,
+            ),
+            |cwd| {
+                let args = &["snowchains", "judge", "--concurrency-1", "--verbose"];
+                let first = run_with_stdout(args, cwd)?;
+                let second = run_with_stdout(args, cwd)?;
+
+                assert_eq!(first, second);
+                assert!(first.contains(r#"4/4 ("4") Accepted"#), "{}", first);
+                assert!(!first.contains(" ms"), "{}", first);
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn offline() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo 1\n"),
+            Some("type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n"),
+            |cwd| {
+                let err = run(
+                    &[
+                        "snowchains",
+                        "download",
+                        "--offline",
+                        "-s",
+                        "atcoder",
+                        "-c",
+                        "abc100",
+                    ],
+                    cwd,
+                )
+                .unwrap_err();
+                assert!(err.to_string().contains("`--offline`"), "{}", err);
+
+                run(&["snowchains", "judge", "--offline"], cwd)?;
+                // `--offline` is global, so it can also come before the subcommand.
+                run(&["snowchains", "--offline", "judge"], cwd)?;
+                let err = run(&["snowchains", "--offline", "login", "atcoder"], cwd).unwrap_err();
+                assert!(err.to_string().contains("`--offline`"), "{}", err);
+                run(
+                    &[
+                        "snowchains",
+                        "download",
+                        "--offline",
+                        "--dry-run",
+                        "-p",
+                        "a",
+                    ],
+                    cwd,
+                )?;
+
+                // `snowchains.dhall` itself must not fetch anything.
+                fs::write(
+                    cwd.join("snowchains.dhall"),
+                    format!(
+                        "let Unused = https://example.invalid/a.dhall\n\n{}",
+                        snowchains_dhall("None Compile"),
+                    ),
+                )?;
+                let err = run(&["snowchains", "judge", "--offline"], cwd).unwrap_err();
+                assert!(
+                    err.to_string()
+                        .contains("imports `https://example.invalid/a.dhall` without a hash"),
+                    "{}",
+                    err,
+                );
+                assert_eq!(3, crate::exit_code(&err));
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn judge_problem_dir() {
+        with_practice_fixture(Some("None Compile"), Some("echo 2\n"), None, |cwd| {
+            fs::create_dir_all(cwd.join("problems/a"))?;
+            fs::write(cwd.join("problems/a/a.bash"), "echo 1\n")?;
+            fs::write(
+                cwd.join("problems/a/a.yml"),
+                "type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n",
+            )?;
+
+            run(&["snowchains", "judge"], cwd).unwrap_err();
+            run(&["snowchains", "judge", "--problem-dir"], cwd)
+        });
+    }
+
+    #[test]
+    fn retrieve_testcases_dry_run() {
+        with_practice_fixture(Some("None Compile"), None, None, |cwd| {
+            let stdout = run_with_stdout(
+                &["snowchains", "download", "--dry-run", "-p", "a", "B"],
+                cwd,
+            )?;
+
+            let dir = cwd.join(".snowchains/tests/atcoder/practice");
+            assert_eq!(
+                format!(
+                    "B: {}\na: {}\n",
+                    dir.join("b.yml").display(),
+                    dir.join("a.yml").display(),
+                ),
+                stdout,
+            );
+            assert!(!cwd.join(".snowchains").exists());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn submit_dry_run_with_source() {
+        with_practice_fixture(Some("None Compile"), Some("echo 1\n"), None, |cwd| {
+            fs::create_dir(cwd.join("scratch"))?;
+            fs::write(cwd.join("scratch/b.bash"), "echo 2\n")?;
+
+            let submit = |source: &str| {
+                run_with_stdout(
+                    &[
+                        "snowchains",
+                        "submit",
+                        "--dry-run",
+                        "--offline",
+                        "--lang-id",
+                        "4007",
+                        "--source",
+                        source,
+                    ],
+                    cwd,
+                )
+            };
+
+            assert_eq!(
+                format!(
+                    "service: atcoder\ncontest: practice\nproblem: a\nlanguage ID: 4007\nsource: {}\n",
+                    cwd.join("scratch/b.bash").display(),
+                ),
+                submit("scratch/b.bash")?,
+            );
+
+            let err = submit("scratch/c.bash").unwrap_err();
+            assert!(err.to_string().contains("is not a file"), "{}", err);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn judge_run_args() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo \"$#:$1:$2\"\n"),
+            Some("type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"2:--sub:a b\\n\"\n"),
+            |cwd| {
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                run(&["snowchains", "judge", "a", "--", "--sub", "a b"], cwd)
+            },
+        );
+    }
+
+    #[test]
+    fn judge_print_command() {
+        with_practice_fixture(
+            Some(
+                r#"Some { command = Command.Args [ "cp", "a.bash", "a b.out" ], output = "a b.out" }"#,
+            ),
+            None,
+            None,
+            |cwd| {
+                let stdout = run_with_stdout(
+                    &["snowchains", "judge", "--print-command", "--", "x y"],
+                    cwd,
+                )?;
+
+                assert_eq!(
+                    format!(
+                        "cd {}\ncp a.bash 'a b.out'\nbash a.bash 'x y'\n",
+                        shell_escape::unix::escape(cwd.to_string_lossy()),
+                    ),
+                    stdout,
+                );
+                assert!(!cwd.join("a b.out").exists());
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn judge_history() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo 1\n"),
+            Some(
+                "type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n  - in: \"\"\n    out: \"2\\n\"\n",
+            ),
+            |cwd| {
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+                run(&["snowchains", "judge"], cwd).unwrap_err();
+
+                let entries = fs::read_to_string(cwd.join(".snowchains/history.jsonl"))?
+                    .lines()
+                    .map(serde_json::from_str)
+                    .collect::<Result<Vec<HistoryEntry>, _>>()?;
+
+                assert_eq!(2, entries.len());
+                for entry in &entries {
+                    assert_eq!(Some("practice"), entry.contest.as_deref());
+                    assert_eq!("a", entry.problem);
+                    assert_eq!("bash", entry.language);
+                    assert_eq!((2, 1), (entry.total, entry.passed));
+                    assert_eq!("WA", entry.worst);
+                    assert_eq!(1, entry.failed.len());
+                }
+
+                // Reordered, with another test case which would pass.
+                fs::write(
+                    cwd.join(".snowchains/tests/atcoder/practice/a.yml"),
+                    "type: Batch\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"2\\n\"\n  - in: \"\"\n    out: \"1\\n\"\n  - in: \"1\"\n    out: \"1\\n\"\n",
+                )?;
+                run(&["snowchains", "judge", "--failed"], cwd).unwrap_err();
+
+                let last = fs::read_to_string(cwd.join(".snowchains/history.jsonl"))?
+                    .lines()
+                    .last()
+                    .map(serde_json::from_str::<HistoryEntry>)
+                    .unwrap()?;
+                assert_eq!((1, 0), (last.total, last.passed));
+                assert_eq!(entries[1].failed, last.failed);
+
+                let stdout = run_with_stdout(&["snowchains", "history", "-n", "1"], cwd)?;
+                assert_eq!(1, stdout.lines().count());
+                assert!(
+                    stdout.ends_with(" practice/a (bash) 0/1 WA\n"),
+                    "{}",
+                    stdout
+                );
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn convert_testsuite() {
+        with_practice_fixture(
+            Some("None Compile"),
+            None,
+            Some(
+                "type: Batch\ntimelimit: 2s\nmatch: Lines\ncases:\n  - in: \"1\\n\"\n    out: \"2\\n\"\n",
+            ),
+            |cwd| {
+                let path = cwd.join(".snowchains/tests/atcoder/practice/a.yml");
+
+                run(
+                    &["snowchains", "convert", "testsuite", "--to", "interactive"],
+                    cwd,
+                )?;
+                let yaml = fs::read_to_string(&path)?;
+                assert!(
+                    yaml.starts_with("---\ntype: Interactive\ntimelimit: 2s\n"),
+                    "{}",
+                    yaml
+                );
+
+                fs::write(
+                    &path,
+                    fs::read_to_string(&path)? + "tester:\n  cmd: ./tester\n  shell: Bash\n",
+                )?;
+                let err = run(
+                    &["snowchains", "convert", "testsuite", "--to", "batch"],
+                    cwd,
+                )
+                .unwrap_err();
+                assert!(err.to_string().contains("drops [tester]"), "{}", err);
+
+                run(
+                    &[
+                        "snowchains",
+                        "convert",
+                        "testsuite",
+                        "--to",
+                        "batch",
+                        "--force",
+                    ],
+                    cwd,
+                )?;
+                let yaml = fs::read_to_string(&path)?;
+                assert!(
+                    yaml.starts_with("---\ntype: Batch\ntimelimit: 2s\n"),
+                    "{}",
+                    yaml
+                );
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn check_tester_and_list_testcases() {
+        with_practice_fixture(
+            Some("None Compile"),
+            Some("echo 1\nread x\necho 3\n"),
+            Some(
+                r#"type: Interactive
+timelimit: 2s
+tester:
+  cmd: 'read x && [ "$x" = 1 ] && echo 2 && read y && [ "$y" = 3 ]'
+  shell: Bash
+example:
+  - out: "1"
+  - in: "2"
+  - out: "3"
+"#,
+            ),
+            |cwd| {
+                let path = cwd.join(".snowchains/tests/atcoder/practice/a.yml");
+
+                run(&["snowchains", "judge", "--check-tester"], cwd)?;
+
+                let stdout = run_with_stdout(&["snowchains", "list", "testcases"], cwd)?;
+                assert_eq!("Example interaction:\n> 1\n< 2\n> 3\n\n", stdout,);
+
+                fs::write(
+                    &path,
+                    fs::read_to_string(&path)?.replace("- out: \"3\"", "- out: \"4\""),
+                )?;
+                let err = run(&["snowchains", "judge", "--check-tester"], cwd).unwrap_err();
+                assert!(
+                    err.to_string().contains("did not accept `example`"),
+                    "{}",
+                    err
+                );
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn format_testsuite() {
+        with_practice_fixture(
+            Some("None Compile"),
+            None,
+            Some(
+                r#"cases: [{out: "3\n", in: "1 2\n", name: "1"}, {in: "2 3\n", out: "5\n"}]
+match:   Lines
+timelimit:    2000ms
+type: Batch
+"#,
+            ),
+            |cwd| {
+                let path = cwd.join(".snowchains/tests/atcoder/practice/a.yml");
+
+                run(&["snowchains", "format", "testsuite"], cwd)?;
+                assert_eq!(
+                    r#"---
+type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+  - name: "1"
+    in: |
+      1 2
+    out: |
+      3
+  - in: |
+      2 3
+    out: |
+      5
+
+extend: []
+"#,
+                    fs::read_to_string(&path)?,
+                );
+
+                let formatted = fs::read_to_string(&path)?;
+                run(&["snowchains", "format", "testsuite"], cwd)?;
+                assert_eq!(formatted, fs::read_to_string(&path)?);
+
+                fs::write(
+                    &path,
+                    "type: Batch\nmatch: Lines\ncases:\n  - in: \"5\\n\"\n    out: \"25\\n\"\n",
+                )?;
+                run(&["snowchains", "format", "testsuite", "--compact"], cwd)?;
+                let yaml = fs::read_to_string(&path)?;
+                assert!(
+                    yaml.contains("\n  - {in: \"5\\n\", out: \"25\\n\"}\n"),
+                    "{}",
+                    yaml,
+                );
+                Ok(())
+            },
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn prepare() {
+        with_practice_fixture(None, None, None, |cwd| {
+            fs::write(
+                cwd.join("snowchains.dhall"),
+                snowchains_dhall(
+                    r#"Some { command = Command.Args [ "cp", "${target.problem.kebabCase}.bash", "${target.problem.kebabCase}.out" ], output = "${target.problem.kebabCase}.out" }"#,
+                )
+                .replace(
+                    r#"src = "a.bash""#,
+                    r#"src = "${target.problem.kebabCase}.bash""#,
+                ),
+            )?;
+            fs::create_dir_all(cwd.join(".snowchains/tests/atcoder/practice"))?;
+            for problem in &["a", "b"] {
+                fs::write(cwd.join(format!("{}.bash", problem)), "echo 1\n")?;
+                fs::write(
+                    cwd.join(format!(
+                        ".snowchains/tests/atcoder/practice/{}.yml",
+                        problem
+                    )),
+                    "type: Batch\nmatch: Lines\ncases: []\n",
+                )?;
+            }
+
+            // The test suites already exist, so nothing is downloaded.
+            let args = &["snowchains", "prepare", "--offline", "-p", "a", "b"];
+            run(args, cwd)?;
+            let modified = |problem: &str| -> anyhow::Result<_> {
+                fs::metadata(cwd.join(format!("{}.out", problem)))?
+                    .modified()
+                    .map_err(Into::into)
+            };
+            let (a_modified, b_modified) = (modified("a")?, modified("b")?);
+
+            // The binaries are up to date.
+            run(args, cwd)?;
+            assert_eq!(a_modified, modified("a")?);
+            assert_eq!(b_modified, modified("b")?);
+
+            let err =
+                run(&["snowchains", "prepare", "--offline", "-p", "a", "c"], cwd).unwrap_err();
+            assert!(err.to_string().contains("[c]"), "{}", err);
+
+            // The problems cannot be listed offline.
+            let err = run(&["snowchains", "prepare", "--offline"], cwd).unwrap_err();
+            assert!(err.to_string().contains("`--problems`"), "{}", err);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn prepare_with_mock_service() {
+        struct MockService<'a> {
+            cwd: &'a Path,
+            retrieved: &'a mut Vec<Vec<String>>,
+        }
+
+        impl Download for MockService<'_> {
+            fn problem_indexes(
+                &mut self,
+                _: Option<&Path>,
+                _: Option<PlatformKind>,
+                _: Option<&str>,
+                _: Context<impl BufRead, impl Write, impl WriteColor>,
+            ) -> anyhow::Result<Vec<String>> {
+                Ok(vec!["a".to_owned(), "b".to_owned()])
+            }
+
+            fn retrieve(
+                &mut self,
+                opt: OptRetrieveTestcases,
+                _: Context<impl BufRead, impl Write, impl WriteColor>,
+            ) -> anyhow::Result<()> {
+                let problems = opt.problems.unwrap_or_default();
+                for problem in &problems {
+                    fs::write(
+                        self.cwd.join(format!(
+                            ".snowchains/tests/atcoder/practice/{}.yml",
+                            problem,
+                        )),
+                        "type: Batch\nmatch: Lines\ncases: []\n",
+                    )?;
+                }
+                self.retrieved.push(problems);
+                Ok(())
+            }
+        }
+
+        with_practice_fixture(None, None, None, |cwd| {
+            fs::write(
+                cwd.join("snowchains.dhall"),
+                snowchains_dhall(
+                    r#"Some { command = Command.Args [ "cp", "${target.problem.kebabCase}.bash", "${target.problem.kebabCase}.out" ], output = "${target.problem.kebabCase}.out" }"#,
+                )
+                .replace(
+                    r#"src = "a.bash""#,
+                    r#"src = "${target.problem.kebabCase}.bash""#,
+                ),
+            )?;
+            fs::create_dir_all(cwd.join(".snowchains/tests/atcoder/practice"))?;
+            fs::write(cwd.join("a.bash"), "echo 1\n")?;
+            fs::write(cwd.join("b.bash"), "echo 2\n")?;
+
+            let mut retrieved = vec![];
+
+            let prepare = |retrieved: &mut Vec<Vec<String>>| {
+                crate::commands::prepare::run_with(
+                    OptPrepare::from_iter_safe(&["prepare"])?,
+                    false,
+                    Context {
+                        cwd: cwd.to_owned(),
+                        shell: crate::shell::Shell {
+                            stdin: TtyOrPiped::Piped(io::empty()),
+                            stdout: NoColor::new(vec![]),
+                            stderr: NoColor::new(vec![]),
+                            stderr_tty: false,
+                            stdin_process_redirection: Stdio::null,
+                            stdout_process_redirection: Stdio::null,
+                            stderr_process_redirection: Stdio::null,
+                        },
+                    },
+                    MockService { cwd, retrieved },
+                )
+            };
+
+            prepare(&mut retrieved)?;
+            assert_eq!(vec![vec!["a".to_owned(), "b".to_owned()]], retrieved);
+            assert!(cwd.join("a.out").exists());
+            assert!(cwd.join("b.out").exists());
+
+            // Both of the test suites have been downloaded.
+            prepare(&mut retrieved)?;
+            assert_eq!(1, retrieved.len());
+            Ok(())
+        });
+    }
+
+    /// Runs `f` on a thread with `crate::STACK_SIZE`, in a temporary directory with
+    /// `snowchains.dhall` (see `snowchains_dhall`), `a.bash`, and
+    /// `.snowchains/tests/atcoder/practice/a.yml`. Each file is written only if given.
+    fn with_practice_fixture(
+        compile: Option<&str>,
+        a_bash: Option<&str>,
+        a_yml: Option<&str>,
+        f: impl FnOnce(&Path) -> anyhow::Result<()> + Send + 'static,
+    ) {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()
+            .unwrap();
+
+        if let Some(compile) = compile {
+            fs::write(
+                tempdir.path().join("snowchains.dhall"),
+                snowchains_dhall(compile),
+            )
+            .unwrap();
+        }
+        if let Some(a_bash) = a_bash {
+            fs::write(tempdir.path().join("a.bash"), a_bash).unwrap();
+        }
+        if let Some(a_yml) = a_yml {
+            let path = tempdir
+                .path()
+                .join(".snowchains/tests/atcoder/practice/a.yml");
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, a_yml).unwrap();
+        }
+
+        thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(move || {
+                f(tempdir.path())?;
+                tempdir.close()?;
+                anyhow::Ok(())
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+    }
+
+    fn run(args: &[&str], cwd: &Path) -> anyhow::Result<()> {
+        run_with_stdout(args, cwd).map(drop)
+    }
+
+    fn run_with_stdout(args: &[&str], cwd: &Path) -> anyhow::Result<String> {
+        let mut stdout = NoColor::new(vec![]);
+
+        crate::run(
+            Opt::from_iter_safe(args)?,
+            Context {
+                cwd: cwd.to_owned(),
+                shell: crate::shell::Shell {
+                    stdin: TtyOrPiped::Piped(io::empty()),
+                    stdout: &mut stdout,
+                    stderr: NoColor::new(vec![]),
+                    stderr_tty: false,
+                    stdin_process_redirection: Stdio::null,
+                    stdout_process_redirection: Stdio::null,
+                    stderr_process_redirection: Stdio::null,
+                },
+            },
+        )?;
+
+        String::from_utf8(stdout.into_inner()).map_err(Into::into)
+    }
+
+    fn snowchains_dhall(compile: &str) -> String {
+        format!(
+            r#"let CaseConvertedText =
+  {{ lowercase : Text
+  , uppercase : Text
+  , snakeCase : Text
+  , kebabCase : Text
+  , mixedCase : Text
+  , pascalCase : Text
+  }}
+
+let Target =
+  {{ service : < Atcoder | Codeforces | Yukicoder >
+  , contest : Optional CaseConvertedText
+  , problem : CaseConvertedText
+  , mode : < Debug | Release >
+  }}
+
+let Command =
+  < Args : List Text | Script : {{ program : Text, extension : Text, content : Text }} >
+
+let Compile = {{ command : Command, output : Text }}
+
+in  {{ detectServiceFromRelativePathSegments = λ(_ : List Text) → Some "atcoder"
+, detectContestFromRelativePathSegments = λ(_ : List Text) → Some "practice"
+, detectProblemFromRelativePathSegments = λ(_ : List Text) → Some "a"
+, detectLanguageFromRelativePathSegments = λ(_ : List Text) → Some "bash"
+, languages =
+    λ(target : Target) →
+      toMap
+        {{ bash =
+          {{ src = "a.bash"
+          , transpile = None Compile
+          , compile = {}
+          , run = Command.Args [ "bash", "a.bash" ]
+          , languageId = None Text
+          }}
+        }}
+}}
+"#,
+            compile,
+        )
     }
 }
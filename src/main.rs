@@ -61,7 +61,7 @@ fn main() {
             let _ = stderr.write_all(b"\n");
             let _ = stderr.flush();
 
-            process::exit(1);
+            process::exit(snowchains::exit_code(&err));
         }
     });
 }
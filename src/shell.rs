@@ -62,6 +62,13 @@ impl<R: BufRead, W1, W2: Write> Shell<R, W1, W2> {
     }
 }
 
+/// Width of the terminal which stdout is connected to, or 80 if it is not a terminal.
+pub(crate) fn stdout_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| width.into())
+        .unwrap_or(80)
+}
+
 impl<R, W1, W2> Shell<R, W1, W2> {
     pub(crate) fn progress_draw_target(&self) -> ProgressDrawTarget {
         if self.stderr_tty {
@@ -137,6 +144,15 @@ impl<'a> TtyOrPiped<StdinLock<'a>> {
     }
 }
 
+impl<R> TtyOrPiped<R> {
+    pub(crate) fn as_mut(&mut self) -> TtyOrPiped<&mut R> {
+        match self {
+            Self::Tty => TtyOrPiped::Tty,
+            Self::Piped(r) => TtyOrPiped::Piped(r),
+        }
+    }
+}
+
 impl<R: BufRead> TtyOrPiped<R> {
     fn read_reply(&mut self) -> io::Result<String> {
         match self {
@@ -0,0 +1,188 @@
+use anyhow::{bail, Context as _};
+use cookie_store::{Cookie, CookieStore};
+use rusqlite::{Connection, OpenFlags};
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// Imports the unexpired cookies for `url` from `cookies.sqlite` of Firefox.
+///
+/// Firefox keeps the database locked while it is running, so this reads a copy of it.
+pub(crate) fn import_firefox_cookies(
+    cookie_store: &mut CookieStore,
+    db: &Path,
+    url: &Url,
+) -> anyhow::Result<usize> {
+    if !db.exists() {
+        bail!("`{}` does not exist", db.display());
+    }
+
+    let host = url
+        .host_str()
+        .with_context(|| format!("No host: {}", url))?;
+
+    let tempdir = tempfile::Builder::new()
+        .prefix("snowchains-cookies-")
+        .tempdir()?;
+    let copy = tempdir.path().join("cookies.sqlite");
+
+    copy_file(db, &copy)?;
+
+    // Recently written cookies may still be in the write-ahead log.
+    let wal = db.with_file_name(format!(
+        "{}-wal",
+        db.file_name().unwrap_or_default().to_string_lossy(),
+    ));
+    if wal.exists() {
+        copy_file(&wal, &tempdir.path().join("cookies.sqlite-wal"))?;
+    }
+
+    let rows = (|| -> rusqlite::Result<Vec<MozCookie>> {
+        let conn = Connection::open_with_flags(&copy, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+        let mut stmt = conn.prepare(
+            "SELECT name, value, host, path, expiry, isSecure FROM moz_cookies \
+             WHERE host = ?1 OR host = ?2",
+        )?;
+
+        let rows = stmt
+            .query_map([host, &format!(".{}", host)], |row| {
+                Ok(MozCookie {
+                    name: row.get(0)?,
+                    value: row.get(1)?,
+                    host: row.get(2)?,
+                    path: row.get(3)?,
+                    expiry: row.get(4)?,
+                    is_secure: row.get(5)?,
+                })
+            })?
+            .collect();
+        rows
+    })()
+    .with_context(|| format!("Could not read cookies from `{}`", db.display()))?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let mut num_imported = 0;
+
+    for MozCookie {
+        name,
+        value,
+        host: cookie_host,
+        path,
+        expiry,
+        is_secure,
+    } in rows
+    {
+        if expiry <= now {
+            continue;
+        }
+
+        let mut cookie = format!(
+            "{}={}; Path={}; Max-Age={}",
+            name,
+            value,
+            path,
+            expiry - now
+        );
+        if cookie_host.starts_with('.') {
+            cookie += &format!("; Domain={}", cookie_host);
+        }
+        if is_secure {
+            cookie += "; Secure";
+        }
+
+        let cookie = Cookie::parse(cookie, url)
+            .with_context(|| format!("Invalid cookie in `{}`: {:?}", db.display(), name))?;
+
+        cookie_store
+            .insert(cookie, url)
+            .with_context(|| format!("Could not import {:?}", name))?;
+
+        num_imported += 1;
+    }
+
+    tempdir.close()?;
+    return Ok(num_imported);
+
+    struct MozCookie {
+        name: String,
+        value: String,
+        host: String,
+        path: String,
+        expiry: i64,
+        is_secure: bool,
+    }
+
+    fn copy_file(from: &Path, to: &Path) -> anyhow::Result<()> {
+        std::fs::copy(from, to)
+            .map(drop)
+            .with_context(|| format!("Could not copy `{}`", from.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cookie_store::CookieStore;
+    use rusqlite::{params, Connection};
+    use url::Url;
+
+    #[test]
+    fn import_firefox_cookies() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+
+        let db = tempdir.path().join("cookies.sqlite");
+
+        let conn = Connection::open(&db)?;
+        conn.execute(
+            "CREATE TABLE moz_cookies (id INTEGER PRIMARY KEY, name TEXT, value TEXT, host TEXT, \
+             path TEXT, expiry INTEGER, isSecure INTEGER)",
+            [],
+        )?;
+        for (name, value, host, expiry) in &[
+            ("REVEL_SESSION", "foo", "atcoder.jp", i64::from(i32::MAX)),
+            ("REVEL_FLASH", "bar", ".atcoder.jp", i64::from(i32::MAX)),
+            ("expired", "baz", "atcoder.jp", 0),
+            ("JSESSIONID", "qux", "codeforces.com", i64::from(i32::MAX)),
+        ] {
+            conn.execute(
+                "INSERT INTO moz_cookies (name, value, host, path, expiry, isSecure) \
+                 VALUES (?1, ?2, ?3, '/', ?4, 1)",
+                params![name, value, host, expiry],
+            )?;
+        }
+        drop(conn);
+
+        let url = "https://atcoder.jp/".parse::<Url>()?;
+        let mut cookie_store = CookieStore::default();
+
+        let num_imported = super::import_firefox_cookies(&mut cookie_store, &db, &url)?;
+
+        assert_eq!(2, num_imported);
+        assert_eq!(
+            Some("foo"),
+            cookie_store
+                .get("atcoder.jp", "/", "REVEL_SESSION")
+                .map(|c| c.value()),
+        );
+        assert!(cookie_store.get("atcoder.jp", "/", "expired").is_none());
+
+        let err = super::import_firefox_cookies(
+            &mut cookie_store,
+            &tempdir.path().join("nonexistent.sqlite"),
+            &url,
+        )
+        .unwrap_err();
+        assert!(err.to_string().ends_with("does not exist"));
+
+        tempdir.close()?;
+        Ok(())
+    }
+}
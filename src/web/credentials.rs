@@ -1,9 +1,10 @@
 use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
+use snowchains_core::web::LoginOutcome;
 use std::{
     cell::RefCell,
     io::{BufRead, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 pub(crate) fn cookie_store_path() -> anyhow::Result<PathBuf> {
@@ -85,14 +86,27 @@ pub(crate) fn codeforces_api_key_and_secret(
 pub(crate) fn yukicoder_api_key(
     shell: &mut crate::shell::Shell<impl BufRead, impl Sized, impl Write>,
 ) -> anyhow::Result<String> {
-    let path = token_path("yukicoder.json")?;
+    yukicoder_login(shell).map(|(api_key, _)| api_key)
+}
 
+pub(crate) fn yukicoder_login(
+    shell: &mut crate::shell::Shell<impl BufRead, impl Sized, impl Write>,
+) -> anyhow::Result<(String, LoginOutcome)> {
+    load_or_ask_api_key(&token_path("yukicoder.json")?, "yukicoder API key: ", shell)
+}
+
+/// Asks the API key only if it has not been saved to `path` yet.
+fn load_or_ask_api_key(
+    path: &Path,
+    prompt: &'static str,
+    shell: &mut crate::shell::Shell<impl BufRead, impl Sized, impl Write>,
+) -> anyhow::Result<(String, LoginOutcome)> {
     if path.exists() {
-        crate::fs::read_json(path)
+        Ok((crate::fs::read_json(path)?, LoginOutcome::AlreadyLoggedIn))
     } else {
-        let api_key = shell.read_password("yukicoder API key: ")?;
+        let api_key = shell.read_password(prompt)?;
         crate::fs::write_json(path, &api_key, true)?;
-        Ok(api_key)
+        Ok((api_key, LoginOutcome::Success))
     }
 }
 
@@ -105,3 +119,52 @@ fn token_path(file_name: &str) -> anyhow::Result<PathBuf> {
         .join("tokens")
         .join(file_name))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::shell::{Shell, TtyOrPiped};
+    use snowchains_core::web::LoginOutcome;
+    use std::{io::BufRead, process::Stdio};
+    use termcolor::NoColor;
+
+    #[test]
+    fn load_or_ask_api_key() -> anyhow::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-tests-")
+            .tempdir()?;
+        let path = tempdir.path().join("tokens").join("yukicoder.json");
+
+        let shell = |stdin: &'static [u8]| Shell {
+            stdin: TtyOrPiped::Piped(stdin),
+            stdout: NoColor::new(vec![]),
+            stderr: NoColor::new(vec![]),
+            stderr_tty: false,
+            stdin_process_redirection: Stdio::null,
+            stdout_process_redirection: Stdio::null,
+            stderr_process_redirection: Stdio::null,
+        };
+
+        let mut first = shell(b"foo\n");
+        assert_eq!(
+            ("foo".to_owned(), LoginOutcome::Success),
+            super::load_or_ask_api_key(&path, "API key: ", &mut first)?,
+        );
+        assert_eq!(b"API key: ", &**first.stderr.get_ref());
+
+        let mut second = shell(b"bar\n");
+        assert_eq!(
+            ("foo".to_owned(), LoginOutcome::AlreadyLoggedIn),
+            super::load_or_ask_api_key(&path, "API key: ", &mut second)?,
+        );
+        assert!(second.stderr.get_ref().is_empty());
+        assert_eq!(
+            b"bar\n",
+            match &mut second.stdin {
+                TtyOrPiped::Piped(stdin) => stdin.fill_buf()?,
+                TtyOrPiped::Tty => unreachable!(),
+            },
+        );
+
+        tempdir.close().map_err(Into::into)
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "browser-cookies")]
+pub(crate) mod browser_cookies;
 pub(crate) mod credentials;
 
 use heck::{CamelCase as _, KebabCase as _, MixedCase as _, SnakeCase as _};